@@ -0,0 +1,8 @@
+-- Add up migration script here
+ALTER TABLE records ADD COLUMN video_exempt BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Grandfather in all records that currently lack a video. The audit trigger is bypassed, as it would otherwise attribute an
+-- (empty) modification of every such record to the fallback user (and this isn't a change worth auditing anyway).
+ALTER TABLE records DISABLE TRIGGER record_modification_trigger;
+UPDATE records SET video_exempt = TRUE WHERE video IS NULL;
+ALTER TABLE records ENABLE TRIGGER record_modification_trigger;
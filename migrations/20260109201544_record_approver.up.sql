@@ -0,0 +1,3 @@
+-- Add up migration script here
+-- Like the audit log, this isn't a foreign key, so that approvals by deleted users stay attributed
+ALTER TABLE records ADD COLUMN approved_by INTEGER;
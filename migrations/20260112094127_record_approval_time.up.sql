@@ -0,0 +1,13 @@
+-- Add up migration script here
+ALTER TABLE records ADD COLUMN approved_at TIMESTAMP WITHOUT TIME ZONE;
+
+-- Backfill from the audit log: an approved record was approved either by its most recent status change, or upon being added
+-- with approved status. The audit trigger is bypassed so that the backfill does not add an empty modification entry for every record.
+ALTER TABLE records DISABLE TRIGGER record_modification_trigger;
+UPDATE records
+SET approved_at = COALESCE(
+    (SELECT MAX(time) FROM record_modifications WHERE record_modifications.id = records.id AND record_modifications.status_ IS NOT NULL),
+    (SELECT MIN(time) FROM record_additions WHERE record_additions.id = records.id)
+)
+WHERE status_ = 'APPROVED';
+ALTER TABLE records ENABLE TRIGGER record_modification_trigger;
@@ -0,0 +1,22 @@
+-- Add down migration script here
+
+-- CREATE OR REPLACE cannot drop columns from a view
+DROP VIEW ranked_players;
+
+CREATE VIEW ranked_players AS
+SELECT
+    ROW_NUMBER() OVER(ORDER BY rank, id) AS index,
+    rank,
+    id, name, players.score, subdivision,
+    nationalities.iso_country_code,
+    nationalities.nation,
+    nationalities.continent
+FROM players
+LEFT OUTER JOIN nationalities
+             ON players.nationality = nationalities.iso_country_code
+NATURAL JOIN player_ranks;
+
+ALTER TABLE players DROP COLUMN search_key;
+ALTER TABLE demons DROP COLUMN search_key;
+
+DROP FUNCTION search_key(TEXT);
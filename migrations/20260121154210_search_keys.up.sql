@@ -0,0 +1,25 @@
+-- Add up migration script here
+
+-- Normalized form of a name used for searching: emoji (including the variation selectors and joiners they are built from)
+-- are removed, whitespace is collapsed and the result is lowercased. Display names are never changed.
+CREATE FUNCTION search_key(name TEXT) RETURNS TEXT AS
+$$
+    SELECT btrim(regexp_replace(regexp_replace(lower(name), '[\u20E3\u2300-\u23FF\u2600-\u27BF\u2B00-\u2BFF\uFE0F\u200D\U0001F000-\U0001FAFF]', '', 'g'), '\s+', ' ', 'g'))
+$$ LANGUAGE SQL IMMUTABLE;
+
+ALTER TABLE demons ADD COLUMN search_key TEXT GENERATED ALWAYS AS (search_key(name::TEXT)) STORED;
+ALTER TABLE players ADD COLUMN search_key TEXT GENERATED ALWAYS AS (search_key(name::TEXT)) STORED;
+
+CREATE OR REPLACE VIEW ranked_players AS
+SELECT
+    ROW_NUMBER() OVER(ORDER BY rank, id) AS index,
+    rank,
+    id, name, players.score, subdivision,
+    nationalities.iso_country_code,
+    nationalities.nation,
+    nationalities.continent,
+    players.search_key
+FROM players
+LEFT OUTER JOIN nationalities
+             ON players.nationality = nationalities.iso_country_code
+NATURAL JOIN player_ranks;
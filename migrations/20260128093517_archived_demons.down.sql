@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE demons DROP COLUMN submissions_closed;
+ALTER TABLE demons DROP COLUMN archived;
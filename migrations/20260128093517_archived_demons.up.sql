@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE demons ADD COLUMN archived BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE demons ADD COLUMN submissions_closed BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,17 @@
+-- Add up migration script here
+ALTER TABLE demons ADD COLUMN scores_from TIMESTAMP WITHOUT TIME ZONE;
+
+-- Demons still in their grace period do not give points to anyone, not even their verifier
+CREATE OR REPLACE VIEW score_giving AS
+    SELECT records.progress, demons.position, demons.requirement, records.player
+    FROM records
+    INNER JOIN demons
+    ON demons.id = records.demon
+    WHERE records.status_ = 'APPROVED' AND (demons.position <= 75 OR records.progress = 100)
+      AND (demons.scores_from IS NULL OR demons.scores_from <= (now() AT TIME ZONE 'utc'))
+
+    UNION
+
+    SELECT 100, demons.position, demons.requirement, demons.verifier
+    FROM demons
+    WHERE demons.scores_from IS NULL OR demons.scores_from <= (now() AT TIME ZONE 'utc');
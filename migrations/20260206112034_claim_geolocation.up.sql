@@ -0,0 +1,2 @@
+-- Add up migration script here
+ALTER TABLE player_claims ADD COLUMN geolocated_country VARCHAR(2) NULL;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX demons_search_key_trgm_idx;
+
+DROP EXTENSION IF EXISTS pg_trgm;
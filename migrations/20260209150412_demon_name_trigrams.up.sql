@@ -0,0 +1,4 @@
+-- Add up migration script here
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX demons_search_key_trgm_idx ON demons USING gin (search_key gin_trgm_ops);
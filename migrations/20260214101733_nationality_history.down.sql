@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TRIGGER nationality_change_trigger ON players;
+DROP FUNCTION audit_nationality_change();
+DROP TABLE nationality_changes;
@@ -0,0 +1,23 @@
+-- Add up migration script here
+CREATE TABLE nationality_changes (
+    id INTEGER NOT NULL,
+    old_nationality VARCHAR(2),
+    old_subdivision VARCHAR(3),
+    new_nationality VARCHAR(2),
+    new_subdivision VARCHAR(3)
+) INHERITS (audit_log2);
+
+-- player_modifications only stores the old value of changed columns, and compares with <>, which
+-- misses changes from or to NULL. Keep a full before/after record for nationalities instead.
+CREATE FUNCTION audit_nationality_change() RETURNS trigger AS $nationality_change_trigger$
+BEGIN
+    INSERT INTO nationality_changes (userid, id, old_nationality, old_subdivision, new_nationality, new_subdivision)
+        (SELECT id, NEW.id, OLD.nationality, OLD.subdivision, NEW.nationality, NEW.subdivision FROM active_user LIMIT 1);
+
+    RETURN NEW;
+END;
+$nationality_change_trigger$ LANGUAGE plpgsql;
+
+CREATE TRIGGER nationality_change_trigger AFTER UPDATE ON players FOR EACH ROW
+    WHEN (OLD.nationality IS DISTINCT FROM NEW.nationality OR OLD.subdivision IS DISTINCT FROM NEW.subdivision)
+    EXECUTE PROCEDURE audit_nationality_change();
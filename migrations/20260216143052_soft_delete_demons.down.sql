@@ -0,0 +1,59 @@
+-- Add down migration script here
+CREATE OR REPLACE VIEW score_giving AS
+    SELECT records.progress, demons.position, demons.requirement, records.player
+    FROM records
+    INNER JOIN demons
+    ON demons.id = records.demon
+    WHERE records.status_ = 'APPROVED' AND (demons.position <= 75 OR records.progress = 100)
+      AND (demons.scores_from IS NULL OR demons.scores_from <= (now() AT TIME ZONE 'utc'))
+
+    UNION
+
+    SELECT 100, demons.position, demons.requirement, demons.verifier
+    FROM demons
+    WHERE demons.scores_from IS NULL OR demons.scores_from <= (now() AT TIME ZONE 'utc');
+
+CREATE OR REPLACE FUNCTION list_at(TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      name CITEXT,
+                      position_ SMALLINT,
+                      requirement SMALLINT,
+                      video VARCHAR(200),
+                      thumbnail TEXT,
+                      verifier INTEGER,
+                      publisher INTEGER,
+                      id INTEGER,
+                      level_id BIGINT,
+                      difficulty level_difficulty,
+                      current_position SMALLINT
+                  )
+AS $$
+SELECT name, CASE WHEN t.position IS NULL THEN demons.position ELSE t.position END, requirement, video, thumbnail, verifier, publisher, demons.id, level_id, difficulty, demons.position AS current_position
+FROM demons
+         LEFT OUTER JOIN (
+    SELECT DISTINCT ON (id) id, position
+    FROM demon_modifications
+    WHERE time >= $1 AND position != -1
+    ORDER BY id, time
+) t
+                         ON demons.id = t.id
+WHERE NOT EXISTS (SELECT 1 FROM demon_additions WHERE demon_additions.id = demons.id AND time >= $1)
+$$
+    LANGUAGE SQL
+    STABLE;
+
+-- Without soft deletion, deleted demons can only be put back onto the list. They go to the end, so
+-- that positions are unique again.
+UPDATE demons
+SET position = q.new_position
+FROM (
+    SELECT id, (SELECT MAX(position) FROM demons WHERE deleted_at IS NULL) + ROW_NUMBER() OVER (ORDER BY deleted_at) AS new_position
+    FROM demons
+    WHERE deleted_at IS NOT NULL
+) q
+WHERE demons.id = q.id;
+
+ALTER TABLE demons DROP CONSTRAINT unique_position;
+ALTER TABLE demons ADD CONSTRAINT unique_position UNIQUE (position) DEFERRABLE INITIALLY IMMEDIATE;
+
+ALTER TABLE demons DROP COLUMN deleted_at;
@@ -0,0 +1,14 @@
+-- Add up migration script here
+ALTER TABLE creators ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+
+-- For existing demons, order the creators by when they were added according to the audit log. Creators added before
+-- the audit log existed come first, in no particular order
+UPDATE creators SET priority = ordered.priority
+FROM (
+    SELECT creators.demon, creators.creator,
+           ROW_NUMBER() OVER (PARTITION BY creators.demon ORDER BY MIN(creator_additions.audit_id) NULLS FIRST, creators.creator) - 1 AS priority
+    FROM creators
+    LEFT OUTER JOIN creator_additions ON creator_additions.demon = creators.demon AND creator_additions.creator = creators.creator
+    GROUP BY creators.demon, creators.creator
+) AS ordered
+WHERE ordered.demon = creators.demon AND ordered.creator = creators.creator;
@@ -0,0 +1,114 @@
+-- Add up migration script here
+
+-- Mirrors the DIFFICULTY_MULTIPLIERS configuration, which is written here on startup. Tiers without
+-- an entry have a multiplier of 1
+CREATE TABLE difficulty_multipliers (
+    difficulty level_difficulty PRIMARY KEY,
+    multiplier DOUBLE PRECISION NOT NULL CHECK (multiplier >= 0)
+);
+
+-- New columns can only go at the end of a view
+CREATE OR REPLACE VIEW score_giving AS
+    SELECT records.progress, demons.position, demons.requirement, records.player, COALESCE(difficulty_multipliers.multiplier, 1.0) AS multiplier
+    FROM records
+    INNER JOIN demons
+    ON demons.id = records.demon
+    LEFT OUTER JOIN difficulty_multipliers
+    ON difficulty_multipliers.difficulty = demons.difficulty
+    WHERE records.status_ = 'APPROVED' AND (demons.position <= 75 OR records.progress = 100)
+      AND (demons.scores_from IS NULL OR demons.scores_from <= (now() AT TIME ZONE 'utc'))
+      AND demons.deleted_at IS NULL
+
+    UNION
+
+    SELECT 100, demons.position, demons.requirement, demons.verifier, COALESCE(difficulty_multipliers.multiplier, 1.0)
+    FROM demons
+    LEFT OUTER JOIN difficulty_multipliers
+    ON difficulty_multipliers.difficulty = demons.difficulty
+    WHERE (demons.scores_from IS NULL OR demons.scores_from <= (now() AT TIME ZONE 'utc'))
+      AND demons.deleted_at IS NULL;
+
+CREATE OR REPLACE FUNCTION score_of_player(player_id INTEGER)
+RETURNS DOUBLE PRECISION AS $$
+    SELECT SUM(record_score(progress, position, 150, requirement) * multiplier)
+    FROM score_giving
+    WHERE player = player_id
+$$ LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION score_of_nation(iso_country_code VARCHAR(2))
+RETURNS DOUBLE PRECISION AS $$
+    SELECT SUM(record_score(q.progress, q.position, 150, q.requirement) * q.multiplier)
+    FROM (
+        SELECT DISTINCT ON (position) * from score_giving
+        INNER JOIN players
+                ON players.id=player
+        WHERE players.nationality = iso_country_code
+        ORDER BY position, progress DESC
+    ) q
+$$ LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION score_of_subdivision(iso_country_code VARCHAR(2), iso_code VARCHAR(3))
+RETURNS DOUBLE PRECISION AS $$
+    SELECT SUM(record_score(q.progress, q.position, 150, q.requirement) * q.multiplier)
+    FROM (
+        SELECT DISTINCT ON (position) * from score_giving
+        INNER JOIN players
+                ON players.id=player
+        WHERE players.nationality = iso_country_code
+          AND players.subdivision = iso_code
+        ORDER BY position, progress DESC
+    ) q
+$$ LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION recompute_player_scores() RETURNS void AS $$
+    UPDATE players
+    SET score = coalesce(q.score, 0)
+    FROM players p
+        LEFT OUTER JOIN (
+            SELECT player, SUM(record_score(progress, position, 150, requirement) * multiplier) as score
+            FROM score_giving
+            GROUP BY player
+        ) q
+        ON q.player = p.id
+    WHERE players.id = p.id;
+$$ LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION recompute_nation_scores() RETURNS void AS $$
+    UPDATE nationalities
+    SET score = COALESCE(p.sum, 0)
+    FROM nationalities n
+        LEFT OUTER JOIN (
+            SELECT nationality, SUM(record_score(q.progress, q.position, 150, q.requirement) * q.multiplier)
+            FROM (
+                SELECT DISTINCT ON (position, nationality) * from score_giving
+                INNER JOIN players
+                        ON players.id=player
+                WHERE players.nationality IS NOT NULL
+                ORDER BY players.nationality, position, progress DESC
+            ) q
+            GROUP BY nationality
+        ) p
+        ON p.nationality = n.iso_country_code
+    WHERE n.iso_country_code = nationalities.iso_country_code
+$$ LANGUAGE SQL;
+
+CREATE OR REPLACE FUNCTION recompute_subdivision_scores() RETURNS void AS $$
+    UPDATE subdivisions
+    SET score = COALESCE(p.sum, 0)
+    FROM subdivisions s
+        LEFT OUTER JOIN (
+            SELECT nationality, subdivision, SUM(record_score(q.progress, q.position, 150, q.requirement) * q.multiplier)
+            FROM (
+                SELECT DISTINCT ON (position, nationality, subdivision) * from score_giving
+                INNER JOIN players
+                        ON players.id=player
+                WHERE players.nationality IS NOT NULL
+                AND players.subdivision IS NOT NULL
+                ORDER BY players.nationality, players.subdivision, position, progress DESC
+            ) q
+            GROUP BY nationality, subdivision
+        ) p
+        ON s.nation = p.nationality AND s.iso_code = p.subdivision
+    WHERE s.nation = subdivisions.nation
+      AND s.iso_code = subdivisions.iso_code
+$$ LANGUAGE SQL;
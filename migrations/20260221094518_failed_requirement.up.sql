@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- The requirement a rejected record fell below, for records that were rejected because the requirement of their demon was
+-- raised past their progress. NULL for records that were rejected for any other reason (or are not rejected at all).
+ALTER TABLE records ADD COLUMN failed_requirement SMALLINT;
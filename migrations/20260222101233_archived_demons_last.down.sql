@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+-- Nothing to do, the original positions of archived demons are not known anymore (and keeping them at the bottom is valid
+-- either way)
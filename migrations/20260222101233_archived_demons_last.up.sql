@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+-- Archived demons are no longer part of the list proper, and are kept below all non-archived demons. Move those that
+-- were archived in place to the bottom, keeping the relative order of both the archived and the non-archived demons
+UPDATE demons SET position = ordered.position
+FROM (
+    SELECT id, (ROW_NUMBER() OVER (ORDER BY archived, position))::SMALLINT AS position FROM demons WHERE deleted_at IS NULL
+) AS ordered
+WHERE demons.id = ordered.id AND demons.position <> ordered.position;
+
+SELECT recompute_player_scores();
+SELECT recompute_nation_scores();
+SELECT recompute_subdivision_scores();
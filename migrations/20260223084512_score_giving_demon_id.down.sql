@@ -0,0 +1,22 @@
+-- Add down migration script here
+DROP VIEW score_giving;
+
+CREATE VIEW score_giving AS
+    SELECT records.progress, demons.position, demons.requirement, records.player, COALESCE(difficulty_multipliers.multiplier, 1.0) AS multiplier
+    FROM records
+    INNER JOIN demons
+    ON demons.id = records.demon
+    LEFT OUTER JOIN difficulty_multipliers
+    ON difficulty_multipliers.difficulty = demons.difficulty
+    WHERE records.status_ = 'APPROVED' AND (demons.position <= 75 OR records.progress = 100)
+      AND (demons.scores_from IS NULL OR demons.scores_from <= (now() AT TIME ZONE 'utc'))
+      AND demons.deleted_at IS NULL
+
+    UNION
+
+    SELECT 100, demons.position, demons.requirement, demons.verifier, COALESCE(difficulty_multipliers.multiplier, 1.0)
+    FROM demons
+    LEFT OUTER JOIN difficulty_multipliers
+    ON difficulty_multipliers.difficulty = demons.difficulty
+    WHERE (demons.scores_from IS NULL OR demons.scores_from <= (now() AT TIME ZONE 'utc'))
+      AND demons.deleted_at IS NULL;
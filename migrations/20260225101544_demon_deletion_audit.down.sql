@@ -0,0 +1,60 @@
+-- Add down migration script here
+CREATE OR REPLACE FUNCTION audit_demon_modification() RETURNS trigger AS $demon_modification_trigger$
+DECLARE
+    name_change CITEXT;
+    position_change SMALLINT;
+    requirement_change SMALLINT;
+    video_change VARCHAR(200);
+    thumbnail_change TEXT;
+    verifier_change INT;
+    publisher_change INT;
+    difficulty_change level_difficulty;
+BEGIN
+    IF (OLD.name <> NEW.name) THEN
+        name_change = OLD.name;
+    END IF;
+
+    IF (OLD.position <> NEW.position) THEN
+        position_change = OLD.position;
+    END IF;
+
+    IF (OLD.requirement <> NEW.requirement) THEN
+        requirement_change = OLD.requirement;
+    END IF;
+
+    IF (OLD.video <> NEW.video) THEN
+        video_change = OLD.video;
+    END IF;
+
+    IF (OLD.thumbnail <> NEW.thumbnail) THEN
+        thumbnail_change = OLD.thumbnail;
+    END IF;
+
+    IF (OLD.verifier <> NEW.verifier) THEN
+        verifier_change = OLD.verifier;
+    END IF;
+
+    IF (OLD.publisher <> NEW.publisher) THEN
+        publisher_change = OLD.publisher;
+    END IF;
+
+    IF (OLD.difficulty <> NEW.difficulty) THEN
+        difficulty_change = OLD.difficulty;
+    END IF;
+
+    -- Updates of columns that are not audited (such as the end of a grace period) would otherwise leave an empty
+    -- modification, attributed to whoever happens to be the active user
+    IF name_change IS NULL AND position_change IS NULL AND requirement_change IS NULL AND video_change IS NULL
+        AND thumbnail_change IS NULL AND verifier_change IS NULL AND publisher_change IS NULL AND difficulty_change IS NULL THEN
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO demon_modifications (userid, name, position, requirement, video, verifier, publisher, thumbnail, difficulty, id)
+        (SELECT id, name_change, position_change, requirement_change, video_change, verifier_change, publisher_change, thumbnail_change, difficulty_change, NEW.id
+         FROM active_user LIMIT 1);
+
+    RETURN NEW;
+END;
+$demon_modification_trigger$ LANGUAGE plpgsql;
+
+ALTER TABLE demon_modifications DROP COLUMN deleted;
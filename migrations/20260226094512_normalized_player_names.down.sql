@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX players_normalized_name_idx;
+DROP FUNCTION normalized_player_name(TEXT);
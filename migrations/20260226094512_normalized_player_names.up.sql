@@ -0,0 +1,17 @@
+-- Add up migration script here
+-- The form player names are compared in when NORMALIZE_PLAYER_NAMES is set: NFKC normalized, case folded, and with cyrillic
+-- and greek look-alikes replaced by the latin letter they imitate.
+--
+-- lower() only folds the letters the database's locale knows about (just ASCII under the C locale), and the lower case
+-- forms of some capital look-alikes do not look latin at all (e.g. cyrillic "Н" and "н"). Capital look-alikes are thus
+-- replaced by capital latin letters before case folding, and the remaining lower case ones afterwards.
+CREATE FUNCTION normalized_player_name(name TEXT) RETURNS TEXT AS $$
+    SELECT translate(
+        lower(translate(normalize(name, NFKC), 'АВЕКМНОРСТХУІЈЅԀԚԜҺӀΑΒΕΖΗΙΚΜΝΟΡΤΥΧ', 'ABEKMHOPCTXYIJSDQWHLABEZHIKMNOPTYX')),
+        'аеорсхуіјѕԁԛԝһӏονιρ',
+        'aeopcxyijsdqwhlovip'
+    )
+$$ LANGUAGE SQL IMMUTABLE STRICT PARALLEL SAFE;
+
+-- Resolving names with normalization compares against every player, which would otherwise be a sequential scan
+CREATE INDEX players_normalized_name_idx ON players (normalized_player_name(name::TEXT));
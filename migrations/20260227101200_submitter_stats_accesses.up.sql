@@ -0,0 +1,5 @@
+-- Add up migration script here
+-- Submitter statistics are derived from data tied to IP addresses, so every access to them is recorded
+CREATE TABLE submitter_stats_accesses (
+    since TIMESTAMP WITHOUT TIME ZONE NOT NULL
+) INHERITS (audit_log2);
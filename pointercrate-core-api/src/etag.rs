@@ -53,7 +53,10 @@ impl<'r, T: Taggable> Responder<'r, 'static> for Tagged<T> {
             Method::Get => {
                 if let Some(if_none_match) = request.headers().get_one("if-none-match") {
                     if if_none_match.contains(&response_etag) {
-                        return Response::build().status(Status::NotModified).ok();
+                        return Response::build()
+                            .status(Status::NotModified)
+                            .raw_header("etag", response_etag)
+                            .ok();
                     }
                 }
             },
@@ -5,22 +5,60 @@ use pointercrate_core::{error::CoreError};
 use rocket::{
     fairing::{Fairing, Info, Kind},
     http::Method,
-    routes, uri, Build, Data, Request, Rocket,
+    routes, uri, Build, Data, Request, Response, Rocket, State,
 };
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Name of the header added to every response while maintenance mode is active, so that frontends can tell
+/// that the site is read-only even if they only ever make GET requests
+pub const MAINTENANCE_HEADER: &str = "X-Pointercrate-Maintenance";
 
-/// Rocket fairing that causes all mutating requests (aka non-GET requests) to return 503 SERVICE UNAVAILABLE if `.0` is `true`.
+/// Switch for turning maintenance mode on and off while the server is running
+///
+/// The [`MaintenanceFairing`] puts this into rocket's managed state, so request handlers can get hold of it
+/// via `&State<Maintenance>`. All clones refer to the same switch.
+#[derive(Clone, Default)]
+pub struct Maintenance(Arc<AtomicBool>);
+
+impl Maintenance {
+    pub fn is_active(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub fn set_active(&self, read_only: bool) {
+        if self.0.swap(read_only, Ordering::Relaxed) != read_only {
+            if read_only {
+                log::warn!("Maintenance mode activated! All non-GET requests will receive a 503 response!");
+            } else {
+                log::info!("Maintenance mode deactivated");
+            }
+        }
+    }
+}
+
+/// Rocket fairing that causes all mutating requests (aka non-GET requests) to return 503 SERVICE UNAVAILABLE while
+/// its [`Maintenance`] switch is active. Additionally, all responses get the [`MAINTENANCE_HEADER`] in that case.
 ///
 /// Works in a very hacky way, as rocket does not allow fairing to terminate requests. Thus we instead rewrite the
 /// request on the fly to be a GET /maintenance, which is an endpoint that unconditionally returns a 503 response.
-/// This endpoint only exists when maintainence mode is active (it is dynamically mounted in `on_ignite`).
+/// As maintenance mode can be turned on at any time, this endpoint is always mounted, but it pretends to not exist
+/// while maintenance mode is inactive.
 ///
 /// Idea taken from https://stackoverflow.com/questions/70011965/global-authentication-authorization-in-rocket-based-on-a-header
 #[derive(Default)]
-pub struct MaintenanceFairing(bool);
+pub struct MaintenanceFairing(Maintenance);
 
 impl MaintenanceFairing {
     pub fn new(read_only: bool) -> Self {
-        MaintenanceFairing(read_only)
+        MaintenanceFairing(Maintenance(Arc::new(AtomicBool::new(read_only))))
+    }
+
+    /// The switch controlling this fairing, for toggling maintenance mode from outside of request handlers
+    pub fn maintenance(&self) -> Maintenance {
+        self.0.clone()
     }
 }
 
@@ -29,27 +67,36 @@ impl Fairing for MaintenanceFairing {
     fn info(&self) -> Info {
         Info {
             name: "Maintenance",
-            kind: Kind::Ignite | Kind::Request,
+            kind: Kind::Ignite | Kind::Request | Kind::Response,
         }
     }
 
-    async fn on_ignite(&self, mut rocket: Rocket<Build>) -> rocket::fairing::Result {
-        if self.0 {
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> rocket::fairing::Result {
+        if self.0.is_active() {
             log::warn!("Maintenance mode activated! All non-GET requests will receive a 503 response!");
-            rocket = rocket.mount("/", routes![maintenance]);
         }
-        Ok(rocket)
+        Ok(rocket.manage(self.0.clone()).mount("/", routes![maintenance]))
     }
 
     async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
-        if self.0 && request.method() != Method::Get {
-            request.set_uri(uri!("/maintenance"));
+        if self.0.is_active() && request.method() != Method::Get {
+            request.set_uri(uri!("/maintenance/"));
             request.set_method(Method::Get);
         }
     }
+
+    async fn on_response<'r>(&self, _: &'r Request<'_>, response: &mut Response<'r>) {
+        if self.0.is_active() {
+            response.set_raw_header(MAINTENANCE_HEADER, "true");
+        }
+    }
 }
 
 #[rocket::get("/maintenance/")]
-async fn maintenance() -> Result<()> {
-    Err(CoreError::ReadOnlyMaintenance.into())
-}
\ No newline at end of file
+async fn maintenance(maintenance: &State<Maintenance>) -> Result<()> {
+    if maintenance.is_active() {
+        Err(CoreError::ReadOnlyMaintenance.into())
+    } else {
+        Err(CoreError::NotFound.into())
+    }
+}
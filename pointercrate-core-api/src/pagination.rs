@@ -12,7 +12,9 @@ use crate::response::Response2;
 #[derive(Debug)]
 pub struct LinksBuilder {
     endpoint: &'static str,
-    rels: BTreeMap<&'static str, PaginationParameters>,
+    /// The parameters of each link, together with the anchors (before, after) passed to
+    /// [`PaginationQuery::with_anchors`] when generating it
+    rels: BTreeMap<&'static str, (PaginationParameters, Option<i32>, Option<i32>)>,
 }
 
 impl LinksBuilder {
@@ -26,11 +28,15 @@ impl LinksBuilder {
     pub fn with_first(mut self, id_before_first: i32) -> Self {
         self.rels.insert(
             "first",
-            PaginationParameters {
-                after: Some(id_before_first),
-                before: None,
-                ..Default::default()
-            },
+            (
+                PaginationParameters {
+                    after: Some(id_before_first),
+                    before: None,
+                    ..Default::default()
+                },
+                None,
+                None,
+            ),
         );
         self
     }
@@ -38,35 +44,59 @@ impl LinksBuilder {
     pub fn with_last(mut self, id_after_last: i32) -> Self {
         self.rels.insert(
             "last",
-            PaginationParameters {
-                after: None,
-                before: Some(id_after_last),
-                ..Default::default()
-            },
+            (
+                PaginationParameters {
+                    after: None,
+                    before: Some(id_after_last),
+                    ..Default::default()
+                },
+                None,
+                None,
+            ),
         );
         self
     }
 
-    pub fn with_next(mut self, after: i32) -> Self {
+    pub fn with_next(self, after: i32) -> Self {
+        self.with_anchored_next(after, None)
+    }
+
+    /// Like [`LinksBuilder::with_next`], but additionally anchors the link on the object with the
+    /// given [`Paginatable::anchor_id`] (usually the last object on the current page)
+    pub fn with_anchored_next(mut self, after: i32, anchor: Option<i32>) -> Self {
         self.rels.insert(
             "next",
-            PaginationParameters {
-                after: Some(after),
-                before: None,
-                ..Default::default()
-            },
+            (
+                PaginationParameters {
+                    after: Some(after),
+                    before: None,
+                    ..Default::default()
+                },
+                None,
+                anchor,
+            ),
         );
         self
     }
 
-    pub fn with_previous(mut self, before: i32) -> Self {
+    pub fn with_previous(self, before: i32) -> Self {
+        self.with_anchored_previous(before, None)
+    }
+
+    /// Like [`LinksBuilder::with_previous`], but additionally anchors the link on the object with
+    /// the given [`Paginatable::anchor_id`] (usually the first object on the current page)
+    pub fn with_anchored_previous(mut self, before: i32, anchor: Option<i32>) -> Self {
         self.rels.insert(
             "prev",
-            PaginationParameters {
-                after: None,
-                before: Some(before),
-                ..Default::default()
-            },
+            (
+                PaginationParameters {
+                    after: None,
+                    before: Some(before),
+                    ..Default::default()
+                },
+                anchor,
+                None,
+            ),
         );
         self
     }
@@ -77,19 +107,21 @@ impl LinksBuilder {
         // The build functions set a default value for "limit" - copy the actual value from the given base here
         let limit = base.parameters().limit;
 
-        for (rel, param) in &self.rels {
+        for (rel, (param, before_anchor, after_anchor)) in &self.rels {
             if !is_first {
                 buf.push(',');
             }
             is_first = false;
 
-            let query_string =
-                serde_urlencoded::to_string(base.with_parameters(PaginationParameters { limit, ..*param })).map_err(|err| {
-                    CoreError::internal_server_error(format!(
-                        "Failed to serialize pagination query string: {:?}. Base: {:?}, Builder: {:?}, Current Rel: {}",
-                        err, base, self, rel
-                    ))
-                })?;
+            let query = base
+                .with_parameters(PaginationParameters { limit, ..*param })
+                .with_anchors(*before_anchor, *after_anchor);
+            let query_string = serde_urlencoded::to_string(&query).map_err(|err| {
+                CoreError::internal_server_error(format!(
+                    "Failed to serialize pagination query string: {:?}. Base: {:?}, Builder: {:?}, Current Rel: {}",
+                    err, base, self, rel
+                ))
+            })?;
 
             buf += &format!("<{}?{}>; rel={}", self.endpoint, query_string, rel);
         }
@@ -114,42 +146,46 @@ pub async fn pagination_response<Q: PaginationQuery, P: Paginatable<Q>>(
     }
 
     if context.has_next() {
-        let after = match objects.last() {
-            Some(obj) => obj.pagination_id(),
+        let (after, anchor) = match objects.last() {
+            Some(obj) => (obj.pagination_id(), obj.anchor_id()),
             None => {
                 // If there exists a next page, but this page is empty, then
                 // we must have had a `before` value set (e.g. this is a page before the first object matching the pagination conditions).
-                parameters.before.ok_or_else(|| {
-                    CoreError::internal_server_error(format!(
-                        "Empty page claims next page exists, yet `before` not set on current request. Caused by {:?}",
-                        query
-                    ))
-                })? - 1
+                (
+                    parameters.before.ok_or_else(|| {
+                        CoreError::internal_server_error(format!(
+                            "Empty page claims next page exists, yet `before` not set on current request. Caused by {:?}",
+                            query
+                        ))
+                    })? - 1,
+                    None,
+                )
             },
         };
 
         // TODO: Figure out the case where both `before` and `after` are set
         // If `before` is set on this request, then we _could_ support one-way pagination up to `before` by preserving the "before" value here.
         // Currently, this scenario cannot happen, as the documentation of `Pagination::page` we treat these pages as "standalone".
-        links = links.with_next(after);
+        links = links.with_anchored_next(after, anchor);
     }
 
     if context.has_previous() {
-        let before = match objects.first() {
-            Some(obj) => obj.pagination_id(),
-            None => {
+        let (before, anchor) = match objects.first() {
+            Some(obj) => (obj.pagination_id(), obj.anchor_id()),
+            None => (
                 parameters.after.ok_or_else(|| {
                     CoreError::internal_server_error(format!(
                         "Empty page claims previous page exists, yet `after` not set on current request. Caused by {:?}",
                         query
                     ))
-                })? + 1
-            },
+                })? + 1,
+                None,
+            ),
         };
 
         // Either this request had the `after` parameter set, in which case we definitely do not want to preserve it as our "before" variable above is either
         // the ID of the smallest object greater than `after`, or it is literally `after + 1`.
-        links = links.with_previous(before);
+        links = links.with_anchored_previous(before, anchor);
     };
 
     Ok(Response2::json(objects).with_header("Links", links.generate(&query)?))
@@ -111,6 +111,18 @@ impl PageContext {
 pub trait PaginationQuery: Serialize + Debug {
     fn parameters(&self) -> PaginationParameters;
     fn with_parameters(&self, parameters: PaginationParameters) -> Self;
+
+    /// Attaches the [`Paginatable::anchor_id`]s of the objects the `before`/`after` parameters of
+    /// this query were derived from.
+    ///
+    /// Only relevant for queries whose pagination ids can change between requests (such as list
+    /// positions), which can use these to re-locate their cursor. Ignored by default.
+    fn with_anchors(self, _before: Option<i32>, _after: Option<i32>) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
 }
 
 #[allow(async_fn_in_trait)]
@@ -139,6 +151,15 @@ pub trait Paginatable<Q: PaginationQuery>: Serialize + Sized {
     async fn first_and_last(connection: &mut PgConnection) -> Result<Option<(i32, i32)>, sqlx::Error>;
 
     fn pagination_id(&self) -> i32;
+
+    /// A stable identifier of this object, forming a composite cursor together with its
+    /// [`Paginatable::pagination_id`]. Links to neighbouring pages pass it to
+    /// [`PaginationQuery::with_anchors`].
+    ///
+    /// `None` by default, which is fine whenever the pagination id itself never changes.
+    fn anchor_id(&self) -> Option<i32> {
+        None
+    }
 }
 
 /// Historically, pointercrate has been determining whether a new page exists by simply incrementing the "limit" parameter
@@ -33,6 +33,22 @@ impl From<Permission> for u16 {
     }
 }
 
+/// A [`Permission`] together with how it relates to other permissions, see
+/// [`PermissionsManager::describe`]
+#[derive(Serialize, Debug, Eq, PartialEq)]
+pub struct PermissionDescription {
+    pub permission: Permission,
+    pub bit: u16,
+
+    /// The permissions directly implied by this one. Implication is transitive, so this is not
+    /// necessarily everything a user with this permission can do (see
+    /// [`PermissionsManager::implied_by`] for that)
+    pub implies: Vec<Permission>,
+
+    /// The permissions a user with this permission can directly assign
+    pub assigns: Vec<Permission>,
+}
+
 /// Structure containing all information about different [`Permission`] levels
 /// of a pointercrate instance
 ///
@@ -201,6 +217,30 @@ impl PermissionsManager {
         perms
     }
 
+    /// Lists all known permissions, ordered by their bit, together with the implication and
+    /// assignment relations they were configured with
+    pub fn describe(&self) -> Vec<PermissionDescription> {
+        let sorted = |set: Option<&HashSet<Permission>>| {
+            let mut permissions: Vec<Permission> = set.into_iter().flatten().copied().collect();
+            permissions.sort_by_key(Permission::bit);
+            permissions
+        };
+
+        let mut descriptions: Vec<PermissionDescription> = self
+            .permissions
+            .iter()
+            .map(|&permission| PermissionDescription {
+                permission,
+                bit: permission.bit,
+                implies: sorted(self.implication_map.get(&permission)),
+                assigns: sorted(self.assignable_map.get(&permission)),
+            })
+            .collect();
+
+        descriptions.sort_by_key(|description| description.bit);
+        descriptions
+    }
+
     pub fn require_permission(&self, permissions_we_have: u16, permission_required: Permission) -> Result<(), CoreError> {
         if !self.implied_by_bits(permissions_we_have).contains(&permission_required) {
             return Err(CoreError::MissingPermissions {
@@ -227,7 +267,7 @@ mod test {
         };
     }
 
-    use crate::permission::{Permission, PermissionsManager};
+    use crate::permission::{Permission, PermissionDescription, PermissionsManager};
     use std::collections::HashSet;
 
     const PERM1: Permission = Permission::new("1", 0x1);
@@ -263,4 +303,25 @@ mod test {
     fn test_assignment() {
         assert_eq!(permission_manager().assignable_by(PERM4), set![PERM2, PERM5, PERM6]);
     }
+
+    #[test]
+    fn test_describe() {
+        let descriptions = permission_manager().describe();
+
+        assert_eq!(
+            descriptions.iter().map(|description| description.bit).collect::<Vec<_>>(),
+            vec![0x1, 0x2, 0x4, 0x8, 0x10]
+        );
+        assert_eq!(
+            descriptions[1],
+            PermissionDescription {
+                permission: PERM2,
+                bit: 0x2,
+                implies: vec![PERM3],
+                assigns: vec![PERM3],
+            }
+        );
+        assert_eq!(descriptions[3].assigns, vec![PERM2, PERM5]);
+        assert!(descriptions[2].implies.is_empty());
+    }
 }
@@ -0,0 +1,47 @@
+use std::sync::{Arc, Mutex};
+
+/// In-memory cache for values derived from the whole list that are expensive to compute
+///
+/// Anything cached here only depends on the demons' positions and difficulties, on which of them are
+/// still in their grace period, and on the score formula and difficulty multipliers. The latter two
+/// are fixed at startup, so a deployment changing them restarts the server and starts with an empty
+/// cache. Endpoints moving demons around or changing their difficulty must call
+/// [`ListCache::invalidate`] after committing, and so does the sweep ending grace periods.
+///
+/// Clones share the cached values.
+#[derive(Default, Clone)]
+pub struct ListCache {
+    inner: Arc<Mutex<CacheState>>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    /// Incremented on each invalidation, so that values computed from outdated data are not stored
+    generation: u64,
+    total_points: Option<f64>,
+}
+
+impl ListCache {
+    /// Returns the cached total points (if any), together with the generation to pass to
+    /// [`ListCache::store_total_points`] after recomputing them
+    pub fn total_points(&self) -> (Option<f64>, u64) {
+        let state = self.inner.lock().unwrap();
+
+        (state.total_points, state.generation)
+    }
+
+    pub fn store_total_points(&self, total_points: f64, generation: u64) {
+        let mut state = self.inner.lock().unwrap();
+
+        if state.generation == generation {
+            state.total_points = Some(total_points);
+        }
+    }
+
+    pub fn invalidate(&self) {
+        let mut state = self.inner.lock().unwrap();
+
+        state.generation += 1;
+        state.total_points = None;
+    }
+}
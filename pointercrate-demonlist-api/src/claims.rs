@@ -7,6 +7,7 @@ use pointercrate_user_api::auth::Auth;
 use rocket::outcome::try_outcome;
 use rocket::request::{FromRequest, Outcome};
 use rocket::{async_trait, Request};
+use std::convert::Infallible;
 
 pub struct AuthWithClaim<T, const VERIFIED: bool>(pub Auth<T>, pub ClaimBy);
 
@@ -33,3 +34,27 @@ where
         Outcome::Success(AuthWithClaim(auth, claim))
     }
 }
+
+/// The country (as ISO country code) the request making a claim was geolocated to
+///
+/// `None` if the geolocation feature is disabled, no [`crate::GeolocationProvider`] is managed,
+/// or geolocation failed. Claims are still made in that case, whether they can be verified later
+/// is up to [`pointercrate_demonlist::config::strict_claim_geolocation`].
+pub struct ClaimantCountry(pub Option<String>);
+
+#[async_trait]
+impl<'r> FromRequest<'r> for ClaimantCountry {
+    type Error = Infallible;
+
+    #[cfg_attr(not(feature = "geolocation"), allow(unused_variables))]
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        #[cfg(feature = "geolocation")]
+        if let Some(provider) = request.rocket().state::<Box<dyn crate::GeolocationProvider>>() {
+            let country = crate::geolocate::claimant_country(provider.as_ref(), request).await;
+
+            return Outcome::Success(ClaimantCountry(country));
+        }
+
+        Outcome::Success(ClaimantCountry(None))
+    }
+}
@@ -1,5 +1,12 @@
-use crate::ratelimits::DemonlistRatelimits;
-use pointercrate_core::{audit::AuditLogEntry, pool::PointercratePool};
+use crate::{
+    cache::ListCache,
+    level_id::{LevelIdResolution, LevelIdResolver},
+    overflow_hook::OverflowHook,
+    ratelimits::DemonlistRatelimits,
+};
+use chrono::NaiveDateTime;
+use log::{error, warn};
+use pointercrate_core::{audit::AuditLogEntry, error::CoreError, pool::PointercratePool};
 use pointercrate_core_api::{
     error::Result,
     etag::{Precondition, TaggableExt, Tagged},
@@ -8,18 +15,30 @@ use pointercrate_core_api::{
     response::Response2,
 };
 use pointercrate_demonlist::{
-    creator::{Creator, PostCreator},
+    creator::{creators_of, Creator, PatchCreator, PostCreator},
     demon::{
-        audit::{DemonModificationData, MovementLogEntry},
-        Demon, DemonIdPagination, DemonPositionPagination, FullDemon, PatchDemon, PostDemon,
+        audit::{DemonHistoryEntry, DemonHistoryPagination, DemonModificationData, MovementLogEntry},
+        Demon, DemonEnjoymentPagination, DemonIdPagination, DemonNeighbors, DemonPosition, DemonPositionPagination, FullDemon,
+        ListPositions, MinimalDemon, PatchDemon, PostDemon, RatedDemon, TimeShiftedDemon, MAX_SEARCH_RESULTS,
     },
     error::DemonlistError,
     player::DatabasePlayer,
+    record::{approved_records_on, MinimalRecordP, RecordOrder},
+    submitter::Submitter,
     LIST_ADMINISTRATOR, LIST_MODERATOR,
 };
 use pointercrate_user::auth::ApiToken;
 use pointercrate_user_api::auth::Auth;
-use rocket::{http::Status, serde::json::Json, State};
+use rocket::{
+    futures::StreamExt,
+    http::{ContentType, Status},
+    response::stream::TextStream,
+    serde::json::Json,
+    State,
+};
+use serde::Deserialize;
+use sqlx::{pool::PoolConnection, Postgres};
+use std::{borrow::Cow, net::IpAddr};
 
 
 #[rocket::get("/")]
@@ -28,6 +47,26 @@ pub async fn paginate(pool: &State<PointercratePool>, pagination: Query<DemonIdP
 }
 
 
+/// The list as it was at the given point in time (UTC), reconstructed from the audit log
+///
+/// Demons that were added afterwards are left out. Takes precedence over [`paginate`] whenever `at`
+/// is given.
+#[rocket::get("/?<at>")]
+pub async fn time_machine(at: &str, pool: &State<PointercratePool>) -> Result<Json<Vec<TimeShiftedDemon>>> {
+    let at = at.parse::<NaiveDateTime>().map_err(|_| CoreError::UnprocessableEntity)?;
+
+    Ok(Json(pointercrate_demonlist::demon::list_at(&mut *pool.connection().await?, at).await?))
+}
+
+
+/// The "most fun levels" view. Takes precedence over [`paginate`] if the `sort=enjoyment` query
+/// parameter is present
+#[rocket::get("/?sort=enjoyment")]
+pub async fn by_enjoyment(pool: &State<PointercratePool>, pagination: Query<DemonEnjoymentPagination>) -> Result<Json<Vec<RatedDemon>>> {
+    Ok(Json(pointercrate_demonlist::demon::by_enjoyment(&pagination.0, &mut *pool.connection().await?).await?))
+}
+
+
 #[rocket::get("/listed/")]
 pub async fn paginate_listed(
     pool: &State<PointercratePool>, pagination: Query<DemonPositionPagination>,
@@ -36,9 +75,142 @@ pub async fn paginate_listed(
 }
 
 
+/// The entire list as CSV, for people who want to work with it in a spreadsheet
+#[rocket::get("/listed.csv")]
+pub async fn export_csv_file(pool: &State<PointercratePool>) -> Result<Response2<(ContentType, TextStream![String])>> {
+    let connection = pool.connection().await?;
+
+    Ok(Response2::new((ContentType::CSV, csv_rows(connection)))
+        .with_header("Content-Disposition", "attachment; filename=\"demonlist.csv\""))
+}
+
+fn csv_rows(mut connection: PoolConnection<Postgres>) -> TextStream![String] {
+    TextStream! {
+        yield "position,name,difficulty,requirement,publisher,verifier,level_id\n".to_string();
+
+        let mut demons = pointercrate_demonlist::demon::stream_current_list(&mut *connection);
+
+        while let Some(demon) = demons.next().await {
+            let demon = match demon {
+                Ok(demon) => demon,
+                Err(err) => {
+                    // The status line has long been sent at this point, all we can do is cut the response short
+                    error!("Exporting the demon list failed: {}", err);
+
+                    break;
+                },
+            };
+
+            yield format!(
+                "{},{},{},{},{},{},{}\n",
+                demon.base.position,
+                csv_field(&demon.base.name),
+                demon.difficulty,
+                demon.requirement,
+                csv_field(&demon.publisher.name),
+                csv_field(&demon.verifier.name),
+                demon.level_id.map(|id| id.to_string()).unwrap_or_default()
+            );
+        }
+    }
+}
+
+/// Quotes the given value if it contains anything that would otherwise break the CSV structure
+///
+/// Values that spreadsheet applications would evaluate as a formula are prefixed with a `'`, since
+/// names are user controlled.
+fn csv_field(value: &str) -> Cow<'_, str> {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        Cow::Owned(format!("'{}", value))
+    } else {
+        Cow::Borrowed(value)
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        value
+    }
+}
+
+
+/// Demons whose names resemble `q`, most similar first. Since names aren't unique, results include
+/// positions to tell demons apart
+#[rocket::get("/search/?<q>&<limit>")]
+pub async fn search(q: &str, limit: Option<i64>, pool: &State<PointercratePool>) -> Result<Json<Vec<MinimalDemon>>> {
+    let limit = limit.unwrap_or(MAX_SEARCH_RESULTS);
+
+    Ok(Json(MinimalDemon::search(q, limit, &mut *pool.connection().await?).await?))
+}
+
+
+/// Just the ordering of the list, for clients that resolve demon details separately
+#[rocket::get("/positions/")]
+pub async fn positions(pool: &State<PointercratePool>) -> Result<Tagged<ListPositions>> {
+    Ok(Tagged(pointercrate_demonlist::demon::list_positions(&mut *pool.connection().await?).await?))
+}
+
+
+/// Moves several demons at once, e.g. after a big list update. Demons not mentioned keep their
+/// relative order
+#[rocket::patch("/positions/", data = "<moves>")]
+pub async fn reorder(mut auth: Auth<ApiToken>, moves: Json<Vec<DemonPosition>>, cache: &State<ListCache>) -> Result<Tagged<ListPositions>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    Demon::reorder(&moves, &mut auth.connection).await?;
+
+    let positions = pointercrate_demonlist::demon::list_positions(&mut auth.connection).await?;
+
+    auth.commit().await?;
+    cache.invalidate();
+
+    Ok(Tagged(positions))
+}
+
+
+/// Demons whose verifier or publisher is banned, for reviewing their placement after bans
+#[rocket::get("/verified-by-banned/")]
+pub async fn verified_by_banned(mut auth: Auth<ApiToken>) -> Result<Json<Vec<Demon>>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    Ok(Json(pointercrate_demonlist::demon::with_banned_players(&mut auth.connection).await?))
+}
+
+
 #[rocket::get("/<demon_id>/")]
 pub async fn get(demon_id: i32, pool: &State<PointercratePool>) -> Result<Tagged<FullDemon>> {
-    Ok(Tagged(FullDemon::by_id(demon_id, &mut *pool.connection().await?).await?))
+    let mut connection = pool.connection().await?;
+
+    let demon = FullDemon::by_id(demon_id, &mut connection).await?;
+
+    Ok(Tagged(demon.with_peak_position(&mut connection).await?))
+}
+
+
+#[derive(Deserialize)]
+pub struct RecordsQuery {
+    #[serde(default)]
+    order: RecordOrder,
+}
+
+/// The approved records on a demon, by default in the same order as in [`FullDemon::records`]
+#[rocket::get("/<demon_id>/records/")]
+pub async fn records(demon_id: i32, pool: &State<PointercratePool>, query: Query<RecordsQuery>) -> Result<Json<Vec<MinimalRecordP>>> {
+    let mut connection = pool.connection().await?;
+
+    let demon = MinimalDemon::by_id(demon_id, &mut connection).await?;
+
+    Ok(Json(approved_records_on(&demon, query.0.order, &mut connection).await?))
+}
+
+
+#[rocket::get("/<demon_id>/neighbors/")]
+pub async fn neighbors(demon_id: i32, pool: &State<PointercratePool>) -> Result<Json<DemonNeighbors>> {
+    let mut connection = pool.connection().await?;
+
+    let demon = MinimalDemon::by_id(demon_id, &mut connection).await?;
+
+    Ok(Json(demon.neighbors(&mut connection).await?))
 }
 
 
@@ -56,6 +228,22 @@ pub async fn audit(demon_id: i32, mut auth: Auth<ApiToken>) -> Result<Json<Vec<A
 }
 
 
+/// The complete moderation history of a demon, merging its audit log with the status changes and
+/// notes of its records
+#[rocket::get("/<demon_id>/history/")]
+pub async fn history(
+    demon_id: i32, mut auth: Auth<ApiToken>, pagination: Query<DemonHistoryPagination>,
+) -> Result<Json<Vec<DemonHistoryEntry>>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let demon = MinimalDemon::by_id(demon_id, &mut auth.connection).await?;
+
+    let history = pointercrate_demonlist::demon::audit::history_of_demon(demon.id, &pagination.0, &mut auth.connection).await?;
+
+    Ok(Json(history))
+}
+
+
 #[rocket::get("/<demon_id>/audit/movement/")]
 pub async fn movement_log(demon_id: i32, pool: &State<PointercratePool>) -> Result<Json<Vec<MovementLogEntry>>> {
     let log = pointercrate_demonlist::demon::audit::movement_log_for_demon(demon_id, &mut *pool.connection().await?).await?;
@@ -70,15 +258,45 @@ pub async fn movement_log(demon_id: i32, pool: &State<PointercratePool>) -> Resu
 
 #[rocket::post("/", data = "<data>")]
 pub async fn post(
-    mut auth: Auth<ApiToken>, data: Json<PostDemon>, ratelimits: &State<DemonlistRatelimits>,
+    ip: IpAddr, mut auth: Auth<ApiToken>, data: Json<PostDemon>, ratelimits: &State<DemonlistRatelimits>, cache: &State<ListCache>,
+    resolver: &State<Box<dyn LevelIdResolver>>, overflow_hook: &State<Box<dyn OverflowHook>>,
 ) -> Result<Response2<Tagged<FullDemon>>> {
     auth.require_permission(LIST_MODERATOR)?;
 
     ratelimits.add_demon()?;
 
-    let demon = FullDemon::create_from(data.0, &mut auth.connection).await?;
+    let mut data = data.0;
+
+    if data.level_id.is_none() {
+        match resolver.resolve(&data.name).await {
+            LevelIdResolution::Found(level_id) => {
+                data.level_id = Some(i64::try_from(level_id).map_err(|_| DemonlistError::InvalidLevelId)?);
+            },
+            LevelIdResolution::Ambiguous(_) => warn!("Multiple levels named {} exist, not setting a level id", data.name),
+            LevelIdResolution::NotFound => (),
+        }
+    }
+
+    let verification_record = data.verification_record;
+    let (mut demon, archived) = FullDemon::create_from(data, &mut auth.connection).await?;
+
+    if verification_record {
+        let submitter = match Submitter::by_ip(ip, &mut auth.connection).await? {
+            Some(submitter) => submitter,
+            None => Submitter::create_submitter(ip, &mut auth.connection).await?,
+        };
+
+        demon.add_verification_record(&submitter, &mut auth.connection).await?;
+    }
 
     auth.commit().await?;
+    cache.invalidate();
+
+    if !archived.is_empty() {
+        if let Err(err) = overflow_hook.on_overflow(&demon, &archived).await {
+            error!("Overflow hook failed for demon {}: {}", demon.demon.base, err);
+        }
+    }
 
     let demon_id = demon.demon.base.id;
 
@@ -90,10 +308,17 @@ pub async fn post(
 
 #[rocket::patch("/<demon_id>/", data = "<patch>")]
 pub async fn patch(
-    demon_id: i32, mut auth: Auth<ApiToken>, precondition: Precondition, patch: Json<PatchDemon>,
+    demon_id: i32, mut auth: Auth<ApiToken>, precondition: Precondition, patch: Json<PatchDemon>, cache: &State<ListCache>,
 ) -> Result<Tagged<FullDemon>> {
     auth.require_permission(LIST_MODERATOR)?;
 
+    // Archiving moves the demon to the bottom of the list, and the difficulty determines the demon's
+    // score multiplier
+    let changes_scores = patch.position.is_some() || patch.archived.is_some() || patch.difficulty.is_some();
+
+    // Concurrent patches must not both be checked against the same, soon to be outdated, ETag
+    Demon::lock(demon_id, &mut auth.connection).await?;
+
     let demon = FullDemon::by_id(demon_id, &mut auth.connection)
         .await?
         .require_match(precondition)?
@@ -102,6 +327,64 @@ pub async fn patch(
 
     auth.commit().await?;
 
+    if changes_scores {
+        cache.invalidate();
+    }
+
+    Ok(Tagged(demon))
+}
+
+
+/// Looks up the level id of a demon again from its current name, for when the one found on
+/// creation was wrong or went stale
+#[rocket::post("/<demon_id>/level_id/")]
+pub async fn refresh_level_id(
+    demon_id: i32, mut auth: Auth<ApiToken>, resolver: &State<Box<dyn LevelIdResolver>>,
+) -> Result<Tagged<FullDemon>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let mut demon = FullDemon::by_id(demon_id, &mut auth.connection).await?;
+
+    let resolution = resolver.resolve(demon.name()).await;
+
+    demon.demon.refresh_level_id(resolution, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Tagged(demon))
+}
+
+
+/// Takes a demon off the list. Its records and audit log are kept, and it can be brought back via
+/// [`restore`]
+#[rocket::delete("/<demon_id>/")]
+pub async fn delete(demon_id: i32, mut auth: Auth<ApiToken>, precondition: Precondition, cache: &State<ListCache>) -> Result<Status> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let demon = FullDemon::by_id(demon_id, &mut auth.connection)
+        .await?
+        .require_match(precondition)?;
+
+    demon.demon.delete(&mut auth.connection).await?;
+
+    auth.commit().await?;
+    cache.invalidate();
+
+    Ok(Status::NoContent)
+}
+
+
+#[rocket::post("/<demon_id>/restore/")]
+pub async fn restore(demon_id: i32, mut auth: Auth<ApiToken>, cache: &State<ListCache>) -> Result<Tagged<FullDemon>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    Demon::restore(demon_id, &mut auth.connection).await?;
+
+    let demon = FullDemon::by_id(demon_id, &mut auth.connection).await?;
+
+    auth.commit().await?;
+    cache.invalidate();
+
     Ok(Tagged(demon))
 }
 
@@ -111,9 +394,7 @@ pub async fn post_creator(demon_id: i32, mut auth: Auth<ApiToken>, creator: Json
     auth.require_permission(LIST_MODERATOR)?;
 
     let demon = Demon::by_id(demon_id, &mut auth.connection).await?;
-    let player = DatabasePlayer::by_name_or_create(&creator.creator, &mut auth.connection).await?;
-
-    Creator::insert(&demon.base, &player, &mut auth.connection).await?;
+    let player = Creator::insert_by_name(&demon.base, &creator.creator, &mut auth.connection).await?;
 
     auth.commit().await?;
 
@@ -124,6 +405,27 @@ pub async fn post_creator(demon_id: i32, mut auth: Auth<ApiToken>, creator: Json
 }
 
 
+#[rocket::patch("/<demon_id>/creators/<player_id>/", data = "<patch>")]
+pub async fn patch_creator(
+    demon_id: i32, player_id: i32, mut auth: Auth<ApiToken>, patch: Json<PatchCreator>,
+) -> Result<Json<Vec<DatabasePlayer>>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let demon = Demon::by_id(demon_id, &mut auth.connection).await?;
+    let player = DatabasePlayer::by_id(player_id, &mut auth.connection).await?;
+
+    Creator::get(&demon.base, &player, &mut auth.connection)
+        .await?
+        .set_priority(patch.priority, &mut auth.connection)
+        .await?;
+
+    let creators = creators_of(&demon.base, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Json(creators))
+}
+
 #[rocket::delete("/<demon_id>/creators/<player_id>/")]
 pub async fn delete_creator(demon_id: i32, player_id: i32, mut auth: Auth<ApiToken>) -> Result<Status> {
     auth.require_permission(LIST_MODERATOR)?;
@@ -1,5 +1,5 @@
 use pointercrate_demonlist::config;
-use rocket::serde::json::Json;
+use rocket::{http::ContentType, serde::json::Json};
 use serde_json::{json, Value};
 
 #[rocket::get("/")]
@@ -13,3 +13,10 @@ pub fn list_information() -> Json<Value> {
 
     Json(data)
 }
+
+/// The default [placeholder thumbnail](config::placeholder_thumbnail), for demons without a thumbnail
+/// of their own
+#[rocket::get("/placeholder-thumbnail.svg")]
+pub fn placeholder_thumbnail() -> (ContentType, &'static str) {
+    (ContentType::SVG, include_str!("../../static/images/placeholder-thumbnail.svg"))
+}
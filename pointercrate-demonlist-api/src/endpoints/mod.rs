@@ -3,4 +3,6 @@ pub(crate) mod misc;
 pub(crate) mod nationality;
 pub(crate) mod player;
 pub(crate) mod record;
+pub(crate) mod scoring;
 pub(crate) mod submitter;
+pub(crate) mod video;
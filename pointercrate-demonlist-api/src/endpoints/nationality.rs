@@ -1,6 +1,10 @@
-use pointercrate_core::pool::PointercratePool;
-use pointercrate_core_api::{error::Result, etag::Tagged, query::Query};
-use pointercrate_demonlist::nationality::{Nationality, NationalityRankingPagination, NationalityRecord, RankedNation, Subdivision};
+use pointercrate_core::{error::CoreError, pool::PointercratePool};
+use pointercrate_core_api::{error::Result, etag::Tagged, pagination::pagination_response, query::Query, response::Response2};
+use pointercrate_demonlist::{
+    demon::{Demon, DemonPositionPagination},
+    nationality::{Nationality, NationalityRankingPagination, NationalityRecord, NationalityStats, RankedNation, Subdivision},
+    record::{enjoyment_visible, MinimalRecordPD, RecordPagination, RecordStatus},
+};
 use rocket::{serde::json::Json, State};
 
 
@@ -21,6 +25,13 @@ pub async fn ranking(pool: &State<PointercratePool>, pagination: Query<Nationali
 }
 
 
+/// Player counts and mean scores for every nation, including those without any players
+#[rocket::get("/stats/")]
+pub async fn stats(pool: &State<PointercratePool>) -> Result<Json<Vec<NationalityStats>>> {
+    Ok(Json(Nationality::with_stats(&mut *pool.connection().await?).await?))
+}
+
+
 #[rocket::get("/<iso_code>/")]
 pub async fn nation(pool: &State<PointercratePool>, iso_code: String) -> Result<Tagged<NationalityRecord>> {
     let mut connection = pool.connection().await?;
@@ -30,3 +41,43 @@ pub async fn nation(pool: &State<PointercratePool>, iso_code: String) -> Result<
 
     Ok(Tagged(nationality.upgrade(&mut connection).await?))
 }
+
+
+#[rocket::get("/<iso_code>/verifications/")]
+pub async fn verifications(
+    pool: &State<PointercratePool>, iso_code: String, pagination: Query<DemonPositionPagination>,
+) -> Result<Response2<Json<Vec<Demon>>>> {
+    let mut connection = pool.connection().await?;
+
+    let nationality = Nationality::by_country_code_or_name(iso_code.to_uppercase().as_ref(), &mut connection).await?;
+
+    let mut pagination = pagination.0;
+    pagination.verifier_nationality = Some(nationality.iso_country_code);
+
+    // The filter is part of the query string, so the pagination links can simply point to the general demon pagination endpoint
+    Ok(pagination_response("/api/v2/demons/listed/", pagination, &mut connection).await?)
+}
+
+
+/// The approved records of players from the given nation, optionally restricted to those approved
+/// after `since`
+#[rocket::get("/<iso_code>/records/")]
+pub async fn records(
+    pool: &State<PointercratePool>, iso_code: String, pagination: Query<RecordPagination>,
+) -> Result<Response2<Json<Vec<MinimalRecordPD>>>> {
+    let mut connection = pool.connection().await?;
+
+    let nationality = Nationality::by_country_code_or_name(iso_code.to_uppercase().as_ref(), &mut connection).await?;
+
+    let mut pagination = pagination.0;
+
+    if pagination.submitter.is_some() || pagination.status.is_some_and(|status| status != RecordStatus::Approved) {
+        return Err(CoreError::Unauthorized.into());
+    }
+
+    pagination.nationality = Some(nationality.iso_country_code);
+    pagination.status = Some(RecordStatus::Approved);
+    pagination.hide_enjoyment = !enjoyment_visible(false);
+
+    Ok(pagination_response("/api/v1/records/", pagination, &mut connection).await?)
+}
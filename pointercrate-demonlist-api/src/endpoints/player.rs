@@ -1,4 +1,4 @@
-use crate::claims::AuthWithClaim;
+use crate::claims::{AuthWithClaim, ClaimantCountry};
 use pointercrate_core::pool::PointercratePool;
 use pointercrate_core_api::{
     error::Result,
@@ -9,12 +9,13 @@ use pointercrate_core_api::{
 };
 use pointercrate_demonlist::{
     LIST_HELPER, LIST_MODERATOR, error::DemonlistError, player::{
-        DatabasePlayer, FullPlayer, PatchPlayer, Player, PlayerPagination, RankedPlayer, RankingPagination, claim::{ListedClaim, PatchPlayerClaim, PlayerClaim, PlayerClaimPagination}
+        BanPreview, DatabasePlayer, FullPlayer, NationalRank, PatchPlayer, Player, PlayerComparison, PlayerPagination, RankThreshold, RankedPlayer, RankingPagination, TierScore, claim::{ClaimOverview, ListedClaim, PatchPlayerClaim, PlayerClaim, PlayerClaimPagination}
     }
 };
 use pointercrate_user::{auth::ApiToken, MODERATOR};
 use pointercrate_user_api::auth::Auth;
 use rocket::{http::Status, serde::json::Json, State};
+use serde::Deserialize;
 
 
 #[rocket::get("/")]
@@ -41,6 +42,33 @@ pub async fn ranking(pool: &State<PointercratePool>, query: Query<RankingPaginat
 }
 
 
+#[rocket::get("/ranking/thresholds/")]
+pub async fn ranking_thresholds(pool: &State<PointercratePool>) -> Result<Json<Vec<RankThreshold>>> {
+    let milestones = pointercrate_demonlist::config::rank_milestones();
+
+    Ok(Json(
+        pointercrate_demonlist::player::ranking_thresholds(&milestones, &mut *pool.connection().await?).await?,
+    ))
+}
+
+
+#[derive(Deserialize)]
+pub struct CompareQuery {
+    a: i32,
+    b: i32,
+}
+
+#[rocket::get("/compare/")]
+pub async fn compare(pool: &State<PointercratePool>, query: Query<CompareQuery>) -> Result<Json<PlayerComparison>> {
+    let mut connection = pool.connection().await?;
+
+    let a = Player::by_id(query.0.a, &mut connection).await?;
+    let b = Player::by_id(query.0.b, &mut connection).await?;
+
+    Ok(Json(a.compare(b, &mut connection).await?))
+}
+
+
 #[rocket::get("/me/", rank = 0)]
 pub async fn get_me(auth: AuthWithClaim<ApiToken, false>) -> Result<Tagged<FullPlayer>> {
     let AuthWithClaim(mut auth, claim) = auth;
@@ -56,9 +84,45 @@ pub async fn get_me(auth: AuthWithClaim<ApiToken, false>) -> Result<Tagged<FullP
 pub async fn get(player_id: i32, pool: &State<PointercratePool>) -> Result<Tagged<FullPlayer>> {
     let mut connection = pool.connection().await?;
 
-    Ok(Tagged(
-        Player::by_id(player_id, &mut connection).await?.upgrade(&mut connection).await?,
-    ))
+    let mut player = Player::by_id(player_id, &mut connection).await?.upgrade(&mut connection).await?;
+
+    player.hide_private_enjoyment();
+
+    Ok(Tagged(player))
+}
+
+
+/// `null` if the player has no nationality or no score
+#[rocket::get("/<player_id>/national-rank/")]
+pub async fn national_rank(player_id: i32, pool: &State<PointercratePool>) -> Result<Json<Option<NationalRank>>> {
+    let mut connection = pool.connection().await?;
+
+    let player = Player::by_id(player_id, &mut connection).await?;
+
+    Ok(Json(player.national_rank(&mut connection).await?))
+}
+
+
+#[rocket::get("/<player_id>/score-by-tier/")]
+pub async fn score_by_tier(player_id: i32, pool: &State<PointercratePool>) -> Result<Json<Vec<TierScore>>> {
+    let mut connection = pool.connection().await?;
+
+    let player = Player::by_id(player_id, &mut connection).await?;
+
+    Ok(Json(player.score_by_tier(&mut connection).await?))
+}
+
+
+#[rocket::get("/by-name/<name>/")]
+pub async fn get_by_name(name: &str, pool: &State<PointercratePool>) -> Result<Tagged<FullPlayer>> {
+    let mut connection = pool.connection().await?;
+
+    let player = DatabasePlayer::by_name(name, &mut connection).await?;
+    let mut player = Player::by_id(player.id, &mut connection).await?.upgrade(&mut connection).await?;
+
+    player.hide_private_enjoyment();
+
+    Ok(Tagged(player))
 }
 
 
@@ -81,12 +145,25 @@ pub async fn patch(
 }
 
 
+/// Reports what banning the given player would affect. Does not change anything
+#[rocket::post("/<player_id>/preview-ban/")]
+pub async fn preview_ban(player_id: i32, mut auth: Auth<ApiToken>) -> Result<Json<BanPreview>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let player = Player::by_id(player_id, &mut auth.connection).await?;
+
+    Ok(Json(player.preview_ban(&mut auth.connection).await?))
+}
+
+
 #[rocket::put("/<player_id>/claims/")]
-pub async fn put_claim(player_id: i32, mut auth: Auth<ApiToken>) -> Result<Response2<Json<PlayerClaim>>> {
+pub async fn put_claim(player_id: i32, mut auth: Auth<ApiToken>, country: ClaimantCountry) -> Result<Response2<Json<PlayerClaim>>> {
     let user_id = auth.user.user().id;
     let player = DatabasePlayer::by_id(player_id, &mut auth.connection).await?;
     let claim = player.initiate_claim(user_id, &mut auth.connection).await?;
 
+    claim.set_geolocated_country(country.0.as_deref(), &mut auth.connection).await?;
+
     auth.commit().await?;
 
     Ok(Response2::json(claim)
@@ -164,6 +241,28 @@ pub async fn paginate_claims(
     Ok(pagination_response("/api/v1/players/claims/", pagination.0, &mut auth.connection).await?)
 }
 
+
+/// Overview of which players are claimed, and whether these claims are verified. Unlike
+/// [`paginate_claims`], this is available to list helpers, who do not get to see who made a claim
+#[rocket::get("/claimed/")]
+pub async fn claimed(mut auth: Auth<ApiToken>, pagination: Query<PlayerClaimPagination>) -> Result<Response2<Json<Vec<ListedClaim>>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    let mut pagination = pagination.0;
+    pagination.hide_claimants = !auth.has_permission(LIST_MODERATOR);
+
+    Ok(pagination_response("/api/v1/players/claimed/", pagination, &mut auth.connection).await?)
+}
+
+/// All claims made by the authenticated user, including unverified ones, so that they can see why a
+/// claim is still pending
+#[rocket::get("/me/claims/")]
+pub async fn my_claims(mut auth: Auth<ApiToken>) -> Result<Json<Vec<ClaimOverview>>> {
+    let user_id = auth.user.user().id;
+
+    Ok(Json(PlayerClaim::all_for_user(user_id, &mut auth.connection).await?))
+}
+
 #[cfg(feature = "geolocation")]
 
 #[rocket::post("/me/geolocate/")]
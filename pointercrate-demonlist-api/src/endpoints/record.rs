@@ -1,4 +1,8 @@
-use crate::ratelimits::DemonlistRatelimits;
+use crate::{
+    freeze::SubmissionFreeze,
+    ratelimits::{DemonlistRatelimits, SubmitterHourlyLimit},
+    submission_hook::SubmissionHook,
+};
 use log::{debug, error, warn};
 use pointercrate_core::{audit::AuditLogEntry, error::CoreError, pool::PointercratePool};
 use pointercrate_core_api::{
@@ -14,16 +18,25 @@ use pointercrate_demonlist::{
     record::{
         audit::RecordModificationData,
         note::{notes_on, NewNote, Note, PatchNote},
-        submission_count, FullRecord, MinimalRecordPD, PatchRecord, RecordPagination, RecordStatus, Submission,
+        enjoyment_visible, import_enjoyment, purge_rejected_records, recent_records, submission_count, EnjoymentImportResult,
+        EnjoymentImportRow, FullRecord, MinimalRecordPD, PatchRecord, PendingRecord, PendingRecordPagination, PublicRecord, RecentRecord,
+        RecentRecordPagination, RecordPagination, RecordStatus, Submission,
     },
     submitter::Submitter,
-    LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
+    ENJOYMENT_CURATOR, LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
 use pointercrate_user::auth::ApiToken;
 use pointercrate_user_api::auth::Auth;
-use rocket::{http::Status, serde::json::Json, tokio, State};
+use rocket::{
+    futures::StreamExt,
+    http::{ContentType, Status},
+    response::stream::TextStream,
+    serde::json::Json,
+    tokio, Either, State,
+};
+use serde::Deserialize;
 use sqlx::{pool::PoolConnection, Postgres};
-use std::net::IpAddr;
+use std::{net::IpAddr, sync::Arc};
 
 /// Pagination endpoint for records in case authentication is provided
 ///
@@ -55,6 +68,8 @@ pub async fn paginate(mut auth: Auth<ApiToken>, query: Query<RecordPagination>)
         pagination.status = Some(RecordStatus::Approved);
     }
 
+    pagination.hide_enjoyment = !enjoyment_visible(auth.has_permission(LIST_HELPER));
+
     Ok(pagination_response("/api/v1/records/", pagination, &mut auth.connection).await?)
 }
 
@@ -75,16 +90,38 @@ pub async fn unauthed_pagination(
     }
 
     pagination.status = Some(RecordStatus::Approved);
+    pagination.hide_enjoyment = !enjoyment_visible(false);
 
     Ok(pagination_response("/api/v1/records/", pagination, &mut connection).await?)
 }
 
 
+/// The submitted records that have been waiting for review the longest, oldest first
+#[rocket::get("/oldest-pending/")]
+pub async fn oldest_pending(
+    mut auth: Auth<ApiToken>, query: Query<PendingRecordPagination>,
+) -> Result<Response2<Json<Vec<PendingRecord>>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    Ok(pagination_response("/api/v1/records/oldest-pending/", query.0, &mut auth.connection).await?)
+}
+
+
+/// The most recently approved records, newest first. See [`RecentRecordPagination`] for how to page through them
+#[rocket::get("/recent/")]
+pub async fn recent(pool: &State<PointercratePool>, query: Query<RecentRecordPagination>) -> Result<Json<Vec<RecentRecord>>> {
+    let mut connection = pool.connection().await?;
+
+    Ok(Json(recent_records(&query.0, &mut connection).await?))
+}
+
+
 #[rocket::post("/", data = "<submission>")]
 pub async fn submit(
     ip: IpAddr, auth: Option<Auth<ApiToken>>, submission: Json<Submission>, pool: &State<PointercratePool>,
-    ratelimits: &State<DemonlistRatelimits>,
-) -> Result<Response2<Tagged<FullRecord>>> {
+    ratelimits: &State<DemonlistRatelimits>, hourly_limit: &State<SubmitterHourlyLimit>, hook: &State<Arc<dyn SubmissionHook>>,
+    freeze: &State<SubmissionFreeze>,
+) -> Result<Response2<Either<Tagged<FullRecord>, Tagged<PublicRecord>>>> {
     let submission = submission.0;
     let status_is_submitted = submission.status() == RecordStatus::Submitted;
     let (is_team_member, user_id) = match auth {
@@ -92,7 +129,17 @@ pub async fn submit(
         None => (false, None),
     };
 
-    if !status_is_submitted || !submission.has_video() {
+    if !is_team_member && freeze.0 {
+        return Err(DemonlistError::SubmissionsFrozen.into());
+    }
+
+    // Only list staff get to decide a record's status on submission. Checked explicitly (rather than
+    // relying on authentication) so that a crafted body can never skip the queue
+    if !status_is_submitted && !is_team_member {
+        return Err(CoreError::Forbidden.into());
+    }
+
+    if !submission.has_video() {
         match auth {
             Some(ref auth) => auth.require_permission(LIST_HELPER)?,
             None => return Err(CoreError::Unauthorized.into()),
@@ -115,7 +162,7 @@ pub async fn submit(
 
     // Banned submitters cannot submit records
     if submitter.banned {
-        return Err(DemonlistError::BannedFromSubmissions.into());
+        return Err(DemonlistError::SubmitterBanned.into());
     }
 
     let normalized = submission.normalize(&mut connection).await?;
@@ -139,12 +186,25 @@ pub async fn submit(
         // Also check the local ratelimit first since that one expires earlier
         ratelimits.record_submission(ip)?;
         ratelimits.record_submission_global()?;
+
+        submitter.check_submission_limit(hourly_limit.0, &mut connection).await?;
     }
 
-    let mut record = validated.create(submitter, &mut connection).await?;
+    let record = validated.create(submitter, &mut connection).await?;
 
     connection.commit().await.map_err(DemonlistError::from)?;
 
+    if !is_team_member {
+        let hook = Arc::clone(hook);
+        let submitted = record.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = hook.on_submitted(&submitted).await {
+                error!("Submission hook failed for record {}: {}", submitted.id, err);
+            }
+        });
+    }
+
     // FIXME: This is fucking stupid
     if status_is_submitted {
         if let Some(ref video) = record.video {
@@ -157,11 +217,11 @@ pub async fn submit(
         }
     }
 
-    if !is_team_member {
-        record.submitter = None;
-    }
-
-    let mut response = Response2::tagged(record);
+    let mut response = if is_team_member {
+        Response2::new(Either::Left(Tagged(record)))
+    } else {
+        Response2::new(Either::Right(Tagged(PublicRecord::from(record))))
+    };
 
     if status_is_submitted {
         response = response.with_header(
@@ -175,7 +235,9 @@ pub async fn submit(
 
 
 #[rocket::get("/<record_id>/")]
-pub async fn get(record_id: i32, auth: Option<Auth<ApiToken>>, pool: &State<PointercratePool>) -> Result<Tagged<FullRecord>> {
+pub async fn get(
+    record_id: i32, auth: Option<Auth<ApiToken>>, pool: &State<PointercratePool>,
+) -> Result<Either<Tagged<FullRecord>, Tagged<PublicRecord>>> {
     let is_helper = auth.as_ref().is_some_and(|auth| auth.has_permission(LIST_HELPER));
 
     let mut connection = match auth {
@@ -183,18 +245,18 @@ pub async fn get(record_id: i32, auth: Option<Auth<ApiToken>>, pool: &State<Poin
         None => pool.transaction().await?,
     };
 
-    let mut record = FullRecord::by_id(record_id, &mut connection).await?;
+    let record = FullRecord::by_id(record_id, &mut connection).await?;
+
+    if is_helper {
+        return Ok(Either::Left(Tagged(record)));
+    }
 
     // TODO: allow access if auth is provided and a verified claim on the record's player is given
-    if !is_helper {
-        if record.status != RecordStatus::Approved {
-            return Err(DemonlistError::RecordNotFound { record_id }.into());
-        }
-        record.submitter = None;
-        record.raw_footage = None;
+    if record.status != RecordStatus::Approved {
+        return Err(DemonlistError::RecordNotFound { record_id }.into());
     }
 
-    Ok(Tagged(record))
+    Ok(Either::Right(Tagged(record.into())))
 }
 
 
@@ -218,10 +280,13 @@ pub async fn patch(
 ) -> Result<Tagged<FullRecord>> {
     let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
 
-    if record.demon.position > pointercrate_demonlist::config::extended_list_size() {
-        auth.require_permission(LIST_MODERATOR)?;
-    } else {
-        auth.require_permission(LIST_HELPER)?;
+    // Enjoyment curators may touch the enjoyment rating of any record, but nothing else
+    if !(patch.only_changes_enjoyment() && auth.has_permission(ENJOYMENT_CURATOR)) {
+        if record.demon.is_legacy() {
+            auth.require_permission(LIST_MODERATOR)?;
+        } else {
+            auth.require_permission(LIST_HELPER)?;
+        }
     }
 
     let record = record
@@ -235,6 +300,30 @@ pub async fn patch(
 }
 
 
+#[derive(Deserialize)]
+pub struct BulkStatusChange {
+    ids: Vec<i32>,
+    status: RecordStatus,
+}
+
+/// Moves all given records to the same status. Either all of them are updated, or none are
+#[rocket::patch("/status/", data = "<change>")]
+pub async fn patch_status(mut auth: Auth<ApiToken>, change: Json<BulkStatusChange>) -> Result<Json<Vec<FullRecord>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    let records = FullRecord::set_status_bulk(&change.ids, change.status, &mut auth.connection).await?;
+
+    // Same rule as for single record patches. Nothing is committed if this fails
+    if records.iter().any(|record| record.demon.is_legacy()) {
+        auth.require_permission(LIST_MODERATOR)?;
+    }
+
+    auth.commit().await?;
+
+    Ok(Json(records))
+}
+
+
 #[rocket::delete("/<record_id>/")]
 pub async fn delete(record_id: i32, mut auth: Auth<ApiToken>, precondition: Precondition) -> Result<Status> {
     let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
@@ -254,6 +343,82 @@ pub async fn delete(record_id: i32, mut auth: Auth<ApiToken>, precondition: Prec
 }
 
 
+/// All records as newline-delimited JSON, for full backups. The records are streamed straight from
+/// the database, so this works regardless of how many there are
+///
+/// As the response has already started by the time an error can occur, errors are reported by a
+/// final `{"error": ...}` line instead of the status code. A backup is only complete if its last
+/// line is a record.
+#[rocket::get("/export/")]
+pub async fn export(auth: Auth<ApiToken>, pool: &State<PointercratePool>) -> Result<(ContentType, TextStream![String])> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let connection = pool.connection().await?;
+
+    Ok((ContentType::new("application", "x-ndjson"), ndjson_records(connection)))
+}
+
+fn ndjson_records(mut connection: PoolConnection<Postgres>) -> TextStream![String] {
+    TextStream! {
+        let mut records = pointercrate_demonlist::record::stream_all_records(&mut *connection);
+
+        while let Some(record) = records.next().await {
+            let record = match record {
+                Ok(record) => record,
+                Err(err) => {
+                    error!("Exporting records failed: {}", err);
+
+                    yield export_error(err.to_string());
+                    break;
+                },
+            };
+
+            match serde_json::to_string(&record) {
+                Ok(line) => yield line + "\n",
+                Err(err) => {
+                    error!("Failed to serialize record {} for export: {}", record.id, err);
+
+                    yield export_error(format!("Failed to serialize record {}", record.id));
+                    break;
+                },
+            }
+        }
+    }
+}
+
+/// The line ending an export that failed midway, so that it cannot be mistaken for a complete one
+fn export_error(message: String) -> String {
+    serde_json::json!({ "error": message }).to_string() + "\n"
+}
+
+
+/// Purges rejected records older than the configured retention period
+#[rocket::post("/cleanup/")]
+pub async fn cleanup(mut auth: Auth<ApiToken>) -> Result<Json<serde_json::Value>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let purged = purge_rejected_records(&mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Json(serde_json::json!({ "purged": purged })))
+}
+
+
+/// Sets the enjoyment ratings of many records at once, e.g. from a spreadsheet. Invalid rows are
+/// skipped, the response reports for each row whether it was applied
+#[rocket::post("/enjoyment-import/", data = "<rows>")]
+pub async fn enjoyment_import(mut auth: Auth<ApiToken>, rows: Json<Vec<EnjoymentImportRow>>) -> Result<Json<Vec<EnjoymentImportResult>>> {
+    auth.require_permission(ENJOYMENT_CURATOR)?;
+
+    let results = import_enjoyment(rows.0, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Json(results))
+}
+
+
 #[rocket::get("/<record_id>/notes/")]
 pub async fn get_notes(record_id: i32, mut auth: Auth<ApiToken>) -> Result<Response2<Json<Vec<Note>>>> {
     let record_holder_id = sqlx::query!("SELECT player FROM records WHERE id = $1", record_id)
@@ -0,0 +1,43 @@
+use crate::cache::ListCache;
+use pointercrate_core::pool::PointercratePool;
+use pointercrate_core_api::error::Result;
+use pointercrate_demonlist::demon::{total_awardable_points, Difficulty, ScoreFormula};
+use rocket::{serde::json::Json, State};
+use serde_json::{json, Value};
+
+
+/// The points a player would have after completing every demon on the list
+#[rocket::get("/total/")]
+pub async fn total(pool: &State<PointercratePool>, cache: &State<ListCache>) -> Result<Json<Value>> {
+    let total = match cache.total_points() {
+        (Some(total), _) => total,
+        (None, generation) => {
+            let total = total_awardable_points(&mut *pool.connection().await?).await?;
+
+            cache.store_total_points(total, generation);
+
+            total
+        },
+    };
+
+    Ok(Json(json!({ "total": total })))
+}
+
+
+/// The parameters of the score formula in use, so that third party tools can compute scores the
+/// same way the server does. Scores are never rounded, and the score of a demon is multiplied by the
+/// multiplier of its difficulty tier.
+#[rocket::get("/config/")]
+pub async fn config(formula: &State<Box<dyn ScoreFormula>>) -> Json<Value> {
+    let multipliers: serde_json::Map<String, Value> = Difficulty::all()
+        .into_iter()
+        .map(|difficulty| (difficulty.to_sql(), json!(difficulty.score_multiplier())))
+        .collect();
+
+    Json(json!({
+        "formula": formula.describe(),
+        "difficulty_multipliers": multipliers,
+        "list_size": pointercrate_demonlist::config::list_size(),
+        "extended_list_size": pointercrate_demonlist::config::extended_list_size(),
+    }))
+}
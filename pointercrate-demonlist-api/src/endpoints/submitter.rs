@@ -5,13 +5,15 @@ use pointercrate_core_api::{
     query::Query,
     response::Response2,
 };
+use chrono::NaiveDateTime;
 use pointercrate_demonlist::{
-    submitter::{PatchSubmitter, Submitter, SubmitterPagination},
-    LIST_MODERATOR,
+    submitter::{submitter_stats, PatchSubmitter, Submitter, SubmitterPagination, SubmitterStats},
+    LIST_ADMINISTRATOR, LIST_MODERATOR,
 };
 use pointercrate_user::auth::ApiToken;
 use pointercrate_user_api::auth::Auth;
 use rocket::serde::json::Json;
+use serde::Deserialize;
 
 
 #[rocket::get("/")]
@@ -22,6 +24,25 @@ pub async fn paginate(mut auth: Auth<ApiToken>, pagination: Query<SubmitterPagin
 }
 
 
+#[derive(Deserialize)]
+pub struct StatsQuery {
+    since: NaiveDateTime,
+}
+
+/// Submission volume per submitter since some point in time, for spotting bursts of abusive
+/// submissions
+#[rocket::get("/stats/")]
+pub async fn stats(mut auth: Auth<ApiToken>, query: Query<StatsQuery>) -> Result<Json<Vec<SubmitterStats>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let stats = submitter_stats(query.0.since, auth.user.user().id, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Json(stats))
+}
+
+
 #[rocket::get("/<submitter_id>/")]
 pub async fn get(submitter_id: i32, mut auth: Auth<ApiToken>) -> Result<Tagged<Submitter>> {
     auth.require_permission(LIST_MODERATOR)?;
@@ -0,0 +1,17 @@
+use pointercrate_core_api::error::Result;
+use pointercrate_demonlist::video::{preview, VideoPreview};
+use rocket::serde::json::Json;
+use serde::Deserialize;
+
+
+#[derive(Deserialize)]
+pub struct VideoUrl {
+    url: String,
+}
+
+/// Runs the same validation as record submission does on a video URL, so that frontends can show a
+/// preview before submitting
+#[rocket::post("/validate/", data = "<video>")]
+pub fn validate(video: Json<VideoUrl>) -> Result<Json<VideoPreview>> {
+    Ok(Json(preview(&video.0.url)?))
+}
@@ -0,0 +1,7 @@
+/// Whether record submissions from non-staff are currently rejected
+///
+/// Taken from [`config::submissions_frozen`] unless managed before the demonlist API is mounted.
+///
+/// [`config::submissions_frozen`]: pointercrate_demonlist::config::submissions_frozen
+#[derive(Debug, Clone, Copy)]
+pub struct SubmissionFreeze(pub bool);
@@ -5,6 +5,10 @@ use pointercrate_demonlist::error::DemonlistError;
 use pointercrate_demonlist::nationality::Nationality;
 use rocket::request::{FromRequest, Outcome};
 use rocket::{async_trait, Request};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 #[async_trait]
 pub trait GeolocationProvider: Sync + Send {
@@ -12,6 +16,101 @@ pub trait GeolocationProvider: Sync + Send {
     async fn geolocate(&self, req: &Request<'_>) -> Option<(String, Option<String>)>;
 }
 
+/// A [`GeolocationProvider`] remembering the results of another provider for some time
+///
+/// Results are cached per client IP. Requests whose IP cannot be determined, as well as failed
+/// lookups, are never cached and always go to the inner provider.
+///
+/// At most `capacity` results are kept. Once the cache is full, expired results are evicted, and if
+/// that does not free up any space, the oldest result is.
+pub struct CachingGeolocationProvider<P: GeolocationProvider> {
+    inner: P,
+    ttl: Duration,
+    capacity: usize,
+    cache: Mutex<HashMap<IpAddr, (Instant, (String, Option<String>))>>,
+}
+
+impl<P: GeolocationProvider> CachingGeolocationProvider<P> {
+    pub fn new(inner: P, ttl: Duration, capacity: usize) -> Self {
+        CachingGeolocationProvider {
+            inner,
+            ttl,
+            capacity,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Forgets all cached results
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().clear()
+    }
+
+    fn cached(&self, ip: IpAddr) -> Option<(String, Option<String>)> {
+        let mut cache = self.cache.lock().unwrap();
+
+        match cache.get(&ip) {
+            Some((cached_at, location)) if cached_at.elapsed() < self.ttl => Some(location.clone()),
+            Some(_) => {
+                cache.remove(&ip);
+
+                None
+            },
+            None => None,
+        }
+    }
+
+    fn store(&self, ip: IpAddr, location: (String, Option<String>)) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut cache = self.cache.lock().unwrap();
+
+        if cache.len() >= self.capacity && !cache.contains_key(&ip) {
+            cache.retain(|_, (cached_at, _)| cached_at.elapsed() < self.ttl);
+
+            if cache.len() >= self.capacity {
+                let oldest = cache.iter().min_by_key(|(_, (cached_at, _))| *cached_at).map(|(ip, _)| *ip);
+
+                if let Some(oldest) = oldest {
+                    cache.remove(&oldest);
+                }
+            }
+        }
+
+        cache.insert(ip, (Instant::now(), location));
+    }
+}
+
+#[async_trait]
+impl<P: GeolocationProvider> GeolocationProvider for CachingGeolocationProvider<P> {
+    async fn geolocate(&self, req: &Request<'_>) -> Option<(String, Option<String>)> {
+        let Some(ip) = req.client_ip() else {
+            return self.inner.geolocate(req).await;
+        };
+
+        if let Some(location) = self.cached(ip) {
+            return Some(location);
+        }
+
+        // The lock is not held across the lookup, so concurrent requests from the same IP might both
+        // reach the inner provider. That's fine, they'll just store the same result twice.
+        let location = self.inner.geolocate(req).await?;
+
+        self.store(ip, location.clone());
+
+        Some(location)
+    }
+}
+
+/// Geolocates the country a request originates from, as an upper case ISO country code
+pub(crate) async fn claimant_country(provider: &dyn GeolocationProvider, request: &Request<'_>) -> Option<String> {
+    provider
+        .geolocate(request)
+        .await
+        .map(|(country_code, _)| country_code.to_uppercase())
+}
+
 pub struct GeolocatedNationality(pub Nationality);
 
 #[async_trait]
@@ -50,3 +149,134 @@ impl<'r> FromRequest<'r> for GeolocatedNationality {
         Outcome::Success(GeolocatedNationality(nationality))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::geolocate::{claimant_country, CachingGeolocationProvider, GeolocationProvider};
+    use pointercrate_demonlist::error::DemonlistError;
+    use pointercrate_demonlist::player::claim::PlayerClaim;
+    use rocket::local::asynchronous::Client;
+    use rocket::{async_trait, Request};
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    struct CountingProvider(AtomicUsize);
+
+    #[async_trait]
+    impl GeolocationProvider for CountingProvider {
+        async fn geolocate(&self, _req: &Request<'_>) -> Option<(String, Option<String>)> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+
+            Some(("DE".to_string(), Some("BY".to_string())))
+        }
+    }
+
+    struct FixedProvider(Option<&'static str>);
+
+    #[async_trait]
+    impl GeolocationProvider for FixedProvider {
+        async fn geolocate(&self, _req: &Request<'_>) -> Option<(String, Option<String>)> {
+            self.0.map(|country| (country.to_string(), None))
+        }
+    }
+
+    async fn check_claim(provider: FixedProvider, strict: bool) -> Result<(), DemonlistError> {
+        let client = Client::untracked(rocket::build()).await.unwrap();
+        let actual = claimant_country(&provider, &client.get("/")).await;
+
+        PlayerClaim::check_geolocation(Some("DE".to_string()), actual, strict)
+    }
+
+    #[rocket::async_test]
+    async fn test_claim_geolocation_matching() {
+        assert_eq!(check_claim(FixedProvider(Some("de")), true).await, Ok(()));
+        assert_eq!(check_claim(FixedProvider(Some("DE")), false).await, Ok(()));
+    }
+
+    #[rocket::async_test]
+    async fn test_claim_geolocation_mismatching() {
+        assert_eq!(
+            check_claim(FixedProvider(Some("FR")), true).await,
+            Err(DemonlistError::GeolocationMismatch {
+                expected: Some("DE".to_string()),
+                actual: Some("FR".to_string())
+            })
+        );
+        assert_eq!(check_claim(FixedProvider(Some("FR")), false).await, Ok(()));
+    }
+
+    #[rocket::async_test]
+    async fn test_claim_geolocation_unavailable() {
+        assert_eq!(
+            check_claim(FixedProvider(None), true).await,
+            Err(DemonlistError::GeolocationMismatch {
+                expected: Some("DE".to_string()),
+                actual: None
+            })
+        );
+        assert_eq!(check_claim(FixedProvider(None), false).await, Ok(()));
+    }
+
+    #[rocket::async_test]
+    async fn test_caching_geolocation_provider() {
+        let client = Client::untracked(rocket::build()).await.unwrap();
+        let provider = CachingGeolocationProvider::new(CountingProvider(AtomicUsize::new(0)), Duration::from_secs(3600), 16);
+
+        let alice: SocketAddr = "127.0.0.1:8000".parse().unwrap();
+        let bob: SocketAddr = "127.0.0.2:8000".parse().unwrap();
+
+        let first = provider.geolocate(&client.get("/").remote(alice)).await;
+        let second = provider.geolocate(&client.get("/").remote(alice)).await;
+
+        assert_eq!(first, Some(("DE".to_string(), Some("BY".to_string()))));
+        assert_eq!(first, second);
+        assert_eq!(provider.inner.0.load(Ordering::SeqCst), 1);
+
+        provider.geolocate(&client.get("/").remote(bob)).await;
+
+        assert_eq!(provider.inner.0.load(Ordering::SeqCst), 2);
+
+        provider.clear();
+        provider.geolocate(&client.get("/").remote(alice)).await;
+
+        assert_eq!(provider.inner.0.load(Ordering::SeqCst), 3);
+    }
+
+    #[rocket::async_test]
+    async fn test_caching_geolocation_provider_expiry() {
+        let client = Client::untracked(rocket::build()).await.unwrap();
+        let provider = CachingGeolocationProvider::new(CountingProvider(AtomicUsize::new(0)), Duration::ZERO, 16);
+
+        let alice: SocketAddr = "127.0.0.1:8000".parse().unwrap();
+
+        provider.geolocate(&client.get("/").remote(alice)).await;
+        provider.geolocate(&client.get("/").remote(alice)).await;
+
+        assert_eq!(provider.inner.0.load(Ordering::SeqCst), 2);
+        assert!(provider.cache.lock().unwrap().len() <= 1);
+    }
+
+    #[rocket::async_test]
+    async fn test_caching_geolocation_provider_capacity() {
+        let client = Client::untracked(rocket::build()).await.unwrap();
+        let provider = CachingGeolocationProvider::new(CountingProvider(AtomicUsize::new(0)), Duration::from_secs(3600), 2);
+
+        let alice: SocketAddr = "127.0.0.1:8000".parse().unwrap();
+        let bob: SocketAddr = "127.0.0.2:8000".parse().unwrap();
+        let carol: SocketAddr = "127.0.0.3:8000".parse().unwrap();
+
+        provider.geolocate(&client.get("/").remote(alice)).await;
+        provider.geolocate(&client.get("/").remote(bob)).await;
+        provider.geolocate(&client.get("/").remote(carol)).await;
+
+        assert_eq!(provider.cache.lock().unwrap().len(), 2);
+
+        // alice was the oldest entry, so she got evicted to make room for carol
+        provider.geolocate(&client.get("/").remote(carol)).await;
+        assert_eq!(provider.inner.0.load(Ordering::SeqCst), 3);
+
+        provider.geolocate(&client.get("/").remote(alice)).await;
+        assert_eq!(provider.inner.0.load(Ordering::SeqCst), 4);
+    }
+}
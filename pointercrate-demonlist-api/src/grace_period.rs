@@ -0,0 +1,45 @@
+use crate::cache::ListCache;
+use log::error;
+use pointercrate_core::pool::PointercratePool;
+use pointercrate_demonlist::player::end_grace_periods;
+use rocket::{tokio, Orbit, Rocket};
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+
+/// How often to check whether the grace period of a new demon ran out
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Spawns a task that periodically ends the grace periods of new demons (see
+/// [`new_demon_grace_hours`](pointercrate_demonlist::config::new_demon_grace_hours)), recomputing
+/// scores and invalidating the [`ListCache`] whenever one ran out
+///
+/// This way, reading scores never has to write anything, and scores update even if nobody looks at
+/// them.
+pub(crate) async fn spawn_sweep(rocket: &Rocket<Orbit>) {
+    let pool = rocket.state::<PointercratePool>().unwrap().clone_inner();
+    let cache = rocket.state::<ListCache>().unwrap().clone();
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            match sweep(&pool).await {
+                Ok(true) => cache.invalidate(),
+                Ok(false) => (),
+                Err(err) => error!("Failed to end grace periods: {:?}", err),
+            }
+        }
+    });
+}
+
+async fn sweep(pool: &Pool<Postgres>) -> pointercrate_demonlist::error::Result<bool> {
+    let mut connection = pool.begin().await?;
+
+    let ended = end_grace_periods(&mut connection).await?;
+
+    connection.commit().await?;
+
+    Ok(ended)
+}
@@ -0,0 +1,18 @@
+pub use pointercrate_demonlist::demon::LevelIdResolution;
+use rocket::async_trait;
+
+#[async_trait]
+pub trait LevelIdResolver: Sync + Send {
+    /// Looks up the id of the Geometry Dash level with the given name
+    async fn resolve(&self, name: &str) -> LevelIdResolution;
+}
+
+/// Resolver used if none is registered, which never finds anything
+pub struct NoLevelIdResolver;
+
+#[async_trait]
+impl LevelIdResolver for NoLevelIdResolver {
+    async fn resolve(&self, _name: &str) -> LevelIdResolution {
+        LevelIdResolution::NotFound
+    }
+}
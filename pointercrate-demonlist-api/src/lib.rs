@@ -1,19 +1,73 @@
-use crate::{endpoints::misc, ratelimits::DemonlistRatelimits};
+use crate::{
+    cache::ListCache, endpoints::misc, level_id::NoLevelIdResolver, overflow_hook::NoOverflowHook, ratelimits::DemonlistRatelimits,
+    submission_hook::NoSubmissionHook,
+};
 use pointercrate_core::pool::PointercratePool;
+use pointercrate_demonlist::demon::{store_score_formula, DefaultScoreFormula, Difficulty, ScoreFormula};
 use pointercrate_integrate::gd::GeometryDashConnector;
-use rocket::{Build, Rocket};
+use rocket::{fairing::AdHoc, Build, Rocket};
+use std::sync::Arc;
 
+pub(crate) mod cache;
 pub(crate) mod claims;
 pub(crate) mod config;
 mod endpoints;
+mod freeze;
 #[cfg(feature = "geolocation")]
 mod geolocate;
+mod grace_period;
+mod level_id;
+mod overflow_hook;
 pub(crate) mod ratelimits;
+mod submission_hook;
 
+pub use freeze::SubmissionFreeze;
 #[cfg(feature = "geolocation")]
-pub use geolocate::GeolocationProvider;
+pub use geolocate::{CachingGeolocationProvider, GeolocationProvider};
+pub use level_id::{LevelIdResolution, LevelIdResolver};
+pub use overflow_hook::OverflowHook;
+pub use ratelimits::SubmitterHourlyLimit;
+pub use submission_hook::SubmissionHook;
+
+/// Mounts the demonlist API
+///
+/// An alternative [`ScoreFormula`] can be used by managing it as a `Box<dyn ScoreFormula>` before
+/// calling this. Otherwise, the [`DefaultScoreFormula`] is used. All stored scores are recomputed
+/// when the formula changes between launches.
+///
+/// Similarly, level ids of newly added demons are only looked up if a `Box<dyn LevelIdResolver>` is
+/// managed, and an `Arc<dyn SubmissionHook>` can be managed to be notified of new submissions. A
+/// `Box<dyn OverflowHook>` is notified whenever demons are archived for being pushed past the maximal
+/// list length. Managing a [`SubmitterHourlyLimit`] or [`SubmissionFreeze`] overrides the configured
+/// one.
+///
+/// The demonlist configuration is validated when rocket ignites, and ignition fails if it is invalid.
+/// Once rocket lifts off, the grace periods of new demons are periodically checked for having run out.
+pub fn setup(mut rocket: Rocket<Build>) -> Rocket<Build> {
+    if rocket.state::<Box<dyn ScoreFormula>>().is_none() {
+        rocket = rocket.manage(Box::new(DefaultScoreFormula) as Box<dyn ScoreFormula>);
+    }
+
+    if rocket.state::<Box<dyn LevelIdResolver>>().is_none() {
+        rocket = rocket.manage(Box::new(NoLevelIdResolver) as Box<dyn LevelIdResolver>);
+    }
+
+    if rocket.state::<Arc<dyn SubmissionHook>>().is_none() {
+        rocket = rocket.manage(Arc::new(NoSubmissionHook) as Arc<dyn SubmissionHook>);
+    }
+
+    if rocket.state::<Box<dyn OverflowHook>>().is_none() {
+        rocket = rocket.manage(Box::new(NoOverflowHook) as Box<dyn OverflowHook>);
+    }
+
+    if rocket.state::<SubmitterHourlyLimit>().is_none() {
+        rocket = rocket.manage(SubmitterHourlyLimit(pointercrate_demonlist::config::submitter_hourly_limit()));
+    }
+
+    if rocket.state::<SubmissionFreeze>().is_none() {
+        rocket = rocket.manage(SubmissionFreeze(pointercrate_demonlist::config::submissions_frozen()));
+    }
 
-pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
     let ratelimits = DemonlistRatelimits::new();
     let dash_rs = GeometryDashConnector::new(rocket.state::<PointercratePool>().unwrap().clone_inner());
 
@@ -23,11 +77,19 @@ pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
 
     #[cfg_attr(not(feature = "geolocation"), allow(unused_mut))]
     let mut player_routes = rocket::routes![
+        endpoints::player::claimed,
+        endpoints::player::compare,
         endpoints::player::get,
+        endpoints::player::get_by_name,
         endpoints::player::get_me,
+        endpoints::player::my_claims,
+        endpoints::player::national_rank,
         endpoints::player::paginate,
         endpoints::player::patch,
+        endpoints::player::preview_ban,
         endpoints::player::ranking,
+        endpoints::player::ranking_thresholds,
+        endpoints::player::score_by_tier,
         endpoints::player::put_claim,
         endpoints::player::patch_claim,
         endpoints::player::paginate_claims,
@@ -38,15 +100,20 @@ pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
     player_routes.extend(rocket::routes![endpoints::player::geolocate_nationality]);
 
     rocket
+        .attach(AdHoc::try_on_ignite("Demonlist configuration", load_configuration))
+        .attach(AdHoc::on_liftoff("Grace period sweep", |rocket| Box::pin(grace_period::spawn_sweep(rocket))))
         .manage(ratelimits)
         .manage(dash_rs)
+        .manage(ListCache::default())
         .mount("/api/v1/list_information/", rocket::routes![misc::list_information])
+        .mount("/static/demonlist/images/", rocket::routes![misc::placeholder_thumbnail])
         .mount(
             "/api/v1/submitters/",
             rocket::routes![
                 endpoints::submitter::paginate,
                 endpoints::submitter::get,
-                endpoints::submitter::patch
+                endpoints::submitter::patch,
+                endpoints::submitter::stats
             ],
         )
         .mount(
@@ -55,37 +122,93 @@ pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
                 endpoints::record::get_notes,
                 endpoints::record::add_note,
                 endpoints::record::audit,
+                endpoints::record::cleanup,
                 endpoints::record::delete,
                 endpoints::record::delete_note,
+                endpoints::record::enjoyment_import,
+                endpoints::record::export,
                 endpoints::record::get,
                 endpoints::record::paginate,
+                endpoints::record::oldest_pending,
+                endpoints::record::recent,
                 endpoints::record::unauthed_pagination,
                 endpoints::record::patch,
+                endpoints::record::patch_status,
                 endpoints::record::patch_note,
                 endpoints::record::submit
             ],
         )
         .mount("/api/v1/players/", player_routes)
+        .mount("/api/v1/scoring/", rocket::routes![endpoints::scoring::config, endpoints::scoring::total])
+        .mount("/api/v1/video/", rocket::routes![endpoints::video::validate])
         .mount(
             "/api/v1/nationalities/",
             rocket::routes![
                 endpoints::nationality::subdivisions,
                 endpoints::nationality::ranking,
-                endpoints::nationality::nation
+                endpoints::nationality::stats,
+                endpoints::nationality::nation,
+                endpoints::nationality::records,
+                endpoints::nationality::verifications
             ],
         )
         .mount(
             "/api/v2/demons/",
             rocket::routes![
+                endpoints::demon::by_enjoyment,
                 endpoints::demon::get,
+                endpoints::demon::neighbors,
+                endpoints::demon::records,
                 endpoints::demon::paginate,
+                endpoints::demon::time_machine,
                 endpoints::demon::paginate_listed,
+                endpoints::demon::export_csv_file,
+                endpoints::demon::search,
+                endpoints::demon::positions,
+                endpoints::demon::reorder,
+                endpoints::demon::verified_by_banned,
                 endpoints::demon::audit,
                 endpoints::demon::movement_log,
+                endpoints::demon::history,
                 endpoints::demon::patch,
+                endpoints::demon::refresh_level_id,
+                endpoints::demon::delete,
+                endpoints::demon::restore,
                 endpoints::demon::post,
                 endpoints::demon::post_creator,
+                endpoints::demon::patch_creator,
                 endpoints::demon::delete_creator
             ],
         )
 }
+
+/// Parses the demonlist configuration and stores the parts of it that score computations in the database depend on
+async fn load_configuration(rocket: Rocket<Build>) -> rocket::fairing::Result {
+    if let Err(err) = pointercrate_demonlist::config::load() {
+        log::error!("Invalid demonlist configuration: {}", err);
+
+        return Err(rocket);
+    }
+
+    let pool = rocket.state::<PointercratePool>().unwrap();
+    let formula = rocket.state::<Box<dyn ScoreFormula>>().unwrap();
+
+    let stored: pointercrate_demonlist::error::Result<()> = async {
+        let mut connection = pool.transaction().await?;
+
+        Difficulty::store_multipliers(&mut connection).await?;
+        store_score_formula(formula.as_ref(), &mut connection).await?;
+
+        Ok(connection.commit().await?)
+    }
+    .await;
+
+    match stored {
+        Ok(()) => Ok(rocket),
+        Err(err) => {
+            log::error!("Failed to store difficulty multipliers and score formula: {:?}", err);
+
+            Err(rocket)
+        },
+    }
+}
@@ -0,0 +1,31 @@
+use log::debug;
+use pointercrate_demonlist::demon::{FullDemon, MinimalDemon};
+use rocket::async_trait;
+use std::error::Error;
+
+#[async_trait]
+pub trait OverflowHook: Sync + Send {
+    /// Called once placing a new demon pushed the given demons past the
+    /// [maximal list length](pointercrate_demonlist::config::max_list_length), and they were archived
+    ///
+    /// The new demon itself is among the archived demons if it was placed past the end of a full list.
+    /// Errors are logged, but do not cause the request to fail, as the changes have already been
+    /// committed by then.
+    async fn on_overflow(&self, placed: &FullDemon, archived: &[MinimalDemon]) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
+
+/// Hook used if none is registered, which does nothing
+pub struct NoOverflowHook;
+
+#[async_trait]
+impl OverflowHook for NoOverflowHook {
+    async fn on_overflow(&self, placed: &FullDemon, archived: &[MinimalDemon]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        debug!(
+            "No overflow hook registered, ignoring {} demons archived by placing {}",
+            archived.len(),
+            placed.demon.base
+        );
+
+        Ok(())
+    }
+}
@@ -11,6 +11,16 @@ ratelimits! {
     }
 }
 
+/// How many records a single submitter can submit per hour, see [`Submitter::check_submission_limit`]
+///
+/// Unlike the ratelimits above, this one is computed from the database. Taken from
+/// [`config::submitter_hourly_limit`] unless managed before the demonlist API is mounted.
+///
+/// [`Submitter::check_submission_limit`]: pointercrate_demonlist::submitter::Submitter::check_submission_limit
+/// [`config::submitter_hourly_limit`]: pointercrate_demonlist::config::submitter_hourly_limit
+#[derive(Debug, Clone, Copy)]
+pub struct SubmitterHourlyLimit(pub i64);
+
 #[cfg(test)]
 mod test {
     use crate::ratelimits::DemonlistRatelimits;
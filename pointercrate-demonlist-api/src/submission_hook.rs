@@ -0,0 +1,26 @@
+use log::debug;
+use pointercrate_demonlist::record::FullRecord;
+use rocket::async_trait;
+use std::error::Error;
+
+#[async_trait]
+pub trait SubmissionHook: Sync + Send {
+    /// Called once a new record has been successfully submitted to the queue by someone who is not list
+    /// staff and committed to the database. Records staff add directly do not trigger this.
+    ///
+    /// Runs in the background, so the submission request does not wait for it. Errors are logged, but
+    /// do not affect the submission, as the record has already been stored by then.
+    async fn on_submitted(&self, record: &FullRecord) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
+
+/// Hook used if none is registered, which does nothing
+pub struct NoSubmissionHook;
+
+#[async_trait]
+impl SubmissionHook for NoSubmissionHook {
+    async fn on_submitted(&self, record: &FullRecord) -> Result<(), Box<dyn Error + Send + Sync>> {
+        debug!("No submission hook registered, ignoring submission of record {}", record.id);
+
+        Ok(())
+    }
+}
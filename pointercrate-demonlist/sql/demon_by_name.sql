@@ -4,4 +4,4 @@ SELECT demons.id AS demon_id, demons.name AS "demon_name: String", demons.positi
 FROM demons
 INNER JOIN players AS verifiers ON verifiers.id=demons.verifier
 INNER JOIN players AS publishers ON publishers.id=demons.publisher
-WHERE demons.name=$1::CITEXT
\ No newline at end of file
+WHERE demons.name=$1::CITEXT AND demons.deleted_at IS NULL
\ No newline at end of file
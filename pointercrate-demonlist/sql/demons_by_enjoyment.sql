@@ -0,0 +1,22 @@
+WITH ratings AS (
+    SELECT demon, AVG(enjoyment)::FLOAT8 AS enjoyment, COUNT(*) AS ratings
+    FROM records
+    INNER JOIN demons ON demons.id = records.demon
+    WHERE status_ = 'APPROVED' AND enjoyment IS NOT NULL AND progress >= demons.requirement
+    GROUP BY demon
+    HAVING COUNT(*) >= $3
+)
+SELECT demons.id AS "demon_id!", demons.name AS "demon_name!: String", demons.position as "position!", demons.requirement as "requirement!", demons.level_id, CASE WHEN verifiers.link_banned THEN NULL ElSE demons.video::text END, demons.thumbnail, verifiers.id AS "verifier_id!", verifiers.name AS "verifier_name!: String", verifiers.banned AS "verifier_banned!", publishers.id AS "publisher_id!", publishers.name AS "publisher_name!: String", publishers.banned AS "publisher_banned!", difficulty::text as "difficulty!",
+       ratings.enjoyment AS "enjoyment!", ratings.ratings AS "ratings!"
+FROM demons
+    INNER JOIN ratings
+        ON ratings.demon = demons.id
+    INNER JOIN players as publishers
+        ON demons.publisher = publishers.id
+    INNER JOIN players AS verifiers
+        ON demons.verifier = verifiers.id
+WHERE demons.position IS NOT NULL
+  AND demons.deleted_at IS NULL
+  AND ($1::FLOAT8 IS NULL OR ratings.enjoyment < $1 OR (ratings.enjoyment = $1 AND demons.id > $2))
+ORDER BY ratings.enjoyment DESC, demons.id
+LIMIT $4
@@ -14,7 +14,9 @@ WHERE (demons.id < $1 OR $1 IS NULL)
   AND (verifiers.name::CITEXT = $8 OR $8 IS NULL)
   AND (publishers.id = $9 OR $9 IS NULL)
   AND (publishers.name::CITEXT = $10 OR $10 IS NULL)
-  AND (STRPOS(demons.name, $11::CITEXT) > 0 OR $11 is NULL)
+  AND (STRPOS(demons.name, $11::CITEXT) > 0 OR $11 is NULL
+       OR ($14 AND search_key($11) <> '' AND STRPOS(demons.search_key::CITEXT, search_key($11)::CITEXT) > 0))
   AND (demons.level_id = $12 OR $12 IS NULL)
+  AND demons.deleted_at IS NULL
 ORDER BY demons.id {}
 LIMIT $13
\ No newline at end of file
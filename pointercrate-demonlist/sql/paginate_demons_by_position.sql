@@ -4,8 +4,8 @@ SELECT demons.id AS demon_id, demons.name::text AS demon_name, demons.position,
 FROM demons
 INNER JOIN players AS verifiers ON verifiers.id=demons.verifier
 INNER JOIN players AS publishers ON publishers.id=demons.publisher
-WHERE (demons.position < $1 OR $1 IS NULL)
-  AND (demons.position > $2 OR $2 IS NULL)
+WHERE (demons.position < COALESCE((SELECT anchor.position FROM demons AS anchor WHERE anchor.id = $17), $1) OR $1 IS NULL)
+  AND (demons.position > COALESCE((SELECT anchor.position FROM demons AS anchor WHERE anchor.id = $18), $2) OR $2 IS NULL)
   AND (demons.name::CITEXT = $3 OR $3 IS NULL)
   AND (requirement = $4 OR $4 IS NULL)
   AND (requirement < $5 OR $5 IS NULL)
@@ -14,8 +14,12 @@ WHERE (demons.position < $1 OR $1 IS NULL)
   AND (verifiers.name::CITEXT = $8 OR $8 IS NULL)
   AND (publishers.id = $9 OR $9 IS NULL)
   AND (publishers.name::CITEXT = $10 OR $10 IS NULL)
-  AND (STRPOS(demons.name, $11::CITEXT) > 0 OR $11 is NULL)
+  AND (STRPOS(demons.name, $11::CITEXT) > 0 OR $11 is NULL
+       OR ($15 AND search_key($11) <> '' AND STRPOS(demons.search_key::CITEXT, search_key($11)::CITEXT) > 0))
   AND (demons.level_id = $12 OR $12 IS NULL)
+  AND (verifiers.nationality = $13 OR $13 IS NULL)
+  AND (demons.difficulty::text = ANY($16) OR $16 IS NULL)
   AND demons.position IS NOT NULL
+  AND demons.deleted_at IS NULL
 ORDER BY demons.position {}
-LIMIT $13
\ No newline at end of file
+LIMIT $14
\ No newline at end of file
@@ -0,0 +1,14 @@
+SELECT records.id, progress, enjoyment, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END,
+       players.id AS player_id, players.name::text AS player_name, players.banned AS player_banned,
+       demons.id AS demon_id, demons.name::text AS demon_name, demons.position,
+       record_additions.time AS submitted_at,
+       CAST(EXTRACT(EPOCH FROM (NOW() AT TIME ZONE 'utc') - record_additions.time) AS BIGINT) AS pending_seconds
+FROM records
+INNER JOIN players ON records.player = players.id
+INNER JOIN demons ON records.demon = demons.id
+LEFT OUTER JOIN record_additions ON record_additions.id = records.id
+WHERE status_ = 'SUBMITTED'
+  AND (records.id < $1 OR $1 IS NULL)
+  AND (records.id > $2 OR $2 IS NULL)
+ORDER BY records.id {}
+LIMIT $3
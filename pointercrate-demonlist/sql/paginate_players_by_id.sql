@@ -6,7 +6,8 @@ LEFT OUTER JOIN player_ranks ON player_ranks.id = players.id
 WHERE (players.id < $1 OR $1 IS NULL)
   AND (players.id > $2 OR $2 IS NULL)
   AND (players.name = $3::CITEXT OR $3 is NULL)
-  AND (STRPOS(players.name, $4::CITEXT) > 0 OR $4 is NULL)
+  AND (STRPOS(players.name, $4::CITEXT) > 0 OR $4 is NULL
+       OR ($10 AND search_key($4) <> '' AND STRPOS(players.search_key::CITEXT, search_key($4)::CITEXT) > 0))
   AND (banned = $5 OR $5 IS NULL)
   AND (nationality = $6 OR iso_country_code = $6 OR (nationality IS NULL AND $7) OR ($6 IS NULL AND NOT $7))
   AND (subdivision = $8 OR $8 IS NULL)
@@ -9,6 +9,8 @@ WHERE (records.id < $1 OR $1 IS NULL)
   AND (progress = $3 OR $3 IS NULL)
   AND (progress < $4 OR $4 IS NULL)
   AND (progress > $5 OR $5 IS NULL)
+  AND (progress >= $17 OR $17 IS NULL)
+  AND (progress <= $18 OR $18 IS NULL)
   AND (position = $6 OR $6 IS NULL)
   AND (position < $7 OR $7 IS NULL)
   AND (position > $8 OR $8 IS NULL)
@@ -18,5 +20,9 @@ WHERE (records.id < $1 OR $1 IS NULL)
   AND (records.video = $12 OR (records.video IS NULL AND $13) OR ($12 IS NULL AND NOT $13))
   AND (players.id = $14 OR $14 IS NULL)
   AND (records.submitter = $15 OR $15 IS NULL)
+  AND (players.nationality = $19 OR $19 IS NULL)
+  AND (records.approved_at >= $20 OR $20 IS NULL)
+  AND demons.deleted_at IS NULL
+  AND (status_ <> 'APPROVED' OR progress >= demons.requirement)
 ORDER BY id {}
 LIMIT $16
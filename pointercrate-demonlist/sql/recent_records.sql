@@ -0,0 +1,15 @@
+SELECT records.id, progress, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END, records.approved_at,
+       players.id AS player_id, players.name::text AS player_name, players.banned AS player_banned,
+       nationalities.nation::text, nationalities.iso_country_code::text,
+       demons.id AS demon_id, demons.name::text AS demon_name, demons.position
+FROM records
+INNER JOIN players ON records.player = players.id
+INNER JOIN demons ON records.demon = demons.id
+LEFT OUTER JOIN nationalities ON players.nationality = nationalities.iso_country_code
+WHERE status_ = 'APPROVED'
+  AND records.approved_at IS NOT NULL
+  AND demons.deleted_at IS NULL
+  AND records.progress >= demons.requirement
+  AND ($1::TIMESTAMP IS NULL OR records.approved_at < $1 OR (records.approved_at = $1 AND records.id < $2))
+ORDER BY records.approved_at DESC, records.id DESC
+LIMIT $3
@@ -1,4 +1,4 @@
-SELECT progress, enjoyment,
+SELECT records.id, progress, enjoyment, records.approved_by, records.progress_verified,
        CASE WHEN players.link_banned THEN NULL ELSE records.video::text END,
        CASE WHEN players.link_banned THEN NULL ELSE records.raw_footage::text END,
        status_::text AS "status!: String" ,
@@ -1,4 +1,40 @@
+use crate::demon::Difficulty;
 use pointercrate_core::util::from_env_or_default;
+use std::sync::OnceLock;
+
+/// The settings that need more than a simple `parse` call, parsed once by [`load`]
+struct Parsed {
+    difficulty_multipliers: Vec<(Difficulty, f64)>,
+}
+
+impl Parsed {
+    fn from_env() -> Result<Self, String> {
+        Ok(Parsed {
+            difficulty_multipliers: match std::env::var("DIFFICULTY_MULTIPLIERS") {
+                Ok(multipliers) => crate::demon::parse_difficulty_multipliers(&multipliers)?,
+                Err(_) => Vec::new(),
+            },
+        })
+    }
+}
+
+static PARSED: OnceLock<Parsed> = OnceLock::new();
+
+/// Parses and validates all settings that could be malformed, so that invalid configuration is
+/// noticed on startup instead of failing whichever request first needs the setting
+pub fn load() -> Result<(), String> {
+    let parsed = Parsed::from_env()?;
+
+    // Loading twice (e.g. when mounting the API on multiple rocket instances) keeps the first result
+    let _ = PARSED.set(parsed);
+
+    Ok(())
+}
+
+fn parsed() -> &'static Parsed {
+    // Only actually parses if `load` was never called, which the API does when being set up
+    PARSED.get_or_init(|| Parsed::from_env().unwrap_or_else(|err| panic!("invalid configuration: {}", err)))
+}
 
 pub fn list_size() -> i16 {
     from_env_or_default("LIST_SIZE", 50)
@@ -7,3 +43,244 @@ pub fn list_size() -> i16 {
 pub fn extended_list_size() -> i16 {
     from_env_or_default("EXTENDED_LIST_SIZE", 100)
 }
+
+/// Position bands used to assign a difficulty to new demons that are added without one
+///
+/// Configured as a comma separated list of `difficulty:last_position` pairs, ordered by position,
+/// for example `silent:5,legendary:15,extreme:40`.
+pub fn difficulty_bands() -> Option<String> {
+    std::env::var("DIFFICULTY_BANDS").ok()
+}
+
+/// Factors by which the scores of demons in the listed difficulty tiers are multiplied, on top of
+/// what the [score formula](crate::demon::ScoreFormula) gives for their position
+///
+/// Configured as a comma separated list of `difficulty:multiplier` pairs, for example
+/// `silent:1.5,legendary:1.25`. Tiers that are not listed keep a multiplier of `1.0`.
+pub fn difficulty_multipliers() -> &'static [(Difficulty, f64)] {
+    &parsed().difficulty_multipliers
+}
+
+/// The number of days after which rejected records are purged by the record cleanup. `0` disables
+/// purging
+pub fn rejected_record_retention_days() -> i32 {
+    from_env_or_default("REJECTED_RECORD_RETENTION_DAYS", 0)
+}
+
+/// The fields which, in addition to the ones that are always mandatory, must be provided when adding
+/// a new demon
+///
+/// Configured as a comma separated list of `video`, `level_id`, `difficulty` and `creators`, defaults
+/// to none of them. Note that without this, a difficulty is still needed for positions not covered by
+/// the [difficulty bands](difficulty_bands).
+pub fn required_demon_fields() -> Vec<String> {
+    match std::env::var("REQUIRED_DEMON_FIELDS") {
+        Ok(list) => list
+            .split(',')
+            .map(|field| field.trim().to_string())
+            .filter(|field| !field.is_empty())
+            .inspect(|field| {
+                assert!(
+                    crate::demon::OPTIONAL_DEMON_FIELDS.contains(&field.as_str()),
+                    "REQUIRED_DEMON_FIELDS contains unknown field '{}'",
+                    field
+                )
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Whether the enjoyment ratings of individual records are visible to everyone, instead of only to
+/// list staff
+pub fn public_enjoyment() -> bool {
+    from_env_or_default("PUBLIC_ENJOYMENT", true)
+}
+
+/// Whether only 100% records may carry an enjoyment rating. Off by default, in which case any
+/// record can be rated
+pub fn enjoyment_requires_completion() -> bool {
+    from_env_or_default("ENJOYMENT_REQUIRES_COMPLETION", false)
+}
+
+/// Whether demon and player searches should also match names with emoji removed, so that e.g.
+/// "bloodbath" finds "Bloodbath 🔥"
+pub fn strip_emoji_in_search() -> bool {
+    from_env_or_default("STRIP_EMOJI_IN_SEARCH", false)
+}
+
+/// Whether records with equal progress on a demon should be listed most recently approved first,
+/// instead of earliest approved first
+pub fn latest_approval_first() -> bool {
+    from_env_or_default("LATEST_APPROVAL_FIRST", false)
+}
+
+/// By how much a record patch may lower a record's progress without setting `force`. `None` (the
+/// default) allows arbitrary decreases, `0` disallows decreasing progress entirely
+pub fn max_progress_decrease() -> Option<i16> {
+    std::env::var("MAX_PROGRESS_DECREASE")
+        .ok()
+        .map(|delta| delta.trim().parse().expect("MAX_PROGRESS_DECREASE must be a number"))
+}
+
+/// How many approved records need to carry an enjoyment rating before a demon's mean enjoyment is
+/// considered meaningful. Demons with fewer ratings are left out of enjoyment-based rankings
+pub fn min_enjoyment_ratings() -> i64 {
+    from_env_or_default("MIN_ENJOYMENT_RATINGS", 1)
+}
+
+/// Whether archiving a demon rejects the records for it that are still waiting in the queue. If not,
+/// they stay in the queue and can still be reviewed as usual
+pub fn reject_pending_on_archive() -> bool {
+    from_env_or_default("REJECT_PENDING_ON_ARCHIVE", false)
+}
+
+/// Whether banning a player also unverifies any verified claim on them. Verifying a claim on an
+/// already banned player is never possible
+pub fn revoke_claims_on_ban() -> bool {
+    from_env_or_default("REVOKE_CLAIMS_ON_BAN", false)
+}
+
+/// For how many hours a newly placed demon does not give any points. Defaults to 0, meaning new
+/// demons award points immediately
+pub fn new_demon_grace_hours() -> i32 {
+    from_env_or_default("NEW_DEMON_GRACE_HOURS", 0)
+}
+
+/// Hard upper limit on the number of (non-archived) demons on the list, including the legacy list.
+/// Unlimited if not set
+pub fn max_list_length() -> Option<i16> {
+    std::env::var("MAX_LIST_LENGTH")
+        .ok()
+        .map(|length| length.trim().parse().expect("MAX_LIST_LENGTH must be a number"))
+}
+
+/// Whether placing a demon that pushes another one past [`max_list_length`] archives the pushed out
+/// demon. If not, such placements are rejected
+pub fn archive_list_overflow() -> bool {
+    from_env_or_default("ARCHIVE_LIST_OVERFLOW", false)
+}
+
+/// The canned note configured for the given rejection reason, if any
+///
+/// Configured as a semicolon separated list of `reason:note` pairs. The notes may refer to the
+/// rejected record via `{player}`, `{demon}` and `{progress}`.
+pub fn rejection_note_template(reason: &str) -> Option<String> {
+    std::env::var("REJECTION_NOTE_TEMPLATES")
+        .ok()?
+        .split(';')
+        .filter_map(|entry| entry.split_once(':'))
+        .find(|(key, _)| key.trim() == reason)
+        .map(|(_, template)| template.trim().to_string())
+}
+
+/// The hosts video URLs may point to. If not set, the hosts pointercrate knows how to normalize
+/// video URLs for are allowed
+pub fn video_hosts() -> Option<Vec<String>> {
+    std::env::var("VIDEO_HOSTS")
+        .ok()
+        .map(|hosts| hosts.split(',').map(|host| host.trim().to_string()).collect())
+}
+
+/// How many records a single submitter can submit per hour, counting all their records that have
+/// not been deleted. List staff are not subject to this. `0` (the default) disables the limit.
+pub fn submitter_hourly_limit() -> i64 {
+    from_env_or_default("SUBMITTER_HOURLY_LIMIT", 0)
+}
+
+/// Whether claims can only be verified if geolocating the claimant placed them in the claimed
+/// player's country. If not set, mismatches are merely logged.
+pub fn strict_claim_geolocation() -> bool {
+    from_env_or_default("STRICT_CLAIM_GEOLOCATION", false)
+}
+
+/// Whether player names should be compared after unicode normalization and homoglyph folding when
+/// resolving them to players, instead of only case insensitively
+pub fn normalize_player_names() -> bool {
+    from_env_or_default("NORMALIZE_PLAYER_NAMES", false)
+}
+
+/// Whether adding a player as a creator of a demon they already are a creator of should silently do
+/// nothing instead of failing
+pub fn ignore_duplicate_creators() -> bool {
+    from_env_or_default("IGNORE_DUPLICATE_CREATORS", false)
+}
+
+/// The maximal number of creators a single demon can have
+pub fn max_creators() -> i64 {
+    from_env_or_default("MAX_CREATORS", 100)
+}
+
+/// Whether record submissions from non-staff are currently rejected. Unlike maintenance mode, this
+/// leaves everything else (including list staff adding records) working
+pub fn submissions_frozen() -> bool {
+    from_env_or_default("SUBMISSIONS_FROZEN", false)
+}
+
+/// Whether newly submitted and newly approved records must have a video
+///
+/// Records which already lacked a video when this requirement was introduced are exempt from it, as
+/// are records list staff add without a video (and verification records). The exemption is stored
+/// per record, and has to be granted by hand to any other record.
+pub fn require_video() -> bool {
+    from_env_or_default("REQUIRE_VIDEO", false)
+}
+
+/// Whether submissions of 100% records need to include a video, even if videos are otherwise optional
+pub fn require_video_for_completions() -> bool {
+    from_env_or_default("REQUIRE_VIDEO_FOR_COMPLETIONS", false)
+}
+
+/// Whether submissions for demons of the given difficulty need to include raw footage
+///
+/// Configured as a comma separated list of difficulties, defaults to all of them.
+pub fn raw_footage_required_for(difficulty: Difficulty) -> bool {
+    difficulty_listed_in_env("RAW_FOOTAGE_REQUIRED_FOR", difficulty, true)
+}
+
+/// Whether submissions for demons of the given difficulty need to include an enjoyment rating
+///
+/// Configured as a comma separated list of difficulties, defaults to none of them.
+pub fn enjoyment_required_for(difficulty: Difficulty) -> bool {
+    difficulty_listed_in_env("ENJOYMENT_REQUIRED_FOR", difficulty, false)
+}
+
+fn difficulty_listed_in_env(key: &str, difficulty: Difficulty, default: bool) -> bool {
+    match std::env::var(key) {
+        Ok(list) => list
+            .split(',')
+            .any(|listed| listed.trim().eq_ignore_ascii_case(&difficulty.to_sql())),
+        Err(_) => default,
+    }
+}
+
+/// The thumbnail shown for demons for which no thumbnail is set and none can be derived from their video
+///
+/// Defaults to the placeholder image served by the demonlist API.
+pub fn placeholder_thumbnail() -> String {
+    from_env_or_default("PLACEHOLDER_THUMBNAIL", "/static/demonlist/images/placeholder-thumbnail.svg".to_string())
+}
+
+/// The lowest requirement any demon on the list may have
+pub fn minimum_requirement() -> i16 {
+    from_env_or_default("MINIMUM_REQUIREMENT", 0)
+}
+
+/// Whether submissions falling below a raised requirement should be rejected (instead of deleted) and moved back into the
+/// submission queue once the requirement is lowered enough for them to qualify again
+pub fn reevaluate_on_requirement_decrease() -> bool {
+    from_env_or_default("REEVALUATE_ON_REQUIREMENT_DECREASE", false)
+}
+
+/// The ranks for which the score needed to reach them is reported by the ranking thresholds endpoint
+///
+/// Configured as a comma separated list of ranks, defaults to `10,50,100`.
+pub fn rank_milestones() -> Vec<i64> {
+    match std::env::var("RANK_MILESTONES") {
+        Ok(list) => list
+            .split(',')
+            .map(|rank| rank.trim().parse().expect("RANK_MILESTONES must be a comma separated list of ranks"))
+            .collect(),
+        Err(_) => vec![10, 50, 100],
+    }
+}
@@ -32,10 +32,11 @@ impl Creator {
     }
 }
 
+/// The creators of the given demon, ordered by their priority (the main creator first)
 pub async fn creators_of(demon: &MinimalDemon, connection: &mut PgConnection) -> Result<Vec<DatabasePlayer>> {
     let mut stream = sqlx::query!(
         r#"SELECT players.id, players.name, players.banned FROM players INNER JOIN creators ON players.id = creators.creator WHERE 
-         creators.demon = $1"#,
+         creators.demon = $1 ORDER BY creators.priority, creators.creator"#,
         demon.id
     )
     .fetch(connection);
@@ -58,7 +59,7 @@ pub async fn created_by(player_id: i32, connection: &mut PgConnection) -> Result
     query_many_demons!(
         connection,
         r#"SELECT demons.id, demons.name, demons.position FROM demons INNER JOIN creators ON demons.id = creators.demon WHERE
-         creators.creator=$1"#,
+         creators.creator=$1 AND demons.deleted_at IS NULL"#,
         player_id
     )
 }
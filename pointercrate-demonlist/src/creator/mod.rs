@@ -1,10 +1,12 @@
 // pub use self::post::PostCreator;
 pub use self::get::{created_by, creators_of};
 use derive_more::Display;
+pub use patch::PatchCreator;
 pub use post::PostCreator;
 
 mod delete;
 mod get;
+mod patch;
 mod post;
 
 #[derive(Debug, Display, Hash)]
@@ -0,0 +1,44 @@
+use crate::{creator::Creator, error::Result};
+use log::info;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Deserialize)]
+pub struct PatchCreator {
+    pub priority: usize,
+}
+
+impl Creator {
+    /// Moves this creator to the given place among the creators of its demon, with `0` being the
+    /// main creator. Priorities past the last creator move it to the end.
+    ///
+    /// The other creators keep their relative order, and all priorities are renumbered to be
+    /// consecutive again.
+    pub async fn set_priority(&self, priority: usize, connection: &mut PgConnection) -> Result<()> {
+        info!("Moving {} to priority {}", self, priority);
+
+        let mut creators: Vec<i32> = sqlx::query!(
+            "SELECT creator FROM creators WHERE demon = $1 ORDER BY priority, creator",
+            self.demon
+        )
+        .fetch_all(&mut *connection)
+        .await?
+        .into_iter()
+        .map(|row| row.creator)
+        .filter(|&creator| creator != self.creator)
+        .collect();
+
+        creators.insert(priority.min(creators.len()), self.creator);
+
+        sqlx::query!(
+            "UPDATE creators SET priority = ordered.index - 1 FROM UNNEST($2::INTEGER[]) WITH ORDINALITY AS ordered(creator, index) \
+             WHERE creators.demon = $1 AND creators.creator = ordered.creator",
+            self.demon,
+            &creators
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -13,16 +13,54 @@ pub struct PostCreator {
 }
 
 impl Creator {
+    /// Adds the player with the given name as a creator of the given demon, creating the player if
+    /// necessary
+    pub async fn insert_by_name(demon: &MinimalDemon, name: &str, connection: &mut PgConnection) -> Result<DatabasePlayer> {
+        let name = name.trim();
+
+        if name.is_empty() {
+            return Err(DemonlistError::CreatorNameEmpty);
+        }
+
+        let player = DatabasePlayer::by_name_or_create(name, &mut *connection).await?;
+
+        Creator::insert(demon, &player, connection).await?;
+
+        Ok(player)
+    }
+
+    /// Adds the given player as a creator of the given demon
+    ///
+    /// If they already are one, this either fails with [`DemonlistError::CreatorExists`] or does
+    /// nothing, depending on [`crate::config::ignore_duplicate_creators`].
     pub async fn insert(demon: &MinimalDemon, player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Creator> {
         match Creator::get(demon, player, connection).await {
+            Ok(existing) if crate::config::ignore_duplicate_creators() => return Ok(existing),
             Ok(_) => return Err(DemonlistError::CreatorExists),
             Err(DemonlistError::CreatorNotFound { .. }) => (),
             Err(err) => return Err(err),
         }
 
-        let _ = sqlx::query!("INSERT INTO creators (creator, demon) VALUES ($1, $2)", player.id, demon.id)
-            .execute(connection)
-            .await?;
+        let creator_count = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM creators WHERE demon = $1"#, demon.id)
+            .fetch_one(&mut *connection)
+            .await?
+            .count;
+
+        if creator_count >= crate::config::max_creators() {
+            return Err(DemonlistError::TooManyCreators {
+                maximum: crate::config::max_creators(),
+            });
+        }
+
+        // New creators go last, see `Creator::set_priority` for moving them up
+        let _ = sqlx::query!(
+            "INSERT INTO creators (creator, demon, priority) VALUES ($1, $2, (SELECT COALESCE(MAX(priority) + 1, 0) FROM creators WHERE \
+             demon = $2))",
+            player.id,
+            demon.id
+        )
+        .execute(connection)
+        .await?;
 
         Ok(Creator {
             demon: demon.id,
@@ -1,10 +1,20 @@
-use crate::error::Result;
+use crate::error::{DemonlistError, Result};
 
-use crate::demon::{MinimalDemon, Difficulty};
+use crate::{
+    demon::{Difficulty, MinimalDemon},
+    record::{
+        audit::{audit_log_for_record, RecordModificationData},
+        note::Note,
+    },
+};
 use chrono::{NaiveDateTime, NaiveTime};
 use futures::StreamExt;
-use pointercrate_core::audit::{AuditLogEntry, AuditLogEntryType, NamedId};
-use serde::Serialize;
+use pointercrate_core::{
+    audit::{AuditLogEntry, AuditLogEntryType, NamedId},
+    error::CoreError,
+    pagination::{DEFAULT_ENTRIES_PER_PAGE, ENTRIES_PER_PAGE},
+};
+use serde::{Deserialize, Serialize};
 use sqlx::PgConnection;
 use std::collections::HashMap;
 
@@ -16,7 +26,24 @@ pub struct DemonModificationData {
     pub video: Option<String>,
     pub verifier: Option<NamedId>,
     pub publisher: Option<NamedId>,
+
+    /// The difficulty before this modification, if it was changed
     pub difficulty: Option<Difficulty>,
+
+    /// The difficulty after this modification, if it was changed. Only `None` then if the demon has
+    /// since been deleted.
+    pub new_difficulty: Option<Difficulty>,
+
+    /// Whether this modification took the demon off the list or put it back, if it did either
+    pub deletion: Option<DeletionChange>,
+}
+
+/// How a modification changed whether a demon is [deleted](crate::demon::Demon::delete)
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeletionChange {
+    Deleted,
+    Restored,
 }
 
 #[derive(Serialize, Debug)]
@@ -180,6 +207,56 @@ pub async fn movement_log_for_demon(demon_id: i32, connection: &mut PgConnection
     Ok(movement_log)
 }
 
+/// The highest (numerically lowest) position a demon has ever been placed at
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct PeakPosition {
+    pub position: i16,
+
+    /// When the demon first reached its peak position. `None` if it was already there when the audit
+    /// log started tracking it
+    pub reached: Option<NaiveDateTime>,
+}
+
+/// Reconstructs the peak position of the given demon from its position history
+///
+/// Since modification entries store the position a demon had _before_ the change, every one of them
+/// marks the end of a period in which the demon sat at that position. The temporary `-1` position
+/// used while moving demons around is ignored. A demon whose position never changed peaked at its
+/// current position.
+pub async fn peak_position(demon: &MinimalDemon, connection: &mut PgConnection) -> Result<PeakPosition> {
+    let added = sqlx::query_scalar!("SELECT time FROM demon_additions WHERE id = $1", demon.id)
+        .fetch_optional(&mut *connection)
+        .await?;
+
+    let changes = sqlx::query!(
+        r#"SELECT time, position AS "position!" FROM demon_modifications
+           WHERE id = $1 AND position IS NOT NULL AND position <> -1
+           ORDER BY time"#,
+        demon.id
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    // (position, time since which the demon was at that position)
+    let mut periods = Vec::with_capacity(changes.len() + 1);
+    let mut since = added;
+
+    for change in changes {
+        periods.push((change.position, since));
+        since = Some(change.time);
+    }
+
+    periods.push((demon.position, since));
+
+    // `min_by_key` returns the first minimum, which is the earliest time the peak was reached
+    let (position, reached) = periods
+        .into_iter()
+        .min_by_key(|&(position, _)| position)
+        .unwrap_or((demon.position, added));
+
+    Ok(PeakPosition { position, reached })
+}
+
 pub async fn audit_log_for_demon(demon_id: i32, connection: &mut PgConnection) -> Result<Vec<AuditLogEntry<DemonModificationData>>> {
     let mut entries = Vec::new();
 
@@ -206,6 +283,11 @@ pub async fn audit_log_for_demon(demon_id: i32, connection: &mut PgConnection) -
         });
     }
 
+    let current_difficulty = sqlx::query_scalar!(r#"SELECT difficulty::text AS "difficulty!" FROM demons WHERE id = $1"#, demon_id)
+        .fetch_optional(&mut *connection)
+        .await?
+        .map(|difficulty| Difficulty::from_sql(&difficulty));
+
     let mut modification_stream = sqlx::query!(
         r#"SELECT time,
                 audit_id,
@@ -219,7 +301,8 @@ pub async fn audit_log_for_demon(demon_id: i32, connection: &mut PgConnection) -
                 verifiers.name::text as verifier_name,
                 publisher,
                 publishers.name::text as publisher_name,
-                difficulty::text
+                difficulty::text,
+                deleted
            FROM demon_modifications
            LEFT OUTER JOIN members ON members.member_id = userid
            LEFT OUTER JOIN players AS verifiers ON verifier=verifiers.id
@@ -247,6 +330,12 @@ pub async fn audit_log_for_demon(demon_id: i32, connection: &mut PgConnection) -
                     Some(diff) => Some(Difficulty::from_sql(&diff)),
                     None => None
                 },
+                new_difficulty: None,
+                // The log stores whether the demon was deleted _before_ the modification
+                deletion: row.deleted.map(|was_deleted| match was_deleted {
+                    true => DeletionChange::Restored,
+                    false => DeletionChange::Deleted,
+                }),
                 verifier: match row.verifier {
                     Some(id) => Some(NamedId {
                         name: row.verifier_name,
@@ -269,5 +358,212 @@ pub async fn audit_log_for_demon(demon_id: i32, connection: &mut PgConnection) -
         })
     }
 
+    // The log only stores the value a field had before a modification. The value it had afterwards is
+    // the one stored by the next modification of that field, or the current one if there is none.
+    let mut new_difficulty = current_difficulty;
+
+    for entry in entries.iter_mut().rev() {
+        if let AuditLogEntryType::Modification(ref mut data) = entry.r#type {
+            if let Some(old_difficulty) = data.difficulty {
+                data.new_difficulty = new_difficulty;
+                new_difficulty = Some(old_difficulty);
+            }
+        }
+    }
+
     Ok(entries)
 }
+
+/// A single event in the moderation history of a demon
+#[derive(Serialize)]
+#[serde(tag = "source", content = "entry", rename_all = "snake_case")]
+pub enum DemonHistoryEntry {
+    /// An entry from the demon's own audit log
+    Demon(AuditLogEntry<DemonModificationData>),
+
+    /// The submission, deletion or a status change of a record on this demon
+    Record(AuditLogEntry<RecordModificationData>),
+
+    /// The creation of a note on a record on this demon
+    Note {
+        time: NaiveDateTime,
+        entry_id: i32,
+        record: i32,
+        note: Note,
+    },
+}
+
+impl DemonHistoryEntry {
+    pub fn time(&self) -> NaiveDateTime {
+        match self {
+            DemonHistoryEntry::Demon(entry) => entry.time,
+            DemonHistoryEntry::Record(entry) => entry.time,
+            DemonHistoryEntry::Note { time, .. } => *time,
+        }
+    }
+
+    /// The id of the audit log entry this event was read from. Together with [`DemonHistoryEntry::time`],
+    /// this is the position of the event in the history.
+    pub fn entry_id(&self) -> i32 {
+        match self {
+            DemonHistoryEntry::Demon(entry) => entry.entry_id,
+            DemonHistoryEntry::Record(entry) => entry.entry_id,
+            DemonHistoryEntry::Note { entry_id, .. } => *entry_id,
+        }
+    }
+}
+
+/// Pagination for [`history_of_demon`]
+///
+/// Events are ordered by their time, and then by the id of their audit log entry, as all events
+/// caused by the same transaction share a time. Both bounds are exclusive, and consist of a time and
+/// optionally an entry id. Without an entry id, a bound excludes all events at the given time. If
+/// only `before` is given, the most recent `limit` events before it are returned, otherwise the
+/// oldest `limit` events after `after`.
+#[derive(Deserialize, Debug, Default)]
+pub struct DemonHistoryPagination {
+    #[serde(default)]
+    pub before: Option<NaiveDateTime>,
+
+    #[serde(default)]
+    pub before_entry: Option<i32>,
+
+    #[serde(default)]
+    pub after: Option<NaiveDateTime>,
+
+    #[serde(default)]
+    pub after_entry: Option<i32>,
+
+    #[serde(default = "default_history_limit")]
+    pub limit: i32,
+}
+
+impl DemonHistoryPagination {
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=ENTRIES_PER_PAGE).contains(&self.limit) {
+            return Err(CoreError::InvalidPaginationLimit.into());
+        }
+
+        if let (Some(before), Some(after)) = (self.before, self.after) {
+            if (before, self.before_entry.unwrap_or(i32::MIN)) < (after, self.after_entry.unwrap_or(i32::MAX)) {
+                return Err(CoreError::AfterSmallerBefore.into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn default_history_limit() -> i32 {
+    DEFAULT_ENTRIES_PER_PAGE
+}
+
+/// Merges the audit log of the given demon with the status changes and notes of all records that
+/// are (or ever were) on it, in chronological order.
+///
+/// Records that were moved to a different demon are included, as their history on this demon is
+/// still relevant.
+pub async fn history_of_demon(
+    demon_id: i32, pagination: &DemonHistoryPagination, connection: &mut PgConnection,
+) -> Result<Vec<DemonHistoryEntry>> {
+    pagination.validate()?;
+
+    // When paging backwards, the events closest to `before` are the ones wanted
+    let newest_first = pagination.before.is_some() && pagination.after.is_none();
+
+    // Only the bare events are selected here. Their details are then taken from the audit logs of the
+    // demon and of the records on the page. A missing entry id makes a bound cover its entire time, as
+    // audit ids are positive.
+    let mut page = sqlx::query!(
+        r#"WITH relevant_records AS (
+               SELECT id FROM records WHERE demon = $1 UNION SELECT id FROM record_modifications WHERE demon = $1
+           )
+           SELECT source AS "source!", time AS "time!", audit_id AS "audit_id!", id AS "id!", record FROM (
+               SELECT 'demon' AS source, time, audit_id, id, NULL::INTEGER AS record FROM demon_additions WHERE id = $1
+               UNION ALL
+               SELECT 'demon', time, audit_id, id, NULL FROM demon_modifications WHERE id = $1
+               UNION ALL
+               SELECT 'record', time, audit_id, id, NULL FROM record_additions WHERE id IN (SELECT id FROM relevant_records)
+               UNION ALL
+               SELECT 'record', time, audit_id, id, NULL FROM record_modifications
+               WHERE status_ IS NOT NULL AND id IN (SELECT id FROM relevant_records)
+               UNION ALL
+               SELECT 'record', time, audit_id, id, NULL FROM record_deletions WHERE id IN (SELECT id FROM relevant_records)
+               UNION ALL
+               SELECT 'note', rna.time, rna.audit_id, rna.id, record_notes.record FROM record_notes_additions AS rna
+               INNER JOIN record_notes ON record_notes.id = rna.id
+               WHERE record_notes.record IN (SELECT id FROM relevant_records)
+           ) AS history
+           WHERE ($2::TIMESTAMP IS NULL OR (time, audit_id) < ($2, COALESCE($3, -2147483648)))
+             AND ($4::TIMESTAMP IS NULL OR (time, audit_id) > ($4, COALESCE($5, 2147483647)))
+           ORDER BY CASE WHEN $6 THEN time END DESC, CASE WHEN $6 THEN audit_id END DESC, time, audit_id
+           LIMIT $7"#,
+        demon_id,
+        pagination.before,
+        pagination.before_entry,
+        pagination.after,
+        pagination.after_entry,
+        newest_first,
+        pagination.limit as i64
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    if newest_first {
+        page.reverse();
+    }
+
+    let mut demon_log = None;
+    let mut record_logs = HashMap::new();
+    let mut history = Vec::with_capacity(page.len());
+
+    for event in page {
+        // Entries can only be missing from the logs if they were deleted since the page was selected,
+        // in which case they are no longer part of the history
+        let entry = match event.source.as_str() {
+            "demon" => {
+                if demon_log.is_none() {
+                    demon_log = Some(index_by_entry(audit_log_for_demon(demon_id, &mut *connection).await?));
+                }
+
+                demon_log
+                    .as_mut()
+                    .and_then(|log| log.remove(&event.audit_id))
+                    .map(DemonHistoryEntry::Demon)
+            },
+            "record" => {
+                if !record_logs.contains_key(&event.id) {
+                    let log = index_by_entry(audit_log_for_record(event.id, &mut *connection).await?);
+
+                    record_logs.insert(event.id, log);
+                }
+
+                record_logs
+                    .get_mut(&event.id)
+                    .and_then(|log| log.remove(&event.audit_id))
+                    .map(DemonHistoryEntry::Record)
+            },
+            _ => match event.record {
+                Some(record) => match Note::by_id(record, event.id, &mut *connection).await {
+                    Ok(note) => Some(DemonHistoryEntry::Note {
+                        time: event.time,
+                        entry_id: event.audit_id,
+                        record,
+                        note,
+                    }),
+                    Err(DemonlistError::NoteNotFound { .. }) => None,
+                    Err(err) => return Err(err),
+                },
+                None => None,
+            },
+        };
+
+        history.extend(entry);
+    }
+
+    Ok(history)
+}
+
+fn index_by_entry<T>(log: Vec<AuditLogEntry<T>>) -> HashMap<i32, AuditLogEntry<T>> {
+    log.into_iter().map(|entry| (entry.entry_id, entry)).collect()
+}
@@ -0,0 +1,82 @@
+use crate::{
+    demon::Demon,
+    error::{DemonlistError, Result},
+    player::recompute_scores,
+};
+use log::info;
+use sqlx::PgConnection;
+
+impl Demon {
+    /// Takes this demon off the list, moving every demon below it up by one position
+    ///
+    /// The demon is only marked as deleted, and hidden from everything showing the list. Its
+    /// records (which stop giving points) and audit log are kept, which allows it to be
+    /// [restored](Demon::restore) later on.
+    ///
+    /// Must run inside a transaction!
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        Demon::lock_positions(&mut *connection).await?;
+
+        info!("Deleting demon {}", self);
+
+        sqlx::query!(
+            "UPDATE demons SET deleted_at = (NOW() AT TIME ZONE 'utc') WHERE id = $1",
+            self.base.id
+        )
+        .execute(&mut *connection)
+        .await?;
+        sqlx::query!(
+            "UPDATE demons SET position = position - 1 WHERE position > $1 AND deleted_at IS NULL",
+            self.base.position
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        recompute_scores(connection).await?;
+
+        Ok(())
+    }
+
+    /// Puts a [deleted](Demon::delete) demon back onto the list
+    ///
+    /// The demon returns to the position it was deleted from, or to the end of the list if the
+    /// list has since gotten shorter than that. As archived demons are kept below all others, a
+    /// non-archived demon is never restored below an archived one, and vice versa.
+    ///
+    /// Must run inside a transaction!
+    pub async fn restore(demon_id: i32, connection: &mut PgConnection) -> Result<Demon> {
+        Demon::lock_positions(&mut *connection).await?;
+
+        let row = sqlx::query!(
+            "SELECT position, archived FROM demons WHERE id = $1 AND deleted_at IS NOT NULL",
+            demon_id
+        )
+        .fetch_optional(&mut *connection)
+        .await?
+        .ok_or(DemonlistError::DemonNotFound { demon_id })?;
+        let first_archived = Demon::max_active_position(&mut *connection).await? + 1;
+        let position = if row.archived {
+            let after_last = Demon::max_position(&mut *connection).await? + 1;
+
+            row.position.clamp(first_archived, after_last)
+        } else {
+            row.position.min(first_archived)
+        };
+
+        info!("Restoring demon with id {} at position {}", demon_id, position);
+
+        Demon::shift_down(position, &mut *connection).await?;
+
+        sqlx::query!(
+            "UPDATE demons SET deleted_at = NULL, position = $2 WHERE id = $1",
+            demon_id,
+            position
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        recompute_scores(&mut *connection).await?;
+
+        Demon::by_id(demon_id, connection).await
+    }
+}
@@ -1,27 +1,86 @@
 use crate::{
+    config,
     creator::creators_of,
-    demon::{Demon, Difficulty, FullDemon, MinimalDemon, TimeShiftedDemon},
+    demon::{
+        audit::peak_position, progress_histogram, resolve_thumbnail, Demon, DemonEnjoymentPagination, DemonNeighbors, DemonPosition,
+        Difficulty, EnjoymentStats, FullDemon, ListPositions, MinimalDemon, RatedDemon, TimeShiftedDemon, MAX_SEARCH_RESULTS,
+        MIN_SEARCH_LENGTH,
+    },
     error::{DemonlistError, Result},
     player::DatabasePlayer,
-    record::approved_records_on,
+    record::{approved_records_on, RecordOrder},
 };
 use chrono::NaiveDateTime;
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use sqlx::{Error, PgConnection};
 
 impl MinimalDemon {
     pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<MinimalDemon> {
-        sqlx::query_as!(MinimalDemon, r#"SELECT id, name, position FROM demons WHERE id = $1"#, id)
-            .fetch_one(connection)
-            .await
-            .map_err(|err| match err {
-                Error::RowNotFound => DemonlistError::DemonNotFound { demon_id: id },
-                _ => err.into(),
-            })
+        sqlx::query_as!(
+            MinimalDemon,
+            r#"SELECT id, name, position FROM demons WHERE id = $1 AND deleted_at IS NULL"#,
+            id
+        )
+        .fetch_one(connection)
+        .await
+        .map_err(|err| match err {
+            Error::RowNotFound => DemonlistError::DemonNotFound { demon_id: id },
+            _ => err.into(),
+        })
+    }
+
+    /// The closest non-archived demons above and below this one
+    pub async fn neighbors(&self, connection: &mut PgConnection) -> Result<DemonNeighbors> {
+        let previous = sqlx::query_as!(
+            MinimalDemon,
+            "SELECT id, name, position FROM demons WHERE position < $1 AND NOT archived AND deleted_at IS NULL ORDER BY position DESC \
+             LIMIT 1",
+            self.position
+        )
+        .fetch_optional(&mut *connection)
+        .await?;
+        let next = sqlx::query_as!(
+            MinimalDemon,
+            "SELECT id, name, position FROM demons WHERE position > $1 AND NOT archived AND deleted_at IS NULL ORDER BY position LIMIT 1",
+            self.position
+        )
+        .fetch_optional(&mut *connection)
+        .await?;
+
+        Ok(DemonNeighbors { previous, next })
+    }
+
+    /// Finds the demons whose names are most similar to `query`, best match first
+    ///
+    /// Matches are based on trigram similarity of the names' search keys, so small typos are
+    /// tolerated. Demons whose name contains the query are always included. At most `limit` (but
+    /// never more than [`MAX_SEARCH_RESULTS`]) demons are returned.
+    pub async fn search(query: &str, limit: i64, connection: &mut PgConnection) -> Result<Vec<MinimalDemon>> {
+        let query = query.trim();
+
+        if query.chars().count() < MIN_SEARCH_LENGTH {
+            return Err(DemonlistError::SearchQueryTooShort {
+                min_length: MIN_SEARCH_LENGTH,
+            });
+        }
+
+        Ok(sqlx::query_as!(
+            MinimalDemon,
+            "SELECT id, name, position FROM demons WHERE (search_key % search_key($1) OR strpos(search_key, search_key($1)) > 0) \
+             AND deleted_at IS NULL ORDER BY similarity(search_key, search_key($1)) DESC, position LIMIT $2",
+            query,
+            limit.clamp(1, MAX_SEARCH_RESULTS)
+        )
+        .fetch_all(connection)
+        .await?)
     }
 
     pub async fn by_name(name: &str, connection: &mut PgConnection) -> Result<MinimalDemon> {
-        let mut stream = sqlx::query!(r#"SELECT id, name, position FROM demons WHERE name = $1"#, name.to_string()).fetch(connection);
+        let mut stream = sqlx::query!(
+            r#"SELECT id, name, position FROM demons WHERE name = $1 AND deleted_at IS NULL"#,
+            name.to_string()
+        )
+        .fetch(connection);
 
         let mut demon = None;
         let mut further_demons = Vec::new();
@@ -65,18 +124,27 @@ impl FullDemon {
     pub async fn by_position(position: i16, connection: &mut PgConnection) -> Result<FullDemon> {
         Demon::by_position(position, connection).await?.upgrade(connection).await
     }
+
+    /// Fills in [`FullDemon::peak_position`] from this demon's position history
+    pub async fn with_peak_position(mut self, connection: &mut PgConnection) -> Result<FullDemon> {
+        self.peak_position = Some(peak_position(&self.demon.base, connection).await?);
+
+        Ok(self)
+    }
 }
 
 // FIXME: optimally, we want to only have one of these
 impl Demon {
     async fn upgrade(self, connection: &mut PgConnection) -> Result<FullDemon> {
         let creators = creators_of(&self.base, connection).await?;
-        let records = approved_records_on(&self.base, connection).await?;
+        let records = approved_records_on(&self.base, RecordOrder::default(), connection).await?;
 
         Ok(FullDemon {
             demon: self,
             creators,
             records,
+            peak_position: None,
+            below_requirement: Vec::new(),
         })
     }
 
@@ -101,6 +169,50 @@ impl Demon {
                 _ => err.into(),
             })
     }
+
+    /// Aggregates the enjoyment ratings of this demon's approved records
+    pub async fn enjoyment_stats(&self, connection: &mut PgConnection) -> Result<EnjoymentStats> {
+        let rows = sqlx::query!(
+            r#"SELECT enjoyment AS "enjoyment!", COUNT(*) AS "count!" FROM records
+               WHERE demon = $1 AND status_ = 'APPROVED' AND enjoyment IS NOT NULL AND progress >= $2
+               GROUP BY enjoyment"#,
+            self.base.id,
+            self.requirement
+        )
+        .fetch_all(connection)
+        .await?;
+
+        let mut histogram = [0; 11];
+
+        for row in rows {
+            histogram[row.enjoyment as usize] = row.count;
+        }
+
+        Ok(EnjoymentStats::from_histogram(histogram))
+    }
+
+    /// Counts this demon's approved records by progress, in buckets of `bucket_size` percent each
+    ///
+    /// Returns `(lower bound, count)` pairs, ordered by progress. The first bucket starts at the
+    /// requirement, and empty buckets are included. Completions are always counted separately in
+    /// a final bucket `100`, so a demon with requirement 100 has exactly one bucket. A `bucket_size`
+    /// below 1 is treated as 1.
+    pub async fn progress_distribution(&self, bucket_size: i16, connection: &mut PgConnection) -> Result<Vec<(i16, i64)>> {
+        let counts = sqlx::query!(
+            r#"SELECT progress, COUNT(*) AS "count!" FROM records WHERE demon = $1 AND status_ = 'APPROVED' AND progress >= $2
+               GROUP BY progress"#,
+            self.base.id,
+            self.requirement
+        )
+        .fetch_all(connection)
+        .await?;
+
+        Ok(progress_histogram(
+            self.requirement,
+            bucket_size,
+            counts.into_iter().map(|row| (row.progress, row.count)),
+        ))
+    }
 }
 
 macro_rules! query_many_demons {
@@ -119,7 +231,7 @@ macro_rules! query_many_demons {
 pub async fn published_by(player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Vec<MinimalDemon>> {
     query_many_demons!(
         connection,
-        r#"SELECT id, name, position FROM demons WHERE publisher = $1"#,
+        r#"SELECT id, name, position FROM demons WHERE publisher = $1 AND deleted_at IS NULL"#,
         player.id
     )
 }
@@ -127,7 +239,7 @@ pub async fn published_by(player: &DatabasePlayer, connection: &mut PgConnection
 pub async fn verified_by(player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Vec<MinimalDemon>> {
     query_many_demons!(
         connection,
-        r#"SELECT id, name, position FROM demons WHERE verifier = $1"#,
+        r#"SELECT id, name, position FROM demons WHERE verifier = $1 AND deleted_at IS NULL"#,
         player.id
     )
 }
@@ -151,6 +263,8 @@ struct FetchedDemon {
 
 impl From<FetchedDemon> for Demon {
     fn from(fetched: FetchedDemon) -> Self {
+        let thumbnail = resolve_thumbnail(fetched.thumbnail, fetched.video.as_deref());
+
         Demon {
             base: MinimalDemon {
                 id: fetched.demon_id,
@@ -159,7 +273,7 @@ impl From<FetchedDemon> for Demon {
             },
             requirement: fetched.requirement,
             video: fetched.video,
-            thumbnail: fetched.thumbnail,
+            thumbnail,
             publisher: DatabasePlayer {
                 id: fetched.publisher_id,
                 name: fetched.publisher_name,
@@ -185,6 +299,99 @@ pub async fn current_list(connection: &mut PgConnection) -> Result<Vec<Demon>> {
         .collect())
 }
 
+/// Like [`current_list`], but yields the demons one by one as they are read from the database
+pub fn stream_current_list(connection: &mut PgConnection) -> impl Stream<Item = Result<Demon>> + '_ {
+    sqlx::query_file_as!(FetchedDemon, "sql/all_demons.sql")
+        .fetch(connection)
+        .map(|row| row.map(Demon::from).map_err(DemonlistError::from))
+}
+
+/// The id and position of every demon on the list (including legacy), in list order
+pub async fn list_positions(connection: &mut PgConnection) -> Result<ListPositions> {
+    Ok(ListPositions(
+        sqlx::query_as!(
+            DemonPosition,
+            r#"SELECT id, position AS "position!" FROM demons WHERE position IS NOT NULL AND deleted_at IS NULL ORDER BY position"#
+        )
+        .fetch_all(connection)
+        .await?,
+    ))
+}
+
+/// The demons with at least [`config::min_enjoyment_ratings`] enjoyment ratings, ordered by their
+/// mean enjoyment (highest first). Demons without enough ratings are left out.
+pub async fn by_enjoyment(query: &DemonEnjoymentPagination, connection: &mut PgConnection) -> Result<Vec<RatedDemon>> {
+    query.validate()?;
+
+    let mut stream = sqlx::query_file!(
+        "sql/demons_by_enjoyment.sql",
+        query.after_enjoyment,
+        query.after_id,
+        config::min_enjoyment_ratings(),
+        query.limit as i64
+    )
+    .fetch(connection);
+
+    let mut demons = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        let fetched = FetchedDemon {
+            demon_id: row.demon_id,
+            demon_name: row.demon_name,
+            position: row.position,
+            requirement: row.requirement,
+            video: row.video,
+            thumbnail: row.thumbnail,
+            publisher_id: row.publisher_id,
+            publisher_name: row.publisher_name,
+            publisher_banned: row.publisher_banned,
+            verifier_id: row.verifier_id,
+            verifier_name: row.verifier_name,
+            verifier_banned: row.verifier_banned,
+            level_id: row.level_id,
+            difficulty: row.difficulty,
+        };
+
+        demons.push(RatedDemon {
+            demon: fetched.into(),
+            enjoyment: row.enjoyment,
+            ratings: row.ratings,
+        });
+    }
+
+    Ok(demons)
+}
+
+/// All demons whose verifier or publisher is currently banned, in list order
+pub async fn with_banned_players(connection: &mut PgConnection) -> Result<Vec<Demon>> {
+    Ok(sqlx::query_file_as!(FetchedDemon, "sql/demons_with_banned_players.sql")
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
+/// The maximal score a player can have, i.e. the sum of the scores for completing every demon that gives points
+///
+/// Computed the same way as stored scores, so demons still in their grace period do not count.
+pub async fn total_awardable_points(connection: &mut PgConnection) -> Result<f64> {
+    Ok(sqlx::query!(
+        r#"SELECT COALESCE(SUM(record_score(100, demons.position, 150, demons.requirement)
+                               * COALESCE(difficulty_multipliers.multiplier, 1.0)), 0.0) AS "total!"
+           FROM demons
+           LEFT OUTER JOIN difficulty_multipliers
+           ON difficulty_multipliers.difficulty = demons.difficulty
+           WHERE (demons.scores_from IS NULL OR demons.scores_from <= (NOW() AT TIME ZONE 'utc'))
+             AND demons.deleted_at IS NULL"#
+    )
+    .fetch_one(connection)
+    .await?
+    .total)
+}
+
 pub async fn list_at(connection: &mut PgConnection, at: NaiveDateTime) -> Result<Vec<TimeShiftedDemon>> {
     let mut stream = sqlx::query_file!("sql/all_demons_at.sql", at).fetch(connection);
     let mut demons = Vec::new();
@@ -200,8 +407,8 @@ pub async fn list_at(connection: &mut PgConnection, at: NaiveDateTime) -> Result
                     name: row.demon_name,
                 },
                 requirement: row.requirement,
+                thumbnail: resolve_thumbnail(row.thumbnail, row.video.as_deref()),
                 video: row.video,
-                thumbnail: row.thumbnail,
                 publisher: DatabasePlayer {
                     id: row.publisher_id,
                     name: row.publisher_name,
@@ -1,10 +1,16 @@
 pub use self::{
-    get::{current_list, list_at, published_by, verified_by},
-    paginate::{DemonIdPagination, DemonPositionPagination},
+    get::{
+        by_enjoyment, current_list, list_at, list_positions, published_by, stream_current_list, total_awardable_points, verified_by,
+        with_banned_players,
+    },
+    paginate::{DemonEnjoymentPagination, DemonIdPagination, DemonPositionPagination},
     patch::PatchDemon,
     post::PostDemon,
 };
+pub(crate) use self::post::OPTIONAL_DEMON_FIELDS;
+use self::audit::PeakPosition;
 use crate::{
+    config,
     error::{DemonlistError, Result},
     player::DatabasePlayer,
     record::MinimalRecordP,
@@ -16,23 +22,46 @@ use pointercrate_core::etag::Taggable;
 use serde::{Deserialize, Serialize, Deserializer, Serializer};
 use sqlx::PgConnection;
 use std::{
+    cmp::Ordering,
     collections::hash_map::DefaultHasher,
     hash::{Hash, Hasher},
+    str::FromStr,
 };
 
 #[macro_use]
 mod get;
 pub mod audit;
+mod delete;
 mod paginate;
 mod patch;
 mod post;
 
+/// Whether a demon currently accepts record submissions, see [`MinimalDemon::submission_state`]
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum SubmissionState {
+    Open,
+
+    /// Submissions are closed for the time being, without the demon being archived
+    Closed,
+
+    /// The demon is archived. Archived demons never accept submissions, no matter whether their
+    /// submissions were closed beforehand
+    Archived,
+}
+
+/// A demon as it was at some point in the past, see [`list_at`]
+#[derive(Debug, Serialize)]
 pub struct TimeShiftedDemon {
+    /// The demon, at the position it had back then
+    #[serde(flatten)]
     pub current_demon: Demon,
     pub position_now: i16,
 }
 
 /// The difficulty tiers a level can be in
+///
+/// Tiers are ordered by how hard they are, meaning `Beginner` is the smallest and `Silent` the
+/// largest tier.
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
 pub enum Difficulty {
     Silent,
@@ -46,7 +75,36 @@ pub enum Difficulty {
     Beginner
 }
 
+// The variants are declared from hardest to easiest (like the `level_difficulty` enum in the
+// database), so the derived ordering would be the wrong way round
+impl Ord for Difficulty {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (*other as u8).cmp(&(*self as u8))
+    }
+}
+
+impl PartialOrd for Difficulty {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Difficulty {
+    /// All difficulty tiers, from hardest to easiest
+    pub const fn all() -> [Difficulty; 9] {
+        [
+            Self::Silent,
+            Self::Legendary,
+            Self::Extreme,
+            Self::Mythical,
+            Self::Insane,
+            Self::Hard,
+            Self::Medium,
+            Self::Easy,
+            Self::Beginner,
+        ]
+    }
+
     pub fn to_sql(self) -> String {
         match self {
             Self::Silent => "silent",
@@ -62,20 +120,125 @@ impl Difficulty {
         .to_owned()
     }
 
-    fn from_sql(sql: &str) -> Self {
-        match sql {
-            "silent" => Self::Silent,
-            "legendary" => Self::Legendary,
-            "extreme" => Self::Extreme,
-            "mythical" => Self::Mythical,
-            "insane" => Self::Insane,
-            "hard" => Self::Hard,
-            "medium" => Self::Medium,
-            "easy" => Self::Easy,
-            "beginner" => Self::Beginner,
-            _ => panic!("invalid difficulty: {}", sql),
+    /// Only to be used on values coming from the database, which are guaranteed to be valid by the
+    /// `level_difficulty` enum. Use [`str::parse`] for anything else.
+    pub(crate) fn from_sql(sql: &str) -> Self {
+        sql.parse().unwrap_or_else(|_| panic!("invalid difficulty: {}", sql))
+    }
+
+    /// The difficulty a demon at the given position falls into according to the configured
+    /// position bands (see [`crate::config::difficulty_bands`]), if any
+    pub fn from_position_bands(position: i16) -> Option<Difficulty> {
+        parse_difficulty_bands(&crate::config::difficulty_bands()?)
+            .into_iter()
+            .find(|&(last_position, _)| position <= last_position)
+            .map(|(_, difficulty)| difficulty)
+    }
+
+    /// The factor by which scores for demons in this tier are multiplied according to the configured
+    /// [multipliers](crate::config::difficulty_multipliers), `1.0` if none is set
+    pub fn score_multiplier(self) -> f64 {
+        self.multiplier_in(crate::config::difficulty_multipliers())
+    }
+
+    /// Writes the configured [multipliers](crate::config::difficulty_multipliers) to the database,
+    /// where they are picked up by the `score_giving` view, and recomputes all scores if they changed
+    ///
+    /// Needs to run on startup, as the database would otherwise keep using whatever multipliers were
+    /// configured the last time this ran.
+    pub async fn store_multipliers(connection: &mut PgConnection) -> Result<()> {
+        let mut configured = crate::config::difficulty_multipliers().to_vec();
+        configured.retain(|&(_, multiplier)| multiplier != 1.0);
+        configured.sort_by_key(|&(difficulty, _)| difficulty);
+
+        let mut stored = sqlx::query!(r#"SELECT difficulty::TEXT AS "difficulty!", multiplier FROM difficulty_multipliers"#)
+            .fetch_all(&mut *connection)
+            .await?
+            .into_iter()
+            .map(|row| (Difficulty::from_sql(&row.difficulty), row.multiplier))
+            .collect::<Vec<_>>();
+        stored.sort_by_key(|&(difficulty, _)| difficulty);
+
+        if stored == configured {
+            return Ok(());
+        }
+
+        info!("Difficulty multipliers changed from {:?} to {:?}, recomputing scores", stored, configured);
+
+        sqlx::query!("DELETE FROM difficulty_multipliers").execute(&mut *connection).await?;
+
+        for (difficulty, multiplier) in configured {
+            sqlx::query!(
+                "INSERT INTO difficulty_multipliers (difficulty, multiplier) VALUES ($1::TEXT::level_difficulty, $2)",
+                difficulty.to_sql(),
+                multiplier
+            )
+            .execute(&mut *connection)
+            .await?;
+        }
+
+        crate::player::recompute_scores(connection).await?;
+
+        Ok(())
+    }
+
+    fn multiplier_in(self, multipliers: &[(Difficulty, f64)]) -> f64 {
+        multipliers
+            .iter()
+            .find(|&&(difficulty, _)| difficulty == self)
+            .map(|&(_, multiplier)| multiplier)
+            .unwrap_or(1.0)
+    }
+}
+
+fn parse_difficulty_bands(bands: &str) -> Vec<(i16, Difficulty)> {
+    bands
+        .split(',')
+        .map(|band| {
+            let (difficulty, last_position) = band
+                .trim()
+                .split_once(':')
+                .expect("difficulty bands to be of the form 'difficulty:last_position'");
+
+            (
+                last_position.trim().parse().expect("last position of difficulty band to be a number"),
+                difficulty.trim().parse().expect("difficulty bands to only contain valid difficulties"),
+            )
+        })
+        .collect()
+}
+
+pub(crate) fn parse_difficulty_multipliers(multipliers: &str) -> std::result::Result<Vec<(Difficulty, f64)>, String> {
+    let parsed = multipliers
+        .split(',')
+        .map(|entry| {
+            let (difficulty, multiplier) = entry
+                .trim()
+                .split_once(':')
+                .ok_or_else(|| format!("difficulty multiplier '{}' is not of the form 'difficulty:multiplier'", entry))?;
+
+            let difficulty = difficulty
+                .trim()
+                .parse()
+                .map_err(|_| format!("'{}' is not a difficulty", difficulty.trim()))?;
+            let multiplier = multiplier
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|multiplier| multiplier.is_finite() && *multiplier >= 0.0)
+                .ok_or_else(|| format!("difficulty multiplier '{}' is not a non-negative number", multiplier.trim()))?;
+
+            Ok((difficulty, multiplier))
+        })
+        .collect::<std::result::Result<Vec<(Difficulty, f64)>, String>>()?;
+
+    for (idx, &(difficulty, _)) in parsed.iter().enumerate() {
+        if parsed[..idx].iter().any(|&(other, _)| other == difficulty) {
+            return Err(format!("more than one multiplier given for difficulty '{}'", difficulty));
         }
     }
+
+    Ok(parsed)
 }
 
 impl DisplayFmt for Difficulty {
@@ -94,6 +257,18 @@ impl DisplayFmt for Difficulty {
     }
 }
 
+impl FromStr for Difficulty {
+    type Err = DemonlistError;
+
+    /// Parses the (case insensitive) name of a difficulty tier
+    fn from_str(s: &str) -> Result<Self> {
+        Difficulty::all()
+            .into_iter()
+            .find(|difficulty| difficulty.to_sql().eq_ignore_ascii_case(s))
+            .ok_or_else(|| DemonlistError::InvalidDifficulty { difficulty: s.to_string() })
+    }
+}
+
 impl Serialize for Difficulty {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
@@ -108,23 +283,14 @@ impl<'de> Deserialize<'de> for Difficulty {
     where
         D: Deserializer<'de>,
     {
-        let string = String::deserialize(deserializer)?.to_lowercase();
-
-        match &string[..] {
-            "silent" => Ok(Difficulty::Silent),
-            "legendary" => Ok(Difficulty::Legendary),
-            "extreme" => Ok(Difficulty::Extreme),
-            "mythical" => Ok(Difficulty::Mythical),
-            "insane" => Ok(Difficulty::Insane),
-            "hard" => Ok(Difficulty::Hard),
-            "medium" => Ok(Difficulty::Medium),
-            "easy" => Ok(Difficulty::Easy),
-            "beginner" => Ok(Difficulty::Beginner),
-            _ => Err(serde::de::Error::invalid_value(
+        let string = String::deserialize(deserializer)?;
+
+        string.parse().map_err(|_| {
+            serde::de::Error::invalid_value(
                 serde::de::Unexpected::Str(&string),
                 &"'silent', 'legendary', 'extreme', 'mythical', 'insane', 'hard', 'medium', 'easy' or 'beginner'",
-            )),
-        }
+            )
+        })
     }
 }
 
@@ -177,6 +343,88 @@ pub struct MinimalDemon {
     pub name: String,
 }
 
+/// The demons directly above and below some demon on the list
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DemonNeighbors {
+    /// The demon one position higher up on the list. `None` for the top demon
+    pub previous: Option<MinimalDemon>,
+
+    /// The demon one position further down the list. `None` for the last demon
+    pub next: Option<MinimalDemon>,
+}
+
+/// Where on the list a demon currently is, without any further information about it
+#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Copy)]
+pub struct DemonPosition {
+    pub id: i32,
+    pub position: i16,
+}
+
+/// The current ordering of the list, as a (cacheable) compact alternative to fetching all demons
+#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct ListPositions(pub Vec<DemonPosition>);
+
+impl Taggable for ListPositions {}
+
+/// A demon together with the mean enjoyment rating of its approved records
+#[derive(Debug, Serialize, PartialEq)]
+pub struct RatedDemon {
+    #[serde(flatten)]
+    pub demon: Demon,
+
+    pub enjoyment: f64,
+
+    /// The number of approved records with an enjoyment rating
+    pub ratings: i64,
+}
+
+/// Summary of the enjoyment ratings given to a demon
+#[derive(Debug, Serialize, PartialEq)]
+pub struct EnjoymentStats {
+    /// The mean rating, or `None` if there are no ratings
+    pub mean: Option<f64>,
+
+    /// The number of approved records with an enjoyment rating
+    pub ratings: i64,
+
+    /// `histogram[n]` is the number of ratings of `n`
+    pub histogram: [i64; 11],
+}
+
+impl EnjoymentStats {
+    fn from_histogram(histogram: [i64; 11]) -> Self {
+        let ratings = histogram.iter().sum();
+        let total: i64 = histogram.iter().zip(0..).map(|(&count, rating)| count * rating).sum();
+
+        EnjoymentStats {
+            mean: (ratings > 0).then(|| total as f64 / ratings as f64),
+            ratings,
+            histogram,
+        }
+    }
+}
+
+/// Sorts the given `(progress, count)` pairs into buckets of `bucket_size` percent each, see
+/// [`Demon::progress_distribution`]
+fn progress_histogram(requirement: i16, bucket_size: i16, counts: impl IntoIterator<Item = (i16, i64)>) -> Vec<(i16, i64)> {
+    let bucket_size = bucket_size.max(1);
+
+    let mut buckets: Vec<(i16, i64)> = (requirement..100).step_by(bucket_size as usize).map(|start| (start, 0)).collect();
+    buckets.push((100, 0));
+
+    for (progress, count) in counts {
+        let bucket = match progress {
+            100.. => buckets.len() - 1,
+            _ => ((progress - requirement).max(0) / bucket_size) as usize,
+        };
+
+        buckets[bucket].1 += count;
+    }
+
+    buckets
+}
+
 /// Struct modelling the "full" version of a demon.
 ///
 /// In addition to containing publisher/verifier information it also contains a list of the demon's
@@ -187,9 +435,24 @@ pub struct FullDemon {
     #[serde(flatten)]
     pub demon: Demon,
     pub creators: Vec<DatabasePlayer>,
+
+    /// The approved records on this demon, ordered by progress (highest first) and then by time of
+    /// approval (earliest first)
     pub records: Vec<MinimalRecordP>,
+
+    /// Approved records that stopped meeting the requirement because it was raised. Only filled in
+    /// by the patch raising it, see [`FullDemon::apply_patch`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub below_requirement: Vec<MinimalRecordP>,
+
+    /// The highest position this demon ever reached. Only computed when fetching a single demon, see
+    /// [`FullDemon::with_peak_position`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_position: Option<PeakPosition>,
 }
 
+/// Only the [`Demon`] itself can be modified via `PATCH`, so only it goes into the patch part. The get
+/// part covers the creators and records as well, so adding either invalidates cached responses.
 impl Taggable for FullDemon {
     fn patch_part(&self) -> u64 {
         let mut hasher = DefaultHasher::new();
@@ -199,6 +462,22 @@ impl Taggable for FullDemon {
 }
 
 impl MinimalDemon {
+    /// Whether this demon is on the main list, i.e. within the top [`config::list_size`] positions
+    pub fn is_main_list(&self) -> bool {
+        self.position <= config::list_size()
+    }
+
+    /// Whether this demon is on the extended list, i.e. below the main list but within the top
+    /// [`config::extended_list_size`] positions
+    pub fn is_extended(&self) -> bool {
+        !self.is_main_list() && !self.is_legacy()
+    }
+
+    /// Whether this demon is on the legacy list, i.e. below the extended list
+    pub fn is_legacy(&self) -> bool {
+        self.position > config::extended_list_size()
+    }
+
     /// Queries the record requirement for this demon from the database without collecting any of
     /// the other data
     pub async fn requirement(&self, connection: &mut PgConnection) -> Result<i16> {
@@ -207,6 +486,28 @@ impl MinimalDemon {
             .await?
             .requirement)
     }
+
+    /// Whether this demon is archived or has submissions closed
+    pub async fn submission_state(&self, connection: &mut PgConnection) -> Result<SubmissionState> {
+        let row = sqlx::query!("SELECT archived, submissions_closed FROM demons WHERE id = $1", self.id)
+            .fetch_one(connection)
+            .await?;
+
+        Ok(match (row.archived, row.submissions_closed) {
+            (true, _) => SubmissionState::Archived,
+            (false, true) => SubmissionState::Closed,
+            (false, false) => SubmissionState::Open,
+        })
+    }
+
+    pub async fn difficulty(&self, connection: &mut PgConnection) -> Result<Difficulty> {
+        Ok(Difficulty::from_sql(
+            &sqlx::query!(r#"SELECT difficulty::text AS "difficulty!" FROM demons WHERE id = $1"#, self.id)
+                .fetch_one(connection)
+                .await?
+                .difficulty,
+        ))
+    }
 }
 
 impl FullDemon {
@@ -220,12 +521,10 @@ impl FullDemon {
 }
 
 impl Demon {
+    /// Validates that the given requirement is at most 100 and at least the global minimum
+    /// requirement (see [`crate::config::minimum_requirement`])
     pub fn validate_requirement(requirement: i16) -> Result<()> {
-        if !(0..=100).contains(&requirement) {
-            return Err(DemonlistError::InvalidRequirement);
-        }
-
-        Ok(())
+        validate_requirement_against(requirement, crate::config::minimum_requirement())
     }
 
     pub fn validate_level_id(level_id: i64) -> Result<u64> {
@@ -238,8 +537,8 @@ impl Demon {
 
     pub async fn validate_position(position: i16, connection: &mut PgConnection) -> Result<()> {
         // To prevent holes from being created in the list, the new position must lie between 1 and (current
-        // last position + 1), inclusive
-        let maximal_position = Demon::max_position(connection).await? + 1;
+        // last position + 1), inclusive. Archived demons are kept below all others, so they do not count
+        let maximal_position = Demon::max_active_position(connection).await? + 1;
 
         if position > maximal_position || position < 1 {
             return Err(DemonlistError::InvalidPosition { maximal: maximal_position });
@@ -248,12 +547,65 @@ impl Demon {
         Ok(())
     }
 
+    /// Archives the lowest non-archived demons until at most `max_length` of them are left, returning
+    /// the ones that were archived
+    ///
+    /// As archived demons are kept below all others, the archived demons already are where they belong,
+    /// and no positions need to change.
+    pub(crate) async fn archive_overflow(max_length: i16, connection: &mut PgConnection) -> Result<Vec<MinimalDemon>> {
+        Ok(sqlx::query_as!(
+            MinimalDemon,
+            "UPDATE demons SET archived = TRUE WHERE id IN (SELECT id FROM demons WHERE NOT archived AND deleted_at IS NULL ORDER BY \
+             position OFFSET $1::SMALLINT) RETURNING id, name, position",
+            max_length
+        )
+        .fetch_all(connection)
+        .await?)
+    }
+
     /// Increments the position of all demons with positions equal to or greater than the given one,
     /// by one.
+    ///
+    /// This is a single statement, and the `unique_position` constraint is only checked at its end,
+    /// so demons never transiently share a position.
     async fn shift_down(starting_at: i16, connection: &mut PgConnection) -> Result<()> {
         info!("Shifting down all demons, starting at {}", starting_at);
 
-        sqlx::query!("UPDATE demons SET position = position + 1 WHERE position >= $1", starting_at)
+        sqlx::query!(
+            "UPDATE demons SET position = position + 1 WHERE position >= $1 AND deleted_at IS NULL",
+            starting_at
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Takes a transaction-level advisory lock guarding demon positions
+    ///
+    /// Anything that reads the current positions (e.g. via [`Demon::max_position`]) and then shifts
+    /// demons around based on them must hold this lock, otherwise two concurrent requests might both
+    /// decide on the same slot. The lock is released once the surrounding transaction ends, so this
+    /// is pointless outside of one.
+    pub(crate) async fn lock_positions(connection: &mut PgConnection) -> Result<()> {
+        // Not using the macro here, as it cannot deal with the function returning `void`
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(POSITION_LOCK_KEY)
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Takes a transaction-level advisory lock on the demon with the given id
+    ///
+    /// Held from checking a request's `If-Match` header until the patch is committed. Without it,
+    /// two moderators editing with the same ETag could both pass the check before either change is
+    /// visible, and the second would silently overwrite the first.
+    pub async fn lock(id: i32, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query("SELECT pg_advisory_xact_lock($1, $2)")
+            .bind(DEMON_LOCK_CLASS)
+            .bind(id)
             .execute(connection)
             .await?;
 
@@ -263,20 +615,150 @@ impl Demon {
     /// Gets the current max position a demon has, or `0` if there are no demons
     /// in the database
     pub async fn max_position(connection: &mut PgConnection) -> Result<i16> {
-        Ok(sqlx::query!("SELECT MAX(position) as max_position FROM demons")
-            .fetch_one(connection)
-            .await?
-            .max_position
-            .unwrap_or(0))
+        Ok(
+            sqlx::query!("SELECT MAX(position) as max_position FROM demons WHERE deleted_at IS NULL")
+                .fetch_one(connection)
+                .await?
+                .max_position
+                .unwrap_or(0),
+        )
+    }
+
+    /// Gets the current max position a non-archived demon has, or `0` if there are none
+    ///
+    /// Archived demons are kept below all others, so this is also the number of demons on the list
+    /// proper.
+    pub async fn max_active_position(connection: &mut PgConnection) -> Result<i16> {
+        Ok(
+            sqlx::query!("SELECT MAX(position) as max_position FROM demons WHERE NOT archived AND deleted_at IS NULL")
+                .fetch_one(connection)
+                .await?
+                .max_position
+                .unwrap_or(0),
+        )
+    }
+
+    /// The number of points a record with the given progress on this demon is worth under the given
+    /// [`ScoreFormula`], scaled by the [multiplier](Difficulty::score_multiplier) of the demon's
+    /// difficulty
+    pub fn score(&self, formula: &dyn ScoreFormula, progress: i16) -> f64 {
+        self.score_scaled_by(formula, progress, self.difficulty.score_multiplier())
+    }
+
+    fn score_scaled_by(&self, formula: &dyn ScoreFormula, progress: i16, multiplier: f64) -> f64 {
+        formula.score(self.base.position, progress, self.requirement) * multiplier
     }
+}
+
+/// The outcome of trying to find the Geometry Dash level belonging to a demon
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LevelIdResolution {
+    Found(u64),
+    NotFound,
+
+    /// Multiple levels match the name, and there is no telling which one is meant. Holds the ids of
+    /// all matching levels.
+    Ambiguous(Vec<u64>),
+}
+
+/// A function assigning point values to records
+///
+/// The API picks up an implementation placed into Rocket's managed state (as a
+/// `Box<dyn ScoreFormula>`), falling back to [`DefaultScoreFormula`] otherwise. On startup, the
+/// database's `record_score` function is regenerated from the formula's [description](ScoreFormula::describe)
+/// (see [`store_score_formula`]), so that the scores stored for players and nations follow it as well.
+///
+/// For progress at or above the requirement, [`ScoreFormula::score`] must be non-decreasing in
+/// progress, and progress below the requirement must be worth nothing.
+pub trait ScoreFormula: Send + Sync {
+    /// The number of points a record with the given progress on a demon at the given position and
+    /// with the given requirement is worth
+    fn score(&self, position: i16, progress: i16, requirement: i16) -> f64;
+
+    /// A human (and calculator) readable description of this formula, for third party tools that
+    /// want to replicate it. The database computes stored scores from this description, so it has to
+    /// agree with [`ScoreFormula::score`].
+    fn describe(&self) -> ScoreFormulaDescription;
+}
+
+/// Replaces the database's `record_score` function with one implementing the given formula, and
+/// recomputes all scores if it changed
+pub async fn store_score_formula(formula: &dyn ScoreFormula, connection: &mut PgConnection) -> Result<()> {
+    let body = formula.describe().to_sql();
+    let stored = sqlx::query!(r#"SELECT prosrc AS "prosrc!" FROM pg_proc WHERE proname = 'record_score'"#)
+        .fetch_optional(&mut *connection)
+        .await?;
+
+    if stored.map(|row| row.prosrc).as_deref() == Some(body.as_str()) {
+        return Ok(());
+    }
+
+    info!("Score formula changed, regenerating record_score and recomputing scores");
+
+    sqlx::query(&format!(
+        "CREATE OR REPLACE FUNCTION record_score(progress FLOAT, demon FLOAT, list_size FLOAT, requirement FLOAT) RETURNS FLOAT AS \
+         $record_score${}$record_score$ LANGUAGE SQL IMMUTABLE",
+        body
+    ))
+    .execute(&mut *connection)
+    .await?;
+
+    crate::player::recompute_scores(connection).await?;
+
+    Ok(())
+}
+
+/// Describes a [`ScoreFormula`] that first determines a score for completing a demon based on its
+/// position, and then scales it down for progress records
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ScoreFormulaDescription {
+    /// The score for completing a demon at the positions covered by each band. Positions not
+    /// covered by any band give no points
+    pub bands: Vec<ScoreBand>,
+
+    /// How the completion score is scaled for records with less than 100% progress (but at least the
+    /// requirement), in terms of `score`, `progress` and `requirement`
+    pub progress: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ScoreBand {
+    pub first_position: i16,
+    pub last_position: i16,
+
+    /// The completion score, in terms of `position`
+    pub score: String,
+}
+
+impl ScoreFormulaDescription {
+    /// The body of an SQL function computing the described score from the parameters `progress`,
+    /// `demon` (the position) and `requirement`
+    pub(crate) fn to_sql(&self) -> String {
+        let bands = self
+            .bands
+            .iter()
+            .map(|band| format!("WHEN position BETWEEN {} AND {} THEN ({})", band.first_position, band.last_position, band.score))
+            .collect::<Vec<_>>()
+            .join(" ");
 
-    pub fn score(&self, progress: i16) -> f64 {
-        if progress < self.requirement {
+        format!(
+            "SELECT (CASE WHEN progress < requirement THEN 0.0 WHEN progress = 100 THEN score ELSE ({}) END)::FLOAT FROM (SELECT CASE {} \
+             ELSE 0.0 END AS score FROM (SELECT demon AS position) AS demon) AS completion",
+            self.progress, bands
+        )
+    }
+}
+
+/// The piecewise scoring function the list has always been using
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultScoreFormula;
+
+impl ScoreFormula for DefaultScoreFormula {
+    fn score(&self, position: i16, progress: i16, requirement: i16) -> f64 {
+        if progress < requirement {
             return 0.0;
         }
 
-        let position = self.base.position;
-
         let beaten_score = match position {
             56..=150 => 1.039035131_f64 * ((185.7_f64 * (-0.02715_f64 * position as f64).exp()) + 14.84_f64),
             36..=55 => 1.0371139743_f64 * ((212.61_f64 * 1.036_f64.powf(1_f64 - position as f64)) + 25.071_f64),
@@ -287,9 +769,364 @@ impl Demon {
         };
 
         if progress != 100 {
-            (beaten_score * (5f64.powf((progress - self.requirement) as f64 / (100f64 - self.requirement as f64)))) / 10f64
+            (beaten_score * (5f64.powf((progress - requirement) as f64 / (100f64 - requirement as f64)))) / 10f64
         } else {
             beaten_score
         }
     }
+
+    fn describe(&self) -> ScoreFormulaDescription {
+        let band = |first_position, last_position, score: &str| ScoreBand {
+            first_position,
+            last_position,
+            score: score.to_string(),
+        };
+
+        ScoreFormulaDescription {
+            bands: vec![
+                band(1, 3, "-18.2899079915 * position + 368.2899079915"),
+                band(4, 20, "(326.1 * exp(-0.0871 * position) + 51.09) * 1.037117142"),
+                band(21, 35, "((250 - 83.389) * 1.0099685^(2 - position) - 31.152) * 1.0371139743"),
+                band(36, 55, "1.0371139743 * (212.61 * 1.036^(1 - position) + 25.071)"),
+                band(56, 150, "1.039035131 * (185.7 * exp(-0.02715 * position) + 14.84)"),
+            ],
+            progress: "score * 5^((progress - requirement) / (100 - requirement)) / 10".to_string(),
+        }
+    }
+}
+
+/// Key of the advisory lock taken by [`Demon::lock_positions`]. Arbitrary, it just must not be used
+/// for any other lock.
+const POSITION_LOCK_KEY: i64 = 0x6465_6d6f_6e73;
+
+/// First key of the advisory locks taken by [`Demon::lock`], the second being the demon's id. Two-key
+/// locks live in a different key space than [`POSITION_LOCK_KEY`], so they cannot collide.
+const DEMON_LOCK_CLASS: i32 = 0x6465_6d6f;
+
+/// The shortest query [`MinimalDemon::search`] accepts. Shorter ones would match almost everything
+pub const MIN_SEARCH_LENGTH: usize = 3;
+
+/// The most results [`MinimalDemon::search`] returns, regardless of the requested limit
+pub const MAX_SEARCH_RESULTS: i64 = 25;
+
+/// The thumbnail the database assigns to demons whose thumbnail could not be derived from their
+/// video (or that have no video)
+const DATABASE_DEFAULT_THUMBNAIL: &str = "https://i.ytimg.com/vi/zebrafishes/mqdefault.jpg";
+
+/// Determines the thumbnail to show for a demon
+///
+/// Uses the stored thumbnail, unless it is empty or the database's default. In that case, the thumbnail
+/// is derived from the demon's video, and if that is not possible, the configured placeholder (see
+/// [`crate::config::placeholder_thumbnail`]) is used.
+pub(crate) fn resolve_thumbnail(stored: String, video: Option<&str>) -> String {
+    if !stored.is_empty() && stored != DATABASE_DEFAULT_THUMBNAIL {
+        return stored;
+    }
+
+    video
+        .and_then(crate::video::thumbnail)
+        .unwrap_or_else(crate::config::placeholder_thumbnail)
+}
+
+fn validate_requirement_against(requirement: i16, minimum: i16) -> Result<()> {
+    if !(0..=100).contains(&requirement) {
+        return Err(DemonlistError::InvalidRequirement);
+    }
+
+    if requirement < minimum {
+        return Err(DemonlistError::RequirementBelowMinimum { minimum });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config,
+        demon::{
+            parse_difficulty_bands, parse_difficulty_multipliers, progress_histogram, resolve_thumbnail, store_score_formula,
+            validate_requirement_against, DefaultScoreFormula, Demon, Difficulty, EnjoymentStats, MinimalDemon, ScoreBand, ScoreFormula,
+            ScoreFormulaDescription, DATABASE_DEFAULT_THUMBNAIL,
+        },
+        error::DemonlistError,
+        player::DatabasePlayer,
+    };
+    use sqlx::{pool::PoolConnection, Postgres};
+
+    fn demon_at(position: i16, requirement: i16) -> Demon {
+        let player = DatabasePlayer {
+            id: 1,
+            name: "stardust1971".to_string(),
+            banned: false,
+        };
+
+        Demon {
+            base: MinimalDemon {
+                id: 1,
+                position,
+                name: "Bloodbath".to_string(),
+            },
+            requirement,
+            video: None,
+            thumbnail: DATABASE_DEFAULT_THUMBNAIL.to_string(),
+            publisher: player.clone(),
+            verifier: player,
+            level_id: None,
+            difficulty: Difficulty::Extreme,
+        }
+    }
+
+    fn minimal_demon_at(position: i16) -> MinimalDemon {
+        demon_at(position, 50).base
+    }
+
+    #[test]
+    fn test_list_boundaries() {
+        let list_size = config::list_size();
+        let extended_list_size = config::extended_list_size();
+
+        let last_main = minimal_demon_at(list_size);
+        assert!(last_main.is_main_list() && !last_main.is_extended() && !last_main.is_legacy());
+
+        let first_extended = minimal_demon_at(list_size + 1);
+        assert!(!first_extended.is_main_list() && first_extended.is_extended() && !first_extended.is_legacy());
+
+        let last_extended = minimal_demon_at(extended_list_size);
+        assert!(!last_extended.is_main_list() && last_extended.is_extended() && !last_extended.is_legacy());
+
+        let first_legacy = minimal_demon_at(extended_list_size + 1);
+        assert!(!first_legacy.is_main_list() && !first_legacy.is_extended() && first_legacy.is_legacy());
+    }
+
+    // Positions at the boundaries of the different scoring bands
+    const BAND_BOUNDARIES: [i16; 12] = [1, 3, 4, 20, 21, 35, 36, 55, 56, 100, 150, 151];
+
+    #[test]
+    fn test_no_score_below_requirement() {
+        for position in BAND_BOUNDARIES {
+            for requirement in [1, 30, 57, 99, 100] {
+                let demon = demon_at(position, requirement);
+
+                assert_eq!(demon.score(&DefaultScoreFormula, requirement - 1), 0.0, "position {}, requirement {}", position, requirement);
+                assert_eq!(demon.score(&DefaultScoreFormula, 0), 0.0, "position {}, requirement {}", position, requirement);
+            }
+        }
+    }
+
+    #[test]
+    fn test_score_monotonic_in_progress() {
+        for position in BAND_BOUNDARIES {
+            for requirement in [0, 30, 57, 99, 100] {
+                let demon = demon_at(position, requirement);
+
+                for progress in 0..100 {
+                    assert!(
+                        demon.score(&DefaultScoreFormula, progress) <= demon.score(&DefaultScoreFormula, progress + 1),
+                        "score decreases from {}% to {}% at position {} with requirement {}",
+                        progress,
+                        progress + 1,
+                        position,
+                        requirement
+                    );
+                }
+            }
+        }
+    }
+
+    struct FlatFormula;
+
+    impl ScoreFormula for FlatFormula {
+        fn score(&self, _position: i16, progress: i16, requirement: i16) -> f64 {
+            if progress < requirement { 0.0 } else { 1.0 }
+        }
+
+        fn describe(&self) -> ScoreFormulaDescription {
+            ScoreFormulaDescription {
+                bands: vec![ScoreBand {
+                    first_position: 1,
+                    last_position: i16::MAX,
+                    score: "1".to_string(),
+                }],
+                progress: "score".to_string(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_score_formula() {
+        for position in BAND_BOUNDARIES {
+            let demon = demon_at(position, 50);
+
+            for progress in 0..=100 {
+                assert_eq!(demon.score(&DefaultScoreFormula, progress).to_bits(), DefaultScoreFormula.score(position, progress, 50).to_bits());
+                assert_eq!(demon.score(&FlatFormula, progress), if progress < 50 { 0.0 } else { 1.0 });
+            }
+        }
+
+        assert_eq!(demon_at(1, 100).score(&DefaultScoreFormula, 100), 350.0);
+    }
+
+    #[test]
+    fn test_enjoyment_stats() {
+        let empty = EnjoymentStats::from_histogram([0; 11]);
+
+        assert_eq!(empty.mean, None);
+        assert_eq!(empty.ratings, 0);
+
+        let stats = EnjoymentStats::from_histogram([0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 1]);
+
+        assert_eq!(stats.mean, Some(8.0));
+        assert_eq!(stats.ratings, 3);
+    }
+
+    #[test]
+    fn test_progress_histogram() {
+        assert_eq!(
+            progress_histogram(50, 10, [(50, 1), (59, 2), (60, 1), (99, 4), (100, 3)]),
+            vec![(50, 3), (60, 1), (70, 0), (80, 0), (90, 4), (100, 3)]
+        );
+
+        // The last bucket is cut short if the range does not divide evenly
+        assert_eq!(progress_histogram(55, 20, [(94, 1), (95, 1)]), vec![(55, 0), (75, 1), (95, 1), (100, 0)]);
+
+        // Only completions are accepted, so there is only the completion bucket
+        assert_eq!(progress_histogram(100, 10, [(100, 5)]), vec![(100, 5)]);
+        assert_eq!(progress_histogram(100, 10, []), vec![(100, 0)]);
+    }
+
+    #[test]
+    fn test_thumbnail_fallback() {
+        // Explicitly set thumbnails are kept
+        assert_eq!(
+            resolve_thumbnail("https://example.com/thumb.png".to_string(), Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ")),
+            "https://example.com/thumb.png"
+        );
+
+        // Missing thumbnails are derived from the video, if possible
+        assert_eq!(
+            resolve_thumbnail(String::new(), Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ")),
+            "https://i.ytimg.com/vi/dQw4w9WgXcQ/mqdefault.jpg"
+        );
+
+        // ... and fall back to the placeholder otherwise
+        assert_eq!(
+            resolve_thumbnail(DATABASE_DEFAULT_THUMBNAIL.to_string(), Some("https://www.twitch.tv/videos/123456")),
+            crate::config::placeholder_thumbnail()
+        );
+        assert_eq!(resolve_thumbnail(String::new(), None), crate::config::placeholder_thumbnail());
+    }
+
+    #[test]
+    fn test_requirement_below_minimum() {
+        assert_eq!(validate_requirement_against(50, 0), Ok(()));
+        assert_eq!(validate_requirement_against(50, 50), Ok(()));
+        assert_eq!(
+            validate_requirement_against(49, 50),
+            Err(DemonlistError::RequirementBelowMinimum { minimum: 50 })
+        );
+        assert_eq!(validate_requirement_against(101, 50), Err(DemonlistError::InvalidRequirement));
+    }
+
+    #[test]
+    fn test_difficulty_from_str() {
+        for difficulty in Difficulty::all() {
+            assert_eq!(difficulty.to_sql().parse(), Ok(difficulty));
+            assert_eq!(difficulty.to_string().to_uppercase().parse(), Ok(difficulty));
+        }
+
+        assert_eq!(
+            "impossible".parse::<Difficulty>(),
+            Err(DemonlistError::InvalidDifficulty {
+                difficulty: "impossible".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_difficulty_ordering() {
+        assert!(Difficulty::Beginner < Difficulty::Silent);
+
+        // all() goes from hardest to easiest
+        for pair in Difficulty::all().windows(2) {
+            assert!(pair[0] > pair[1], "{:?} is not harder than {:?}", pair[0], pair[1]);
+        }
+
+        assert_eq!(Difficulty::all().into_iter().max(), Some(Difficulty::Silent));
+        assert_eq!(Difficulty::all().into_iter().min(), Some(Difficulty::Beginner));
+    }
+
+    #[test]
+    fn test_parse_difficulty_bands() {
+        assert_eq!(
+            parse_difficulty_bands("silent:5, Legendary:15,extreme : 40"),
+            vec![(5, Difficulty::Silent), (15, Difficulty::Legendary), (40, Difficulty::Extreme)]
+        );
+    }
+
+    #[test]
+    fn test_difficulty_multipliers() {
+        let multipliers = parse_difficulty_multipliers("extreme:2.0, Legendary : 1.5").unwrap();
+
+        assert_eq!(multipliers, vec![(Difficulty::Extreme, 2.0), (Difficulty::Legendary, 1.5)]);
+
+        assert!(parse_difficulty_multipliers("extreme").is_err());
+        assert!(parse_difficulty_multipliers("very hard:2.0").is_err());
+        assert!(parse_difficulty_multipliers("extreme:-1").is_err());
+        assert!(parse_difficulty_multipliers("extreme:lots").is_err());
+
+        let mut extreme = demon_at(10, 50);
+        extreme.difficulty = Difficulty::Extreme;
+
+        for progress in [50, 75, 100] {
+            let unscaled = DefaultScoreFormula.score(10, progress, 50);
+
+            assert_eq!(
+                extreme.score_scaled_by(&DefaultScoreFormula, progress, Difficulty::Extreme.multiplier_in(&multipliers)),
+                2.0 * unscaled
+            );
+        }
+
+        // Tiers without a multiplier are unaffected
+        for difficulty in [Difficulty::Silent, Difficulty::Mythical, Difficulty::Beginner] {
+            let mut demon = demon_at(10, 50);
+            demon.difficulty = difficulty;
+
+            assert_eq!(difficulty.multiplier_in(&multipliers), 1.0);
+            assert_eq!(
+                demon.score_scaled_by(&DefaultScoreFormula, 100, difficulty.multiplier_in(&multipliers)),
+                DefaultScoreFormula.score(10, 100, 50)
+            );
+        }
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_stored_score_formula(mut conn: PoolConnection<Postgres>) {
+        for formula in [&DefaultScoreFormula as &dyn ScoreFormula, &FlatFormula] {
+            store_score_formula(formula, &mut conn).await.unwrap();
+
+            for position in BAND_BOUNDARIES.into_iter().chain([200]) {
+                for (progress, requirement) in [(0, 50), (49, 50), (50, 50), (73, 50), (99, 50), (100, 50), (100, 100)] {
+                    let stored = sqlx::query!(
+                        r#"SELECT record_score($1::FLOAT, $2::FLOAT, 150, $3::FLOAT) AS "score!""#,
+                        progress as f64,
+                        position as f64,
+                        requirement as f64
+                    )
+                    .fetch_one(&mut *conn)
+                    .await
+                    .unwrap()
+                    .score;
+
+                    assert!(
+                        (stored - formula.score(position, progress, requirement)).abs() < 1e-9,
+                        "position {}, progress {}, requirement {}",
+                        position,
+                        progress,
+                        requirement
+                    );
+                }
+            }
+        }
+    }
 }
@@ -1,11 +1,15 @@
 use crate::{
-    demon::{Demon, Difficulty, MinimalDemon},
+    config,
+    demon::{resolve_thumbnail, Demon, Difficulty, MinimalDemon},
     player::DatabasePlayer,
 };
 use futures::stream::StreamExt;
 use pointercrate_core::{
+    error::CoreError,
     first_and_last,
-    pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pagination::{
+        PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat, DEFAULT_ENTRIES_PER_PAGE, ENTRIES_PER_PAGE,
+    },
     util::non_nullable,
 };
 use serde::{Deserialize, Serialize};
@@ -83,6 +87,7 @@ impl Paginatable<DemonIdPagination> for Demon {
             .bind(query.name_contains.as_deref())
             .bind(query.level_id)
             .bind(query.params.limit + 1)
+            .bind(config::strip_emoji_in_search())
             .fetch(connection);
 
         let mut demons = Vec::new();
@@ -91,6 +96,7 @@ impl Paginatable<DemonIdPagination> for Demon {
             let row = row?;
 
             let video: Option<String> = row.get("video");
+            let thumbnail = resolve_thumbnail(row.get("thumbnail"), video.as_deref());
 
             demons.push(Demon {
                 base: MinimalDemon {
@@ -100,7 +106,7 @@ impl Paginatable<DemonIdPagination> for Demon {
                 },
                 requirement: row.get("requirement"),
                 video,
-                thumbnail: row.get("thumbnail"),
+                thumbnail,
                 publisher: DatabasePlayer {
                     id: row.get("publisher_id"),
                     name: row.get("publisher_name"),
@@ -124,11 +130,25 @@ impl Paginatable<DemonIdPagination> for Demon {
     }
 }
 
+/// Pagination of the demons on the list, ordered by position
+///
+/// Positions shift whenever demons are added or moved, so the `before`/`after` cursor is a
+/// composite of position and id: `before_id`/`after_id` are the ids of the demons that were at
+/// positions `before`/`after` when the cursor was created. If such a demon is still on the list, its
+/// current position is used instead, so that clients paging through the list despite concurrent
+/// changes neither see a demon twice nor miss one. The `next`/`prev` links always carry both parts,
+/// anchored on the last/first demon of the current page.
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct DemonPositionPagination {
     #[serde(flatten)]
     pub params: PaginationParameters,
 
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub before_id: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub after_id: Option<i32>,
+
     #[serde(default, deserialize_with = "non_nullable")]
     pub name: Option<String>,
 
@@ -158,6 +178,44 @@ pub struct DemonPositionPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "requirement__lt")]
     pub requirement_lt: Option<i16>,
+
+    /// Only return demons verified by players of the nation with the given ISO country code
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub verifier_nationality: Option<String>,
+
+    /// Only return demons of exactly the given tier
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub difficulty: Option<Difficulty>,
+
+    /// Only return demons at least as hard as the given tier
+    #[serde(default, deserialize_with = "non_nullable")]
+    #[serde(rename = "difficulty__gte")]
+    pub difficulty_gte: Option<Difficulty>,
+
+    /// Only return demons at most as hard as the given tier
+    #[serde(default, deserialize_with = "non_nullable")]
+    #[serde(rename = "difficulty__lte")]
+    pub difficulty_lte: Option<Difficulty>,
+}
+
+impl DemonPositionPagination {
+    /// The difficulty tiers (as stored in the database) a demon has to be in to match this query,
+    /// or `None` if no difficulty filter was given
+    fn difficulties(&self) -> Option<Vec<String>> {
+        if self.difficulty.is_none() && self.difficulty_gte.is_none() && self.difficulty_lte.is_none() {
+            return None;
+        }
+
+        Some(
+            Difficulty::all()
+                .into_iter()
+                .filter(|&difficulty| self.difficulty.map_or(true, |wanted| difficulty == wanted))
+                .filter(|&difficulty| self.difficulty_gte.map_or(true, |lower| difficulty >= lower))
+                .filter(|&difficulty| self.difficulty_lte.map_or(true, |upper| difficulty <= upper))
+                .map(Difficulty::to_sql)
+                .collect(),
+        )
+    }
 }
 
 impl PaginationQuery for DemonPositionPagination {
@@ -168,9 +226,19 @@ impl PaginationQuery for DemonPositionPagination {
     fn with_parameters(&self, parameters: PaginationParameters) -> Self {
         Self {
             params: parameters,
+            before_id: None,
+            after_id: None,
             ..self.clone()
         }
     }
+
+    fn with_anchors(self, before: Option<i32>, after: Option<i32>) -> Self {
+        Self {
+            before_id: before,
+            after_id: after,
+            ..self
+        }
+    }
 }
 
 impl Paginatable<DemonPositionPagination> for Demon {
@@ -195,7 +263,12 @@ impl Paginatable<DemonPositionPagination> for Demon {
             .bind(query.publisher_name.as_deref())
             .bind(query.name_contains.as_deref())
             .bind(query.level_id)
+            .bind(query.verifier_nationality.as_deref())
             .bind(query.params.limit + 1)
+            .bind(config::strip_emoji_in_search())
+            .bind(query.difficulties())
+            .bind(query.before_id)
+            .bind(query.after_id)
             .fetch(connection);
 
         let mut demons = Vec::new();
@@ -204,6 +277,7 @@ impl Paginatable<DemonPositionPagination> for Demon {
             let row = row?;
 
             let video: Option<String> = row.get("video");
+            let thumbnail = resolve_thumbnail(row.get("thumbnail"), video.as_deref());
 
             demons.push(Demon {
                 base: MinimalDemon {
@@ -213,7 +287,7 @@ impl Paginatable<DemonPositionPagination> for Demon {
                 },
                 requirement: row.get("requirement"),
                 video,
-                thumbnail: row.get("thumbnail"),
+                thumbnail,
                 publisher: DatabasePlayer {
                     id: row.get("publisher_id"),
                     name: row.get("publisher_name"),
@@ -235,4 +309,38 @@ impl Paginatable<DemonPositionPagination> for Demon {
     fn pagination_id(&self) -> i32 {
         self.base.position as i32
     }
+
+    fn anchor_id(&self) -> Option<i32> {
+        Some(self.base.id)
+    }
+}
+
+/// Cursor for walking through the demons ordered by their mean enjoyment rating, highest first.
+///
+/// Demons with equal enjoyment are ordered by id, so the next page is obtained by passing the
+/// enjoyment and id of the last demon on the current page as `after_enjoyment` and `after_id`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemonEnjoymentPagination {
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub after_enjoyment: Option<f64>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub after_id: Option<i32>,
+
+    #[serde(default = "default_limit")]
+    pub limit: i32,
+}
+
+const fn default_limit() -> i32 {
+    DEFAULT_ENTRIES_PER_PAGE
+}
+
+impl DemonEnjoymentPagination {
+    pub fn validate(&self) -> Result<(), CoreError> {
+        if !(1..=ENTRIES_PER_PAGE).contains(&self.limit) {
+            return Err(CoreError::InvalidPaginationLimit);
+        }
+
+        Ok(())
+    }
 }
@@ -1,12 +1,19 @@
 use crate::{
-    demon::{Demon, Difficulty, FullDemon, MinimalDemon},
+    config,
+    demon::{Demon, DemonPosition, Difficulty, FullDemon, LevelIdResolution, MinimalDemon, SubmissionState},
     error::{DemonlistError, Result},
-    player::{DatabasePlayer, recompute_scores},
+    player::{recompute_scores, update_scores, DatabasePlayer},
+    record::{
+        approved_records_on,
+        note::{NewNote, Note},
+        FullRecord, RecordOrder, RecordStatus,
+    },
 };
 use log::{debug, info, warn};
 use pointercrate_core::util::{non_nullable, nullable};
 use serde::Deserialize;
 use sqlx::PgConnection;
+use std::collections::HashSet;
 
 #[derive(Deserialize, Debug, Default)]
 pub struct PatchDemon {
@@ -33,20 +40,43 @@ pub struct PatchDemon {
 
     #[serde(default, deserialize_with = "non_nullable")]
     pub difficulty: Option<Difficulty>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub archived: Option<bool>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub submissions_closed: Option<bool>,
 }
 
 impl FullDemon {
-    pub async fn apply_patch(mut self, patch: PatchDemon, connection: &mut PgConnection) -> Result<Self> {
+    /// Applies the given patch to this demon
+    ///
+    /// Approved records that no longer meet a raised requirement are not deleted (so that lowering
+    /// the requirement again restores them), but no longer listed or awarded points. They are
+    /// reported in [`FullDemon::below_requirement`] instead, for moderators to review.
+    pub async fn apply_patch(self, patch: PatchDemon, connection: &mut PgConnection) -> Result<Self> {
         let changes_requirement = patch.requirement.is_some();
 
-        let updated_demon = self.demon.apply_patch(patch, connection).await?;
+        let updated_demon = self.demon.apply_patch(patch, &mut *connection).await?;
 
-        if changes_requirement {
-            self.records.retain(|record| record.progress >= updated_demon.requirement);
+        if !changes_requirement {
+            return Ok(FullDemon {
+                demon: updated_demon,
+                ..self
+            });
         }
 
+        let records = approved_records_on(&updated_demon.base, RecordOrder::default(), connection).await?;
+        let below_requirement = self
+            .records
+            .into_iter()
+            .filter(|record| record.progress < updated_demon.requirement)
+            .collect();
+
         Ok(FullDemon {
             demon: updated_demon,
+            records,
+            below_requirement,
             ..self
         })
     }
@@ -57,6 +87,11 @@ impl Demon {
     pub async fn apply_patch(mut self, patch: PatchDemon, connection: &mut PgConnection) -> Result<Self> {
         // duplicate names are OK nowadays
 
+        // Archiving moves the demon, so this has to happen first for an explicit position to stick
+        if let Some(archived) = patch.archived {
+            self.set_archived(archived, connection).await?;
+        }
+
         if let Some(position) = patch.position {
             self.base.mv(position, connection).await?;
         }
@@ -89,16 +124,100 @@ impl Demon {
         }
 
         if let Some(requirement) = patch.requirement {
-            self.set_requirement(requirement, connection).await?;
+            self.set_requirement(requirement, config::reevaluate_on_requirement_decrease(), connection)
+                .await?;
         }
 
         if let Some(difficulty) = patch.difficulty {
             self.set_difficulty(difficulty, connection).await?;
         }
 
+        if let Some(submissions_closed) = patch.submissions_closed {
+            sqlx::query!("UPDATE demons SET submissions_closed = $1 WHERE id = $2", submissions_closed, self.base.id)
+                .execute(&mut *connection)
+                .await?;
+        }
+
         Ok(self)
     }
 
+    /// Moves several demons at once. All other demons keep their relative order, filling up the
+    /// positions not targeted by any move.
+    ///
+    /// The whole reorder is validated before anything is changed, and then applied as a single
+    /// update, meaning either all demons end up at their target positions, or none move.
+    ///
+    /// Only non-archived demons can be reordered, as archived demons are kept below all others.
+    ///
+    /// Must run inside a transaction!
+    pub async fn reorder(moves: &[DemonPosition], connection: &mut PgConnection) -> Result<()> {
+        Demon::lock_positions(&mut *connection).await?;
+
+        let current = sqlx::query_scalar!("SELECT id FROM demons WHERE NOT archived AND deleted_at IS NULL ORDER BY position")
+            .fetch_all(&mut *connection)
+            .await?;
+        let maximal = current.len() as i16;
+
+        let mut moved = HashSet::new();
+        let mut slots = vec![None; current.len()];
+
+        for demon_move in moves {
+            if !current.contains(&demon_move.id) {
+                return Err(DemonlistError::DemonNotFound { demon_id: demon_move.id });
+            }
+
+            if demon_move.position < 1 || demon_move.position > maximal {
+                return Err(DemonlistError::InvalidPosition { maximal });
+            }
+
+            if !moved.insert(demon_move.id) {
+                return Err(DemonlistError::DemonMovedTwice { demon_id: demon_move.id });
+            }
+
+            let slot = &mut slots[demon_move.position as usize - 1];
+
+            if slot.is_some() {
+                return Err(DemonlistError::DuplicateTargetPosition {
+                    position: demon_move.position,
+                });
+            }
+
+            *slot = Some(demon_move.id);
+        }
+
+        // There are exactly as many free slots as there are demons not being moved
+        let mut unmoved = current.iter().copied().filter(|id| !moved.contains(id));
+        let new_order = slots.into_iter().filter_map(|slot| slot.or_else(|| unmoved.next()));
+
+        let (ids, positions): (Vec<i32>, Vec<i16>) = new_order
+            .zip(1..)
+            .filter(|&(id, position)| current[position as usize - 1] != id)
+            .unzip();
+
+        if ids.is_empty() {
+            warn!("No-op reorder of {} demons", moves.len());
+
+            return Ok(());
+        }
+
+        // The unique constraint on positions is only checked at the end of the statement, so swapping
+        // positions within a single update is fine
+        sqlx::query!(
+            "UPDATE demons SET position = new.position FROM UNNEST($1::INT[], $2::SMALLINT[]) AS new(id, position) WHERE demons.id = \
+             new.id",
+            &ids,
+            &positions
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        info!("Reordered the list, {} demons changed position", ids.len());
+
+        recompute_scores(connection).await?;
+
+        Ok(())
+    }
+
     pub async fn set_verifier(&mut self, verifier: DatabasePlayer, connection: &mut PgConnection) -> Result<()> {
         if verifier.id != self.verifier.id {
             sqlx::query!("UPDATE demons SET verifier = $1 WHERE id = $2", verifier.id, self.base.id)
@@ -126,22 +245,89 @@ impl Demon {
         Ok(())
     }
 
-    pub async fn set_requirement(&mut self, requirement: i16, connection: &mut PgConnection) -> Result<()> {
-        if !(0..=100).contains(&requirement) {
-            return Err(DemonlistError::InvalidRequirement);
-        }
-
-        // Delete associated notes
-        sqlx::query!("DELETE FROM records WHERE demon = $1 AND progress < $2", self.base.id, requirement)
+    /// Changes this demon's requirement
+    ///
+    /// If `reevaluate` is set, submissions that no longer meet a raised requirement are rejected instead
+    /// of deleted, and put back into the queue once the requirement is lowered enough for them to
+    /// qualify again (see [`reevaluate_on_requirement_decrease`](config::reevaluate_on_requirement_decrease)).
+    /// Records that were rejected for any other reason stay rejected.
+    pub async fn set_requirement(&mut self, requirement: i16, reevaluate: bool, connection: &mut PgConnection) -> Result<()> {
+        Demon::validate_requirement(requirement)?;
+
+        // Remembering which requirement these failed tells them apart from records rejected for any other
+        // reason (cheating, a bad video, ...), which must never be reopened
+        if reevaluate && requirement > self.requirement {
+            sqlx::query!(
+                "UPDATE records SET status_ = 'REJECTED', failed_requirement = $2 WHERE demon = $1 AND progress < $2 AND status_ IN \
+                 ('SUBMITTED', 'UNDER_CONSIDERATION')",
+                self.base.id,
+                requirement
+            )
             .execute(&mut *connection)
             .await?;
+        }
+
+        // Submissions that can no longer be approved are dropped. Approved records are kept, they simply
+        // stop counting (see `FullDemon::apply_patch`), and so are rejected ones, as they still block
+        // resubmissions
+        sqlx::query!(
+            "DELETE FROM records WHERE demon = $1 AND progress < $2 AND status_ NOT IN ('APPROVED', 'REJECTED')",
+            self.base.id,
+            requirement
+        )
+        .execute(&mut *connection)
+        .await?;
 
         sqlx::query!("UPDATE demons SET requirement = $1 WHERE id = $2", requirement, self.base.id)
-            .execute(connection)
+            .execute(&mut *connection)
             .await?;
 
+        if requirement < self.requirement && reevaluate {
+            self.reopen_rejected_records(requirement, connection).await?;
+        }
+
         self.requirement = requirement;
 
+        // The requirement goes into the score of every record on this demon (and of its verification), not
+        // just of those that stopped (or started) meeting it
+        let players = sqlx::query_scalar!(
+            r#"SELECT player AS "player!" FROM records WHERE demon = $1 AND status_ = 'APPROVED'
+               UNION SELECT verifier FROM demons WHERE id = $1"#,
+            self.base.id
+        )
+        .fetch_all(&mut *connection)
+        .await?;
+
+        update_scores(&players, connection).await?;
+
+        Ok(())
+    }
+
+    /// Moves records that were rejected for not meeting a previous requirement, but qualify under the
+    /// given (lowered) one, back into the submission queue.
+    ///
+    /// Each reopened record gets an internal note explaining why it reappeared. Records are never
+    /// approved by this, a moderator still has to review them.
+    async fn reopen_rejected_records(&self, new_requirement: i16, connection: &mut PgConnection) -> Result<()> {
+        let reopened = sqlx::query!(
+            "UPDATE records SET status_ = 'SUBMITTED', failed_requirement = NULL WHERE demon = $1 AND status_ = 'REJECTED' AND \
+             failed_requirement IS NOT NULL AND progress >= $2 RETURNING id",
+            self.base.id,
+            new_requirement
+        )
+        .fetch_all(&mut *connection)
+        .await?;
+
+        for row in reopened {
+            let record = FullRecord::by_id(row.id, &mut *connection).await?;
+            let note = format!(
+                "Record automatically moved back into the queue: the requirement of {} was lowered from {}% to {}%",
+                self.base.name, self.requirement, new_requirement
+            );
+
+            Note::create_on(&record, NewNote::new(note, false), &mut *connection).await?;
+        }
+
         Ok(())
     }
 
@@ -177,17 +363,125 @@ impl Demon {
         Ok(())
     }
 
+    /// Archives (or unarchives) this demon, stopping any further submissions for it.
+    ///
+    /// Archived demons are kept below all others, so the demon is moved to the boundary between
+    /// the non-archived and the archived demons. Unarchiving fails if the list already holds
+    /// [`config::max_list_length`] demons.
+    ///
+    /// Submissions still in the queue stay there for review, unless
+    /// [`config::reject_pending_on_archive`] is set, in which case they are rejected.
+    ///
+    /// Must run inside a transaction!
+    pub async fn set_archived(&mut self, archived: bool, connection: &mut PgConnection) -> Result<()> {
+        self.set_archived_with(archived, config::reject_pending_on_archive(), connection).await
+    }
+
+    /// Like [`Demon::set_archived`], but rejecting the submissions still in the queue if (and
+    /// only if) `reject_pending` is set. Each rejected record gets an internal note saying why.
+    ///
+    /// Must run inside a transaction!
+    pub async fn set_archived_with(&mut self, archived: bool, reject_pending: bool, connection: &mut PgConnection) -> Result<()> {
+        Demon::lock_positions(&mut *connection).await?;
+
+        let currently_archived = self.base.submission_state(&mut *connection).await? == SubmissionState::Archived;
+
+        if currently_archived == archived {
+            return Ok(());
+        }
+
+        let last_active = Demon::max_active_position(&mut *connection).await?;
+        let to = if archived {
+            last_active
+        } else {
+            if let Some(maximal) = config::max_list_length() {
+                if last_active >= maximal {
+                    return Err(DemonlistError::ListFull { maximal });
+                }
+            }
+
+            last_active + 1
+        };
+
+        if to != self.base.position {
+            self.base.shift_to(to, &mut *connection).await?;
+        }
+
+        sqlx::query!("UPDATE demons SET archived = $1 WHERE id = $2", archived, self.base.id)
+            .execute(&mut *connection)
+            .await?;
+
+        recompute_scores(&mut *connection).await?;
+
+        if archived && reject_pending {
+            let pending = sqlx::query!(
+                "SELECT id FROM records WHERE demon = $1 AND status_ IN ('SUBMITTED', 'UNDER_CONSIDERATION')",
+                self.base.id
+            )
+            .fetch_all(&mut *connection)
+            .await?;
+
+            for row in pending {
+                let mut record = FullRecord::by_id(row.id, &mut *connection).await?;
+
+                record.set_status(RecordStatus::Rejected, &mut *connection).await?;
+
+                let note = format!("Record automatically rejected: {} was archived", self.base.name);
+
+                Note::create_on(&record, NewNote::new(note, false), &mut *connection).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Updates this demon's level id to the result of looking up its current name
+    ///
+    /// The stored level id is left untouched if no level matches, or if several do, in which case
+    /// an [`DemonlistError::AmbiguousLevel`] listing all of them is returned instead of guessing.
+    pub async fn refresh_level_id(&mut self, resolution: LevelIdResolution, connection: &mut PgConnection) -> Result<()> {
+        let level_id = match resolution {
+            LevelIdResolution::Found(level_id) => level_id,
+            LevelIdResolution::NotFound => {
+                return Err(DemonlistError::LevelNotFound {
+                    level_name: self.base.name.clone(),
+                })
+            },
+            LevelIdResolution::Ambiguous(candidates) => return Err(DemonlistError::AmbiguousLevel { candidates }),
+        };
+
+        if self.level_id != Some(level_id) {
+            let stored_id = i64::try_from(level_id).map_err(|_| DemonlistError::InvalidLevelId)?;
+
+            sqlx::query!("UPDATE demons SET level_id = $1 WHERE id = $2", stored_id, self.base.id)
+                .execute(connection)
+                .await?;
+
+            info!("Refreshed level id of demon {} from {:?} to {}", self.base, self.level_id, level_id);
+
+            self.level_id = Some(level_id);
+        }
+
+        Ok(())
+    }
+
     pub async fn set_difficulty(&mut self, difficulty: Difficulty, connection: &mut PgConnection) -> Result<()> {
         sqlx::query!(
             "UPDATE demons SET difficulty = $1::level_difficulty WHERE id = $2",
             Difficulty::to_sql(difficulty) as _,
             self.base.id
         )
-        .execute(connection)
+        .execute(&mut *connection)
         .await?;
 
+        let multiplier_changed = self.difficulty.score_multiplier() != difficulty.score_multiplier();
+
         self.difficulty = difficulty;
 
+        if multiplier_changed {
+            recompute_scores(connection).await?;
+        }
+
         Ok(())
     }
 }
@@ -208,12 +502,21 @@ impl MinimalDemon {
     /// Moves this demon to the specified position
     ///
     /// Validates that `to` is `> 0` and less than or equal to the currently highest position on the
-    /// list (to preven "holes")
+    /// list (to preven "holes"). Archived demons are kept below all others, so non-archived demons
+    /// can only be moved between the non-archived ones, and archived demons between the archived ones.
+    ///
+    /// Must run inside a transaction!
     pub async fn mv(&mut self, to: i16, connection: &mut PgConnection) -> Result<()> {
+        Demon::lock_positions(&mut *connection).await?;
+
         // This returns 0 if the list is empty, but if the list is empty then there is no demon for us to do a move with, so we will never get here anyway.
-        let maximal_position = Demon::max_position(connection).await?;
+        let last_active = Demon::max_active_position(&mut *connection).await?;
+        let (minimal_position, maximal_position) = match self.submission_state(&mut *connection).await? {
+            SubmissionState::Archived => (last_active + 1, Demon::max_position(&mut *connection).await?),
+            SubmissionState::Open | SubmissionState::Closed => (1, last_active),
+        };
 
-        if to > maximal_position || to < 1 {
+        if to > maximal_position || to < minimal_position {
             return Err(DemonlistError::InvalidPosition { maximal: maximal_position });
         }
 
@@ -223,6 +526,16 @@ impl MinimalDemon {
             return Ok(());
         }
 
+        self.shift_to(to, &mut *connection).await?;
+
+        recompute_scores(connection).await?;
+
+        Ok(())
+    }
+
+    /// Moves this demon to the specified position, shifting all demons in between by one, without
+    /// any validation
+    async fn shift_to(&mut self, to: i16, connection: &mut PgConnection) -> Result<()> {
         // FIXME: Temporarily move the demon somewhere else because otherwise the unique constraints
         // complains. I actually dont know why, its DEFERRABLE INITIALLY IMMEDIATE (whatever the
         // fuck that means, it made it work in the python version)
@@ -237,7 +550,7 @@ impl MinimalDemon {
             );
 
             sqlx::query!(
-                "UPDATE demons SET position = position - 1 WHERE position > $1 AND position <= $2",
+                "UPDATE demons SET position = position - 1 WHERE position > $1 AND position <= $2 AND deleted_at IS NULL",
                 self.position,
                 to
             )
@@ -250,7 +563,7 @@ impl MinimalDemon {
             );
 
             sqlx::query!(
-                "UPDATE demons SET position = position + 1 WHERE position >= $1 AND position < $2",
+                "UPDATE demons SET position = position + 1 WHERE position >= $1 AND position < $2 AND deleted_at IS NULL",
                 to,
                 self.position
             )
@@ -268,8 +581,6 @@ impl MinimalDemon {
 
         self.position = to;
 
-        recompute_scores(connection).await?;
-
         Ok(())
     }
 }
@@ -1,8 +1,11 @@
 use crate::{
+    config,
     creator::Creator,
-    demon::{Demon, Difficulty, FullDemon, MinimalDemon},
-    error::Result,
+    demon::{resolve_thumbnail, Demon, Difficulty, FullDemon, MinimalDemon},
+    error::{DemonlistError, Result},
     player::{DatabasePlayer, recompute_scores},
+    record::{approved_records_on, RecordOrder},
+    submitter::Submitter,
 };
 use log::info;
 use serde::Deserialize;
@@ -10,22 +13,77 @@ use sqlx::PgConnection;
 
 #[derive(Deserialize, Debug)]
 pub struct PostDemon {
-    name: String,
+    pub name: String,
     position: i16,
     requirement: i16,
     verifier: String,
     publisher: String,
     creators: Vec<String>,
     video: Option<String>,
-    level_id: Option<i64>,
-    difficulty: Difficulty,
+
+    /// If not given, the API tries to resolve it from the name
+    pub level_id: Option<i64>,
+
+    /// If not given, the difficulty is determined from the configured position bands
+    #[serde(default)]
+    difficulty: Option<Difficulty>,
+
+    /// Whether to also add an approved 100% record for the verifier, see
+    /// [`FullDemon::add_verification_record`]
+    #[serde(default)]
+    pub verification_record: bool,
+}
+
+/// The fields of [`PostDemon`] that can be made mandatory via [`config::required_demon_fields`]
+pub(crate) const OPTIONAL_DEMON_FIELDS: [&str; 4] = ["video", "level_id", "difficulty", "creators"];
+
+impl PostDemon {
+    /// Returns those of the given fields that were not provided, in the order they were given
+    fn missing_fields(&self, required: &[String]) -> Vec<String> {
+        required
+            .iter()
+            .filter(|field| match field.as_str() {
+                "video" => self.video.is_none(),
+                "level_id" => self.level_id.is_none(),
+                "difficulty" => self.difficulty.is_none(),
+                "creators" => self.creators.is_empty(),
+                _ => false,
+            })
+            .cloned()
+            .collect()
+    }
 }
 
 impl FullDemon {
+    /// Creates a new demon, capping the list at the configured [maximal length](config::max_list_length)
+    ///
+    /// Returns the new demon, and the demons that were archived to make room for it (which can include
+    /// the new demon itself, if it was placed past the end of a full list).
+    ///
     /// Must be run within a transaction!
-    pub async fn create_from(data: PostDemon, connection: &mut PgConnection) -> Result<FullDemon> {
+    pub async fn create_from(data: PostDemon, connection: &mut PgConnection) -> Result<(FullDemon, Vec<MinimalDemon>)> {
+        FullDemon::create_capped(data, config::max_list_length(), config::archive_list_overflow(), connection).await
+    }
+
+    /// Creates a new demon, making sure that at most `max_length` non-archived demons are on the list
+    /// afterwards
+    ///
+    /// If the list would grow past `max_length`, the lowest non-archived demons are archived if
+    /// `archive_overflow` is set, and the placement is rejected otherwise. The archived demons are
+    /// returned alongside the new demon.
+    ///
+    /// Must be run within a transaction!
+    pub async fn create_capped(
+        data: PostDemon, max_length: Option<i16>, archive_overflow: bool, connection: &mut PgConnection,
+    ) -> Result<(FullDemon, Vec<MinimalDemon>)> {
         info!("Creating new demon from {:?}", data);
 
+        let missing = data.missing_fields(&config::required_demon_fields());
+
+        if !missing.is_empty() {
+            return Err(DemonlistError::MissingRequiredFields { fields: missing });
+        }
+
         Demon::validate_requirement(data.requirement)?;
         let level_id = data.level_id.map(Demon::validate_level_id).transpose()?;
 
@@ -34,16 +92,32 @@ impl FullDemon {
             None => None,
         };
 
+        // Held until the transaction ends, so no other insert or move can happen between validating the
+        // position and shifting the other demons out of the way
+        Demon::lock_positions(&mut *connection).await?;
         Demon::validate_position(data.position, connection).await?;
 
+        let difficulty = data
+            .difficulty
+            .or_else(|| Difficulty::from_position_bands(data.position))
+            .ok_or(DemonlistError::DifficultyRequired)?;
+
         let publisher = DatabasePlayer::by_name_or_create(data.publisher.as_ref(), connection).await?;
         let verifier = DatabasePlayer::by_name_or_create(data.verifier.as_ref(), connection).await?;
 
+        if let Some(maximal) = max_length {
+            if !archive_overflow && Demon::max_active_position(connection).await? >= maximal {
+                return Err(DemonlistError::ListFull { maximal });
+            }
+        }
+
         Demon::shift_down(data.position, connection).await?;
 
+        // New demons do not give points until their grace period (if any) has passed
         let created = sqlx::query!(
-            "INSERT INTO demons (name, position, requirement, video, verifier, publisher, level_id, difficulty) 
-            VALUES ($1::text, $2, $3, $4::text, $5, $6, $7, $8::level_difficulty) 
+            "INSERT INTO demons (name, position, requirement, video, verifier, publisher, level_id, difficulty, scores_from) 
+            VALUES ($1::text, $2, $3, $4::text, $5, $6, $7, $8::level_difficulty,
+                    CASE WHEN $9 > 0 THEN (NOW() AT TIME ZONE 'utc') + make_interval(hours => $9) END) 
             RETURNING id, thumbnail",
             data.name.to_string(),
             data.position,
@@ -52,7 +126,8 @@ impl FullDemon {
             verifier.id,
             publisher.id,
             data.level_id,
-            Difficulty::to_sql(data.difficulty) as _,
+            Difficulty::to_sql(difficulty) as _,
+            config::new_demon_grace_hours(),
         )
         .fetch_one(&mut *connection)
         .await?;
@@ -64,30 +139,76 @@ impl FullDemon {
                 name: data.name,
             },
             requirement: data.requirement,
+            thumbnail: resolve_thumbnail(created.thumbnail, video.as_deref()),
             video,
-            thumbnail: created.thumbnail,
             publisher,
             verifier,
             level_id,
-            difficulty: data.difficulty,
+            difficulty,
         };
 
         let mut creators = Vec::new();
 
         for creator in data.creators {
-            let player = DatabasePlayer::by_name_or_create(creator.as_ref(), &mut *connection).await?;
-            Creator::insert(&demon.base, &player, connection).await?;
+            let player = Creator::insert_by_name(&demon.base, creator.as_ref(), &mut *connection).await?;
+
+            if !creators.contains(&player) {
+                creators.push(player);
+            }
+        }
+
+        let archived = match max_length {
+            Some(maximal) => Demon::archive_overflow(maximal, connection).await?,
+            None => Vec::new(),
+        };
 
-            creators.push(player);
+        for overflown in &archived {
+            info!("Archived {} as placing {} pushed it past the end of the list", overflown, demon.base);
         }
 
         recompute_scores(connection).await?;
 
-        Ok(FullDemon {
+        let demon = FullDemon {
             demon,
             creators,
             records: Vec::new(),
-        })
+            peak_position: None,
+            below_requirement: Vec::new(),
+        };
+
+        Ok((demon, archived))
+    }
+
+    /// Adds an approved 100% record for this demon's verifier, attributed to the given submitter. Does nothing
+    /// if the verifier already has an approved completion of this demon.
+    ///
+    /// The record has no video of its own, as the verification video is already the demon's video. It does
+    /// not change the verifier's score either, as verifiers are already awarded the demon's full points.
+    ///
+    /// Must be run within a transaction!
+    pub async fn add_verification_record(&mut self, submitter: &Submitter, connection: &mut PgConnection) -> Result<()> {
+        let inserted = sqlx::query!(
+            "INSERT INTO records (progress, status_, player, submitter, demon, video_exempt, approved_by, approved_at)
+            SELECT 100, 'APPROVED', $1, $2, $3, TRUE, (SELECT id FROM active_user LIMIT 1), NOW() AT TIME ZONE 'utc'
+            WHERE NOT EXISTS (SELECT 1 FROM records WHERE player = $1 AND demon = $3 AND status_ = 'APPROVED' AND progress = 100)
+            RETURNING id",
+            self.demon.verifier.id,
+            submitter.id,
+            self.demon.base.id
+        )
+        .fetch_optional(&mut *connection)
+        .await?;
+
+        if let Some(row) = inserted {
+            info!(
+                "Added verification record {} for {} on {}",
+                row.id, self.demon.verifier, self.demon.base
+            );
+
+            self.records = approved_records_on(&self.demon.base, RecordOrder::default(), connection).await?;
+        }
+
+        Ok(())
     }
 }
 
@@ -100,11 +221,32 @@ mod tests {
         error::DemonlistError,
     };
 
-    const DEFAULT_THUMBNAIL: &str = "https://i.ytimg.com/vi/zebrafishes/mqdefault.jpg";
+    #[test]
+    fn test_missing_required_fields() {
+        let demon = PostDemon {
+            name: "Bloodbath".to_owned(),
+            position: 1,
+            requirement: 90,
+            verifier: "Riot".to_owned(),
+            publisher: "Riot".to_owned(),
+            creators: Vec::new(),
+            video: Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_owned()),
+            level_id: None,
+            difficulty: None,
+            verification_record: false,
+        };
+
+        assert!(demon.missing_fields(&[]).is_empty());
+        assert!(demon.missing_fields(&["video".to_owned()]).is_empty());
+        assert_eq!(
+            demon.missing_fields(&["creators".to_owned(), "video".to_owned(), "level_id".to_owned(), "difficulty".to_owned()]),
+            vec!["creators".to_owned(), "level_id".to_owned(), "difficulty".to_owned()]
+        );
+    }
 
     #[sqlx::test(migrations = "../migrations")]
     async fn test_default_thumbnail_no_video(mut conn: PoolConnection<Postgres>) {
-        let demon = FullDemon::create_from(
+        let (demon, _) = FullDemon::create_from(
             PostDemon {
                 name: "Bloodbath".to_owned(),
                 position: 1,
@@ -114,14 +256,15 @@ mod tests {
                 creators: Vec::new(),
                 video: None,
                 level_id: None,
-                difficulty: Difficulty::Silent,
+                difficulty: Some(Difficulty::Silent),
+                verification_record: false,
             },
             &mut conn,
         )
         .await
         .unwrap();
 
-        assert_eq!(demon.demon.thumbnail, DEFAULT_THUMBNAIL);
+        assert_eq!(demon.demon.thumbnail, crate::config::placeholder_thumbnail());
     }
 
     #[sqlx::test(migrations = "../migrations")]
@@ -131,7 +274,7 @@ mod tests {
             .await
             .unwrap();
 
-        let demon = FullDemon::create_from(
+        let (demon, _) = FullDemon::create_from(
             PostDemon {
                 name: "Bloodbath".to_owned(),
                 position: 1,
@@ -141,19 +284,20 @@ mod tests {
                 creators: Vec::new(),
                 video: Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_owned()),
                 level_id: None,
-                difficulty: Difficulty::Silent,
+                difficulty: Some(Difficulty::Silent),
+                verification_record: false,
             },
             &mut conn,
         )
         .await
         .unwrap();
 
-        assert_eq!(demon.demon.thumbnail, DEFAULT_THUMBNAIL);
+        assert_eq!(demon.demon.thumbnail, crate::config::placeholder_thumbnail());
     }
 
     #[sqlx::test(migrations = "../migrations")]
     async fn test_default_thumbnail_with_video(mut conn: PoolConnection<Postgres>) {
-        let demon = FullDemon::create_from(
+        let (demon, _) = FullDemon::create_from(
             PostDemon {
                 name: "Bloodbath".to_owned(),
                 position: 1,
@@ -163,7 +307,8 @@ mod tests {
                 creators: Vec::new(),
                 video: Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_owned()),
                 level_id: None,
-                difficulty: Difficulty::Silent,
+                difficulty: Some(Difficulty::Silent),
+                verification_record: false,
             },
             &mut conn,
         )
@@ -185,7 +330,8 @@ mod tests {
                 creators: Vec::new(),
                 video: None,
                 level_id: Some(-1),
-                difficulty: Difficulty::Silent,
+                difficulty: Some(Difficulty::Silent),
+                verification_record: false,
             },
             &mut conn,
         )
@@ -1,8 +1,8 @@
-use crate::{demon::MinimalDemon, record::RecordStatus};
+use crate::{demon::MinimalDemon, player::DatabasePlayer, record::RecordStatus};
 use derive_more::Display;
 
 use pointercrate_core::error::{CoreError, PointercrateError};
-use serde::Serialize;
+use serde::{Serialize, Serializer};
 
 pub type Result<T> = std::result::Result<T, DemonlistError>;
 
@@ -26,7 +26,7 @@ pub enum DemonlistError {
     ///
     /// Error Code `40304`
     #[display("You are banned from submitting records to the demonlist!")]
-    BannedFromSubmissions,
+    SubmitterBanned,
 
     #[display("You claim on this player is unverified")]
     ClaimUnverified,
@@ -77,6 +77,9 @@ pub enum DemonlistError {
     #[display("No record with id {} found", record_id)]
     RecordNotFound { record_id: i32 },
 
+    #[display("No Geometry Dash level named {} found", level_name)]
+    LevelNotFound { level_name: String },
+
     #[display("No claim by user {} on player {} found", member_id, player_id)]
     ClaimNotFound { member_id: i32, player_id: i32 },
 
@@ -155,9 +158,12 @@ pub enum DemonlistError {
 
     /// `422 UNPROCESSABLE ENTITY` variant
     ///
+    /// Unless configured otherwise, supported are 'youtube', 'vimeo', 'everyplay', 'twitch' and
+    /// 'bilibili'
+    ///
     /// Error Code `42224`
-    #[display("The given video host is not supported. Supported are 'youtube', 'vimeo', 'everyplay', 'twitch' and 'bilibili'")]
-    UnsupportedVideoHost,
+    #[display("The video host '{}' is not supported", host)]
+    UnsupportedVideoHost { host: String },
 
     /// `422 UNPROCESSABLE ENTITY` variant
     ///
@@ -200,6 +206,200 @@ pub enum DemonlistError {
     /// Error Code 42236
     #[display("Enjoyment must be between 0 and 10")]
     InvalidEnjoyment,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a player name could not be resolved to a
+    /// single player
+    ///
+    /// Error Code `42237`
+    #[display("There are multiple players with the given name")]
+    AmbiguousPlayerName { players: Vec<DatabasePlayer> },
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42238`
+    #[display("A video must be provided for this record")]
+    VideoRequired,
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42239`
+    #[display("An enjoyment rating must be provided to submit this record")]
+    EnjoymentRequired,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a demon is added without a difficulty at a
+    /// position not covered by the configured difficulty bands
+    ///
+    /// Error Code `42240`
+    #[display("A difficulty needs to be specified for demons at this position")]
+    DifficultyRequired,
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42241`
+    #[display("A video must be provided for 100% records")]
+    CompletionVideoRequired,
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42242`
+    #[display("Creator names mustn't be empty!")]
+    CreatorNameEmpty,
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42243`
+    #[display("A demon cannot have more than {} creators", maximum)]
+    TooManyCreators { maximum: i64 },
+
+    /// `503 SERVICE UNAVAILABLE` variant returned when non-staff try to submit records while
+    /// submissions are frozen (e.g. during a major list update)
+    ///
+    /// Error Code `50302`
+    #[display("Record submissions are currently frozen. Please try again later")]
+    SubmissionsFrozen,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a demon's requirement would be set below the
+    /// globally configured minimum requirement
+    ///
+    /// Error Code `42244`
+    #[display("Record requirement cannot be lower than {}%", minimum)]
+    RequirementBelowMinimum { minimum: i16 },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a demon is added without some of the fields the
+    /// configured creation profile requires
+    ///
+    /// Error Code `42245`
+    #[display("The following fields are required when adding a demon: {}", fields.join(", "))]
+    MissingRequiredFields { fields: Vec<String> },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a record patch would lower its progress by
+    /// more than the configured maximum, without being forced
+    ///
+    /// Error Code `42246`
+    #[display("Record progress cannot be lowered by more than {}% at once unless forced", maximal)]
+    ProgressDecreaseTooLarge { maximal: i16 },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if someone tries to submit a record for an
+    /// archived demon
+    ///
+    /// Error Code `42247`
+    #[display("This demon has been archived and no longer accepts submissions")]
+    DemonArchived,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if someone tries to submit a record for a demon
+    /// whose submissions have been (temporarily) closed
+    ///
+    /// Error Code `42248`
+    #[display("Submissions for this demon are currently closed")]
+    SubmissionsClosed,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned when trying to verify a claim on a banned player
+    ///
+    /// Error Code `42249`
+    #[display("Claims on banned players cannot be verified")]
+    ClaimOnBannedPlayer,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if placing a demon would push another one past
+    /// the configured maximal list length, and overflowing demons are not automatically archived
+    ///
+    /// Error Code `42250`
+    #[display("The list cannot hold more than {} demons", maximal)]
+    ListFull { maximal: i16 },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned when parsing an unknown difficulty tier
+    ///
+    /// Error Code `42251`
+    #[display("'{}' is not a valid difficulty", difficulty)]
+    InvalidDifficulty { difficulty: String },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a record is rejected with a reason for which no
+    /// note template is configured
+    ///
+    /// Error Code `42252`
+    #[display("No note template is configured for rejection reason '{}'", reason)]
+    UnknownReasonTemplate { reason: String },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a rejection note template is given in a record
+    /// patch that does not reject the record
+    ///
+    /// Error Code `42253`
+    #[display("Rejection note templates can only be used when rejecting a record")]
+    ReasonTemplateWithoutRejection,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a record that is part of a bulk status change
+    /// cannot be moved to the requested status
+    ///
+    /// Error Code `42254`
+    #[display("The status of record {} cannot be changed: {}", record_id, reason)]
+    BulkStatusChangeFailed { record_id: i32, reason: String },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a record cannot be moved from its current status
+    /// to the requested one (e.g. an approved record cannot be put under consideration)
+    ///
+    /// Error Code `42255`
+    #[display("A record that is {} cannot become {}", from, to)]
+    InvalidStatusTransition { from: RecordStatus, to: RecordStatus },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a player is requested to be merged into
+    /// themselves
+    ///
+    /// Error Code `42256`
+    #[display("Player {} cannot be merged into themselves", player_id)]
+    SelfMerge { player_id: i32 },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if, with [`crate::config::strict_claim_geolocation`]
+    /// set, a moderator tries to verify a claim whose claimant was not geolocated to the player's
+    /// country. `actual` is `None` if geolocation was unavailable when the claim was made.
+    ///
+    /// Error Code `42257`
+    #[display(
+        "Claim cannot be verified: the claimant was geolocated to {}, but the player's nationality is {}",
+        actual.as_deref().unwrap_or("an unknown country"),
+        expected.as_deref().unwrap_or("unknown")
+    )]
+    GeolocationMismatch { expected: Option<String>, actual: Option<String> },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a demon search query is shorter than
+    /// [`crate::demon::MIN_SEARCH_LENGTH`] characters
+    ///
+    /// Error Code `42258`
+    #[display("Search queries must be at least {} characters long", min_length)]
+    SearchQueryTooShort { min_length: usize },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if the level id of a demon is refreshed, but
+    /// multiple Geometry Dash levels share its name
+    ///
+    /// Error Code `42259`
+    #[display("Multiple Geometry Dash levels match this demon's name (candidates: {:?})", candidates)]
+    AmbiguousLevel { candidates: Vec<u64> },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if more than one field of a submission is invalid.
+    /// Serialized as a list of the individual problems, each tagged with the field it concerns.
+    ///
+    /// Error Code `42260`
+    #[display("{} fields failed to validate", _0.len())]
+    ValidationErrors(#[serde(serialize_with = "serialize_field_errors")] Vec<DemonlistError>),
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a reorder of the list tries to move more than
+    /// one demon to the same position
+    ///
+    /// Error Code `42261`
+    #[display("Multiple demons cannot be moved to position {}", position)]
+    DuplicateTargetPosition { position: i16 },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a reorder of the list contains multiple moves
+    /// for the same demon
+    ///
+    /// Error Code `42262`
+    #[display("Demon {} can only be moved once per reorder", demon_id)]
+    DemonMovedTwice { demon_id: i32 },
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a record that is not a completion comes with an
+    /// enjoyment rating while [`crate::config::enjoyment_requires_completion`] is set
+    ///
+    /// Error Code `42263`
+    #[display("Only completions can be given an enjoyment rating")]
+    EnjoymentRequiresCompletion,
 }
 
 impl std::error::Error for DemonlistError {}
@@ -219,7 +419,7 @@ impl PointercrateError for DemonlistError {
             InvalidPosition { .. } => 42213,
             NoteEmpty => 42230,
             MalformedVideoUrl => 40001,
-            BannedFromSubmissions => 40304,
+            SubmitterBanned => 40304,
             ClaimUnverified => 40306,
             VpsDetected => 40307,
             NoThirdPartySubmissions => 40308,
@@ -231,6 +431,7 @@ impl PointercrateError for DemonlistError {
             DemonNotFoundName { .. } => 40401,
             DemonNotFoundPosition { .. } => 40401,
             RecordNotFound { .. } => 40401,
+            LevelNotFound { .. } => 40401,
             ClaimNotFound { .. } => 40401,
             DuplicateVideo { .. } => 40906,
             NoNationSet => 40907,
@@ -240,15 +441,90 @@ impl PointercrateError for DemonlistError {
             PlayerBanned => 42218,
             SubmitLegacy => 42219,
             Non100Extended => 42220,
-            UnsupportedVideoHost => 42224,
+            UnsupportedVideoHost { .. } => 42224,
             DemonNameNotUnique { .. } => 42228,
             AlreadyClaimed => 42231,
             RawRequired => 42232,
             MalformedRawUrl => 42233,
             InvalidLevelId => 42235,
             InvalidEnjoyment => 42236,
+            AmbiguousPlayerName { .. } => 42237,
+            VideoRequired => 42238,
+            EnjoymentRequired => 42239,
+            DifficultyRequired => 42240,
+            CompletionVideoRequired => 42241,
+            CreatorNameEmpty => 42242,
+            TooManyCreators { .. } => 42243,
+            SubmissionsFrozen => 50302,
+            RequirementBelowMinimum { .. } => 42244,
+            MissingRequiredFields { .. } => 42245,
+            ProgressDecreaseTooLarge { .. } => 42246,
+            DemonArchived => 42247,
+            SubmissionsClosed => 42248,
+            ClaimOnBannedPlayer => 42249,
+            ListFull { .. } => 42250,
+            InvalidDifficulty { .. } => 42251,
+            UnknownReasonTemplate { .. } => 42252,
+            ReasonTemplateWithoutRejection => 42253,
+            BulkStatusChangeFailed { .. } => 42254,
+            InvalidStatusTransition { .. } => 42255,
+            SelfMerge { .. } => 42256,
+            GeolocationMismatch { .. } => 42257,
+            SearchQueryTooShort { .. } => 42258,
+            AmbiguousLevel { .. } => 42259,
+            ValidationErrors(_) => 42260,
+            DuplicateTargetPosition { .. } => 42261,
+            DemonMovedTwice { .. } => 42262,
+            EnjoymentRequiresCompletion => 42263,
+        }
+    }
+}
+
+impl DemonlistError {
+    /// Turns the errors found while validating several independent fields into a single result
+    ///
+    /// A lone error is returned as-is, so that clients matching on specific error codes keep working
+    /// when only one thing is wrong.
+    pub fn aggregate(mut errors: Vec<DemonlistError>) -> Result<()> {
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(DemonlistError::ValidationErrors(errors)),
         }
     }
+
+    /// The submission field this error is about, if it is one that can be part of
+    /// [`DemonlistError::ValidationErrors`]
+    fn field(&self) -> Option<&'static str> {
+        use DemonlistError::*;
+
+        match self {
+            InvalidProgress { .. } => Some("progress"),
+            InvalidEnjoyment | EnjoymentRequired | EnjoymentRequiresCompletion => Some("enjoyment"),
+            RawRequired | MalformedRawUrl => Some("raw_footage"),
+            MalformedVideoUrl | UnsupportedVideoHost { .. } | VideoRequired | CompletionVideoRequired => Some("video"),
+            // Of the fields that are validated together, only the video can fail URL validation from the core crate
+            Core(CoreError::InvalidUrlScheme | CoreError::UrlAuthenticated | CoreError::InvalidUrlFormat { .. }) => Some("video"),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct FieldError<'a> {
+    field: Option<&'static str>,
+    message: String,
+    code: u16,
+    data: &'a DemonlistError,
+}
+
+fn serialize_field_errors<S: Serializer>(errors: &[DemonlistError], serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_seq(errors.iter().map(|error| FieldError {
+        field: error.field(),
+        message: error.to_string(),
+        code: error.error_code(),
+        data: error,
+    }))
 }
 
 impl From<CoreError> for DemonlistError {
@@ -1,7 +1,7 @@
 use crate::{
     demon::MinimalDemon,
     error::{DemonlistError, Result},
-    nationality::{BestRecord, MiniDemonWithPlayers, Nationality, NationalityRecord, Subdivision},
+    nationality::{BestRecord, MiniDemonWithPlayers, Nationality, NationalityRecord, NationalityStats, Subdivision},
 };
 use futures::stream::StreamExt;
 use sqlx::{Error, PgConnection};
@@ -52,6 +52,40 @@ impl Nationality {
         })
     }
 
+    /// Sums up the scores of the (non-banned) players of the given country, grouped by their
+    /// subdivision, highest total first
+    ///
+    /// Players without a subdivision are counted towards [`Subdivision::unknown`].
+    pub async fn subdivision_leaderboard(country_code: &str, connection: &mut PgConnection) -> Result<Vec<(Subdivision, f64)>> {
+        let nation = Nationality::by_country_code_or_name(country_code, &mut *connection).await?;
+
+        let mut stream = sqlx::query!(
+            r#"SELECT players.subdivision AS "iso_code?: String", subdivisions.name AS "name?: String", SUM(players.score) AS "score!"
+               FROM players
+               LEFT OUTER JOIN subdivisions ON subdivisions.nation = players.nationality AND subdivisions.iso_code = players.subdivision
+               WHERE players.nationality = $1 AND NOT players.banned
+               GROUP BY players.subdivision, subdivisions.name
+               ORDER BY "score!" DESC, subdivisions.name"#,
+            nation.iso_country_code
+        )
+        .fetch(connection);
+
+        let mut leaderboard = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            let subdivision = match (row.iso_code, row.name) {
+                (Some(iso_code), Some(name)) => Subdivision { iso_code, name },
+                _ => Subdivision::unknown(),
+            };
+
+            leaderboard.push((subdivision, row.score));
+        }
+
+        Ok(leaderboard)
+    }
+
     pub async fn subdivision_by_code(&self, code: &str, connection: &mut PgConnection) -> Result<Subdivision> {
         let result = sqlx::query!(
             "SELECT name FROM subdivisions WHERE iso_code = $1 AND nation = $2",
@@ -113,6 +147,38 @@ impl Nationality {
         Ok(nationalities)
     }
 
+    /// All nations, together with player counts and mean player scores. Nations without any players are
+    /// included as well, with all aggregates being zero
+    pub async fn with_stats(connection: &mut PgConnection) -> Result<Vec<NationalityStats>> {
+        let mut stream = sqlx::query!(
+            r#"SELECT nation as "nation: String", iso_country_code as "iso_country_code: String", COUNT(players.id) AS "players!",
+             COUNT(players.id) FILTER (WHERE EXISTS (SELECT 1 FROM player_claims WHERE player_id = players.id AND verified))
+               AS "claimed_players!",
+             COALESCE(AVG(players.score), 0) AS "mean_score!"
+             FROM nationalities LEFT OUTER JOIN players ON players.nationality = iso_country_code AND NOT players.banned
+             GROUP BY iso_country_code ORDER BY nation"#
+        )
+        .fetch(connection);
+        let mut stats = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            stats.push(NationalityStats {
+                nation: Nationality {
+                    nation: row.nation,
+                    iso_country_code: row.iso_country_code,
+                    subdivision: None,
+                },
+                players: row.players,
+                claimed_players: row.claimed_players,
+                mean_score: row.mean_score,
+            })
+        }
+
+        Ok(stats)
+    }
+
     pub async fn upgrade(self, connection: &mut PgConnection) -> Result<NationalityRecord> {
         Ok(NationalityRecord {
             best_records: best_records_in(&self, connection).await?,
@@ -127,7 +193,7 @@ impl Nationality {
 
 pub async fn unbeaten_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<MinimalDemon>> {
     let mut stream = sqlx::query!(
-        r#"select name::text as "name!", id as "id!", position as "position!" from demons where position <= $1 except (select demons.name, demons.id, position from records inner join players on 
+        r#"select name::text as "name!", id as "id!", position as "position!" from demons where position <= $1 and deleted_at is null except (select demons.name, demons.id, position from records inner join players on 
          players.id=records.player inner join demons on demons.id=records.demon where status_='APPROVED' and nationality=$2 and progress=100 union select demons.name, demons.id, demons.position from demons inner join players on players.id=verifier where players.nationality=$2)"#,
         crate::config::extended_list_size(),
         nation.iso_country_code
@@ -150,7 +216,7 @@ pub async fn unbeaten_in(nation: &Nationality, connection: &mut PgConnection) ->
 }
 
 pub async fn created_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<MiniDemonWithPlayers>> {
-    let mut stream = sqlx::query!( r#"select demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from creators inner join demons on demons.id=demon inner join players on players.id=creator where nationality=$1 order by demon"#, nation.iso_country_code).fetch(connection);
+    let mut stream = sqlx::query!( r#"select demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from creators inner join demons on demons.id=demon inner join players on players.id=creator where nationality=$1 and demons.deleted_at is null order by demon"#, nation.iso_country_code).fetch(connection);
 
     let mut creations = Vec::<MiniDemonWithPlayers>::new();
 
@@ -175,7 +241,7 @@ pub async fn created_in(nation: &Nationality, connection: &mut PgConnection) ->
 
 pub async fn verified_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<MiniDemonWithPlayers>> {
     let mut stream = sqlx::query!(
-        r#"select demons.id as demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from demons inner join players on players.id=verifier where nationality=$1"#, nation.iso_country_code).fetch(connection);
+        r#"select demons.id as demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from demons inner join players on players.id=verifier where nationality=$1 and demons.deleted_at is null"#, nation.iso_country_code).fetch(connection);
 
     let mut demons = Vec::new();
 
@@ -197,7 +263,7 @@ pub async fn verified_in(nation: &Nationality, connection: &mut PgConnection) ->
 
 pub async fn published_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<MiniDemonWithPlayers>> {
     let mut stream = sqlx::query!(
-        r#"select demons.id as demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from demons inner join players on players.id=publisher where nationality=$1"#, nation.iso_country_code).fetch(connection);
+        r#"select demons.id as demon, demons.name::text as "demon_name!", demons.position, players.name::text as "player_name!" from demons inner join players on players.id=publisher where nationality=$1 and demons.deleted_at is null"#, nation.iso_country_code).fetch(connection);
 
     let mut demons = Vec::new();
 
@@ -219,7 +285,7 @@ pub async fn published_in(nation: &Nationality, connection: &mut PgConnection) -
 
 pub async fn best_records_in(nation: &Nationality, connection: &mut PgConnection) -> Result<Vec<BestRecord>> {
     let mut stream = sqlx::query!(
-        r#"SELECT progress as "progress!", demons.id AS "demon_id!", demons.name as "demon_name!: String", demons.position as "position!", players.name as "player_name!: String" FROM best_records_in($1) as records INNER JOIN demons ON records.demon = demons.id INNER JOIN players ON players.id = records.player"#,
+        r#"SELECT progress as "progress!", demons.id AS "demon_id!", demons.name as "demon_name!: String", demons.position as "position!", players.name as "player_name!: String" FROM best_records_in($1) as records INNER JOIN demons ON records.demon = demons.id INNER JOIN players ON players.id = records.player WHERE demons.deleted_at IS NULL"#,
         nation.iso_country_code
     )
         .fetch(connection);
@@ -44,12 +44,42 @@ pub struct NationalityRecord {
 
 impl Taggable for NationalityRecord {}
 
+/// A [`Nationality`] together with aggregates over the (unbanned) players from it
+#[derive(Debug, Serialize)]
+pub struct NationalityStats {
+    #[serde(flatten)]
+    pub nation: Nationality,
+
+    /// The number of players that have this nationality set
+    pub players: i64,
+
+    /// How many of these players have a verified claim
+    pub claimed_players: i64,
+
+    /// The mean score of this nation's players, `0` if it has none
+    pub mean_score: f64,
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Hash, Deserialize)]
 pub struct Subdivision {
     pub iso_code: String,
     pub name: String,
 }
 
+impl Subdivision {
+    /// The ISO code of [`Subdivision::unknown`]. Real ISO 3166-2 codes are alphanumeric, so this never
+    /// clashes with an actual subdivision.
+    pub const UNKNOWN_ISO_CODE: &'static str = "??";
+
+    /// Placeholder for players that have a nationality set, but no subdivision
+    pub fn unknown() -> Subdivision {
+        Subdivision {
+            iso_code: Subdivision::UNKNOWN_ISO_CODE.to_string(),
+            name: "Unknown".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
 pub enum Continent {
     Asia,
@@ -0,0 +1,63 @@
+use crate::{error::Result, player::DatabasePlayer};
+use chrono::NaiveDateTime;
+use futures::StreamExt;
+use pointercrate_core::audit::NamedId;
+use serde::Serialize;
+use sqlx::PgConnection;
+
+/// A single change of a player's nationality and/or subdivision
+///
+/// Uses ISO codes for everything, with `None` meaning that no nationality (or subdivision) was
+/// set.
+#[derive(Debug, Serialize)]
+pub struct NationalityChange {
+    pub time: NaiveDateTime,
+
+    /// The user that made the change
+    pub user: NamedId,
+
+    pub old_nationality: Option<String>,
+    pub old_subdivision: Option<String>,
+    pub new_nationality: Option<String>,
+    pub new_subdivision: Option<String>,
+}
+
+impl DatabasePlayer {
+    /// All changes ever made to this player's nationality, oldest first
+    pub async fn nationality_history(&self, connection: &mut PgConnection) -> Result<Vec<NationalityChange>> {
+        let mut stream = sqlx::query!(
+            r#"SELECT time,
+                      userid,
+                      members.name AS "username?",
+                      old_nationality,
+                      old_subdivision,
+                      new_nationality,
+                      new_subdivision
+               FROM nationality_changes LEFT OUTER JOIN members ON members.member_id = userid
+               WHERE id = $1
+               ORDER BY time, audit_id"#,
+            self.id
+        )
+        .fetch(connection);
+
+        let mut changes = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            changes.push(NationalityChange {
+                time: row.time,
+                user: NamedId {
+                    id: row.userid,
+                    name: row.username,
+                },
+                old_nationality: row.old_nationality,
+                old_subdivision: row.old_subdivision,
+                new_nationality: row.new_nationality,
+                new_subdivision: row.new_subdivision,
+            });
+        }
+
+        Ok(changes)
+    }
+}
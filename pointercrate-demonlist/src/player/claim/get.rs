@@ -2,6 +2,7 @@ use crate::{
     error::{DemonlistError, Result},
     player::{claim::PlayerClaim, DatabasePlayer},
 };
+use serde::Serialize;
 use sqlx::PgConnection;
 
 pub struct ClaimBy {
@@ -10,6 +11,19 @@ pub struct ClaimBy {
     pub lock_submissions: bool,
 }
 
+/// A claim together with what its claimant needs to know to understand why it has (not) been
+/// verified yet
+#[derive(Serialize, Debug)]
+pub struct ClaimOverview {
+    #[serde(flatten)]
+    pub claim: PlayerClaim,
+    pub player_name: String,
+
+    /// Whether the claimant was geolocated to the claimed player's nationality when making the
+    /// claim. Always `false` if either is unknown.
+    pub geolocation_verified: bool,
+}
+
 impl PlayerClaim {
     pub async fn verified_claim_on(player_id: i32, connection: &mut PgConnection) -> Result<Option<PlayerClaim>> {
         match sqlx::query!(
@@ -65,4 +79,32 @@ impl PlayerClaim {
             _ => Err(DemonlistError::ClaimNotFound { member_id, player_id }),
         }
     }
+
+    /// All claims the given user has made, verified or not
+    pub async fn all_for_user(user_id: i32, connection: &mut PgConnection) -> Result<Vec<ClaimOverview>> {
+        let rows = sqlx::query!(
+            r#"SELECT player_id, verified, lock_submissions, players.name::text AS "name!", players.nationality::text,
+                      geolocated_country::text
+               FROM player_claims INNER JOIN players ON player_id = players.id
+               WHERE member_id = $1
+               ORDER BY player_id"#,
+            user_id
+        )
+        .fetch_all(connection)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ClaimOverview {
+                claim: PlayerClaim {
+                    user_id,
+                    player_id: row.player_id,
+                    verified: row.verified,
+                    lock_submissions: row.lock_submissions,
+                },
+                player_name: row.name,
+                geolocation_verified: PlayerClaim::check_geolocation(row.nationality, row.geolocated_country, true).is_ok(),
+            })
+            .collect())
+    }
 }
@@ -1,4 +1,4 @@
-pub use paginate::{ListedClaim, PlayerClaimPagination};
+pub use paginate::{ClaimStatus, ListedClaim, PlayerClaimPagination};
 pub use patch::PatchPlayerClaim;
 use serde::{Deserialize, Serialize};
 
@@ -8,7 +8,7 @@ mod paginate;
 mod patch;
 mod put;
 
-pub use get::ClaimBy;
+pub use get::{ClaimBy, ClaimOverview};
 
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 pub struct PlayerClaim {
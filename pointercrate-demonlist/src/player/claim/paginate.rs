@@ -1,3 +1,4 @@
+use crate::player::claim::PlayerClaim;
 use futures::StreamExt;
 use pointercrate_core::{
     audit::NamedId,
@@ -18,15 +19,42 @@ pub struct PlayerClaimPagination {
 
     #[serde(default, deserialize_with = "non_nullable")]
     verified: Option<bool>,
+
+    /// Alternative to `verified` for moderation queues, e.g. `status=unverified` lists all claims
+    /// awaiting a moderator decision. Ignored if `verified` is set.
+    #[serde(default, deserialize_with = "non_nullable")]
+    status: Option<ClaimStatus>,
+
+    /// Whether the users making the claims should be left out, both from the results and from what
+    /// `any_name_contains` matches against. Not settable via the query string
+    #[serde(skip)]
+    pub hide_claimants: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimStatus {
+    Verified,
+    Unverified,
 }
 
 #[derive(Serialize)]
 pub struct ListedClaim {
     #[serde(skip)]
     pub id: i32,
-    user: NamedId,
+
+    /// `None` if claimants are hidden, see [`PlayerClaimPagination::hide_claimants`]
+    user: Option<NamedId>,
     player: NamedId,
     verified: bool,
+
+    /// The country (as ISO country code) the claimant was geolocated to when making the claim. `None`
+    /// if geolocation was unavailable, or if claimants are hidden
+    geolocated_country: Option<String>,
+
+    /// Whether [`ListedClaim::geolocated_country`] matches the claimed player's nationality. Always
+    /// `false` if either is unknown
+    geolocation_verified: bool,
 }
 
 impl PaginationQuery for PlayerClaimPagination {
@@ -54,26 +82,30 @@ impl Paginatable<PlayerClaimPagination> for ListedClaim {
             .bind(query.params.before)
             .bind(query.params.after)
             .bind(query.any_name_contains.as_ref())
-            .bind(query.verified)
+            .bind(query.verified.or(query.status.map(|status| status == ClaimStatus::Verified)))
             .bind(query.params.limit + 1)
+            .bind(query.hide_claimants)
             .fetch(connection);
 
         let mut claims = Vec::new();
 
         while let Some(row) = stream.next().await {
             let row = row?;
+            let geolocated_country: Option<String> = if query.hide_claimants { None } else { row.get("geolocated_country") };
 
             claims.push(ListedClaim {
                 id: row.get("id"),
-                user: NamedId {
+                user: (!query.hide_claimants).then(|| NamedId {
                     id: row.get("mid"),
                     name: Some(row.get("mname")),
-                },
+                }),
                 player: NamedId {
                     id: row.get("pid"),
                     name: Some(row.get("pname")),
                 },
                 verified: row.get("verified"),
+                geolocation_verified: PlayerClaim::check_geolocation(row.get("pnation"), geolocated_country.clone(), true).is_ok(),
+                geolocated_country,
             })
         }
 
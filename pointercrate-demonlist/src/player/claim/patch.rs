@@ -1,4 +1,9 @@
-use crate::{error::Result, player::claim::PlayerClaim};
+use crate::{
+    config,
+    error::{DemonlistError, Result},
+    player::claim::PlayerClaim,
+};
+use log::warn;
 use serde::Deserialize;
 use sqlx::PgConnection;
 
@@ -37,6 +42,33 @@ impl PlayerClaim {
     }
 
     pub async fn set_verified(&mut self, verified: bool, connection: &mut PgConnection) -> Result<()> {
+        self.set_verified_with(verified, config::strict_claim_geolocation(), connection).await
+    }
+
+    /// Like [`PlayerClaim::set_verified`], but only refusing to verify claims whose geolocation
+    /// does not match the player's nationality if `strict_geolocation` is set
+    pub async fn set_verified_with(&mut self, verified: bool, strict_geolocation: bool, connection: &mut PgConnection) -> Result<()> {
+        if verified {
+            let banned = sqlx::query_scalar!("SELECT banned FROM players WHERE id = $1", self.player_id)
+                .fetch_one(&mut *connection)
+                .await?;
+
+            if banned {
+                return Err(DemonlistError::ClaimOnBannedPlayer);
+            }
+
+            let row = sqlx::query!(
+                "SELECT players.nationality::text, geolocated_country::text FROM player_claims INNER JOIN players \
+                 ON player_id = players.id WHERE member_id = $1 AND player_id = $2",
+                self.user_id,
+                self.player_id
+            )
+            .fetch_one(&mut *connection)
+            .await?;
+
+            PlayerClaim::check_geolocation(row.nationality, row.geolocated_country, strict_geolocation)?;
+        }
+
         sqlx::query!(
             "UPDATE player_claims SET verified = $3 WHERE member_id = $1 AND player_id = $2",
             self.user_id,
@@ -61,4 +93,38 @@ impl PlayerClaim {
 
         Ok(())
     }
+
+    /// Records the country (as ISO country code) the claimant was geolocated to when making this
+    /// claim. `None` means geolocation was unavailable or failed.
+    pub async fn set_geolocated_country(&self, country_code: Option<&str>, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!(
+            "UPDATE player_claims SET geolocated_country = $3 WHERE member_id = $1 AND player_id = $2",
+            self.user_id,
+            self.player_id,
+            country_code
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Checks whether the country a claimant was geolocated to (`actual`) matches the claimed
+    /// player's nationality (`expected`).
+    ///
+    /// In strict mode, any mismatch is an error, including when either side is unknown. Otherwise
+    /// it is only logged, and moderators are left to make the call.
+    pub fn check_geolocation(expected: Option<String>, actual: Option<String>, strict: bool) -> Result<()> {
+        if expected.is_some() && expected.as_deref().map(str::to_uppercase) == actual.as_deref().map(str::to_uppercase) {
+            return Ok(());
+        }
+
+        if strict {
+            return Err(DemonlistError::GeolocationMismatch { expected, actual });
+        }
+
+        warn!("Claimant geolocated to {:?}, but claimed player has nationality {:?}", actual, expected);
+
+        Ok(())
+    }
 }
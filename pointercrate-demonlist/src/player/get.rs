@@ -1,9 +1,10 @@
 use crate::{
+    config,
     creator::created_by,
-    demon::{published_by, verified_by},
+    demon::{current_list, published_by, verified_by, Demon, Difficulty, MinimalDemon, ScoreFormula},
     error::{DemonlistError, Result},
     nationality::{Nationality, Subdivision},
-    player::{DatabasePlayer, FullPlayer, Player},
+    player::{DatabasePlayer, FullPlayer, NationalRank, Player, PlayerComparison, RankThreshold, RoleStats, TierScore},
     record::approved_records_by,
 };
 use sqlx::{Error, PgConnection};
@@ -24,6 +25,97 @@ impl Player {
         })
     }
 
+    /// This player's rank within their subdivision, or their nation if they have no subdivision set.
+    ///
+    /// `None` if the player has no nationality, or is not ranked at all.
+    pub async fn national_rank(&self, connection: &mut PgConnection) -> Result<Option<NationalRank>> {
+        let Some(ref nationality) = self.nationality else {
+            return Ok(None);
+        };
+
+        let subdivision = nationality.subdivision.as_ref().map(|subdivision| subdivision.iso_code.as_str());
+
+        let rank = sqlx::query_scalar!(
+            r#"SELECT rank AS "rank!" FROM (
+                   SELECT id, RANK() OVER (ORDER BY score DESC) AS rank
+                   FROM players
+                   WHERE nationality = $2 AND (subdivision = $3 OR $3 IS NULL) AND score != 0 AND NOT banned
+               ) national_ranks
+               WHERE id = $1"#,
+            self.base.id,
+            nationality.iso_country_code,
+            subdivision
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        Ok(rank.map(|rank| NationalRank {
+            rank,
+            nationality: nationality.clone(),
+        }))
+    }
+
+    /// Splits this player's score up by the difficulty tiers of the demons it was awarded for
+    ///
+    /// Tiers this player has no points in are omitted. The result is ordered from the tier giving
+    /// the most points to the one giving the least.
+    pub async fn score_by_tier(&self, connection: &mut PgConnection) -> Result<Vec<TierScore>> {
+        let rows = sqlx::query!(
+            r#"SELECT demons.difficulty::text AS "difficulty!",
+                      SUM(record_score(score_giving.progress, score_giving.position, 150, score_giving.requirement)
+                          * score_giving.multiplier) AS "score!",
+                      COALESCE(SUM(record_score(score_giving.progress, score_giving.position, 150, score_giving.requirement)
+                          * score_giving.multiplier) FILTER (WHERE score_giving.position <= $2), 0) AS "main_list_score!"
+               FROM score_giving
+               INNER JOIN demons ON demons.id = score_giving.demon
+               WHERE score_giving.player = $1
+               GROUP BY demons.difficulty
+               ORDER BY 2 DESC"#,
+            self.base.id,
+            config::list_size()
+        )
+        .fetch_all(connection)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter(|row| row.score > 0.0)
+            .map(|row| TierScore {
+                difficulty: Difficulty::from_sql(&row.difficulty),
+                score: row.score,
+                main_list_score: row.main_list_score,
+            })
+            .collect())
+    }
+
+    /// Compares the approved 100% records of this player with those of `other`
+    pub async fn compare(self, other: Player, connection: &mut PgConnection) -> Result<PlayerComparison> {
+        let mut beaten_by_a = completed_demons(&self.base, connection).await?;
+        let beaten_by_b = completed_demons(&other.base, connection).await?;
+
+        let (mut b_only, mut both) = (Vec::new(), Vec::new());
+
+        for demon in beaten_by_b {
+            match beaten_by_a.iter().position(|beaten| beaten.id == demon.id) {
+                Some(index) => both.push(beaten_by_a.remove(index)),
+                None => b_only.push(demon),
+            }
+        }
+
+        for demons in [&mut beaten_by_a, &mut b_only, &mut both] {
+            demons.sort_by_key(|demon| demon.position);
+        }
+
+        Ok(PlayerComparison {
+            score_gap: self.score - other.score,
+            a: self,
+            b: other,
+            a_only: beaten_by_a,
+            b_only,
+            both,
+        })
+    }
+
     pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<Player> {
         let result = sqlx::query!(
             r#"SELECT players.id, players.name, banned, players.score, nationalities.nation::text, iso_country_code::text, iso_code::text as subdivision_code, subdivisions.name::text as subdivision_name, player_ranks.rank FROM players LEFT OUTER JOIN nationalities ON 
@@ -69,19 +161,51 @@ impl Player {
 }
 
 impl DatabasePlayer {
+    /// Resolves the given name to a player, ignoring surrounding whitespace and case
+    ///
+    /// If [`config::normalize_player_names`] is set, names are additionally compared after unicode
+    /// normalization and folding of common homoglyphs, see [`DatabasePlayer::by_name_normalized`].
+    ///
+    /// Fails with [`DemonlistError::AmbiguousPlayerName`] if more than one player matches.
     pub async fn by_name(name: &str, connection: &mut PgConnection) -> Result<DatabasePlayer> {
+        Self::resolve_name(name, config::normalize_player_names(), connection).await
+    }
+
+    /// Resolves the given name to a player, treating names as equal if they are identical after NFKC
+    /// normalization, case folding and replacing cyrillic and greek look-alikes (of both cases) with
+    /// their latin counterparts. The normalization is done by the `normalized_player_name` SQL
+    /// function, which the players table has an index on.
+    ///
+    /// This means that for example "Pl\u{0430}sma" (with a cyrillic "а") resolves to the same player
+    /// as "Plasma". If existing players already collide under this normalization,
+    /// [`DemonlistError::AmbiguousPlayerName`] is returned.
+    pub async fn by_name_normalized(name: &str, connection: &mut PgConnection) -> Result<DatabasePlayer> {
+        Self::resolve_name(name, true, connection).await
+    }
+
+    async fn resolve_name(name: &str, normalize: bool, connection: &mut PgConnection) -> Result<DatabasePlayer> {
         let name = name.trim();
 
-        let result = sqlx::query_as!(DatabasePlayer, "SELECT id, name, banned FROM players WHERE name = $1::CITEXT", name)
-            .fetch_one(connection)
-            .await;
+        let mut players = if normalize {
+            sqlx::query_as!(
+                DatabasePlayer,
+                "SELECT id, name, banned FROM players WHERE normalized_player_name(name::TEXT) = normalized_player_name($1)",
+                name
+            )
+            .fetch_all(connection)
+            .await?
+        } else {
+            sqlx::query_as!(DatabasePlayer, "SELECT id, name, banned FROM players WHERE name = $1::CITEXT", name)
+                .fetch_all(connection)
+                .await?
+        };
 
-        match result {
-            Ok(player) => Ok(player),
-            Err(Error::RowNotFound) => Err(DemonlistError::PlayerNotFoundName {
+        match players.len() {
+            0 => Err(DemonlistError::PlayerNotFoundName {
                 player_name: name.to_string(),
             }),
-            Err(err) => Err(err.into()),
+            1 => Ok(players.remove(0)),
+            _ => Err(DemonlistError::AmbiguousPlayerName { players }),
         }
     }
 
@@ -114,6 +238,115 @@ impl DatabasePlayer {
             result => result,
         }
     }
+
+    /// The points each demon contributes to this player's score, biggest contribution first
+    ///
+    /// Computed the same way as the stored score, so the contributions always add up to it.
+    /// Records that give no points (e.g. below the requirement) are left out.
+    pub async fn score_breakdown(&self, connection: &mut PgConnection) -> Result<Vec<(MinimalDemon, f64)>> {
+        let rows = sqlx::query!(
+            r#"SELECT demons.id, demons.name::text AS "name!", demons.position,
+                      COALESCE(record_score(score_giving.progress, score_giving.position, 150, score_giving.requirement)
+                          * score_giving.multiplier, 0) AS "score!"
+               FROM score_giving
+               INNER JOIN demons ON demons.id = score_giving.demon
+               WHERE score_giving.player = $1
+               ORDER BY 4 DESC, demons.position"#,
+            self.id
+        )
+        .fetch_all(connection)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter(|row| row.score > 0.0)
+            .map(|row| {
+                (
+                    MinimalDemon {
+                        id: row.id,
+                        name: row.name,
+                        position: row.position,
+                    },
+                    row.score,
+                )
+            })
+            .collect())
+    }
+
+    /// A measure of how consistently this player makes progress across many demons, independent of
+    /// their stored score
+    ///
+    /// Each approved record on a demon currently on the list contributes the fraction of the way
+    /// from the demon's requirement to 100% it covers, times the points the given formula gives for
+    /// completing the demon.
+    pub async fn consistency_score(&self, formula: &dyn ScoreFormula, connection: &mut PgConnection) -> Result<f64> {
+        let demons = current_list(&mut *connection).await?;
+
+        Ok(approved_records_by(self, connection)
+            .await?
+            .iter()
+            .filter_map(|record| {
+                demons
+                    .iter()
+                    .find(|demon| demon.base.id == record.demon.id)
+                    .map(|demon| consistency_contribution(formula, demon, record.progress))
+            })
+            .sum())
+    }
+
+    /// The demons published by this player, and how many there are
+    pub async fn publisher_stats(&self, connection: &mut PgConnection) -> Result<RoleStats> {
+        Ok(published_by(self, connection).await?.into())
+    }
+
+    /// The demons verified by this player, and how many there are
+    pub async fn verifier_stats(&self, connection: &mut PgConnection) -> Result<RoleStats> {
+        Ok(verified_by(self, connection).await?.into())
+    }
+}
+
+/// The part of [`DatabasePlayer::consistency_score`] a record with the given progress on the given demon is worth
+fn consistency_contribution(formula: &dyn ScoreFormula, demon: &Demon, progress: i16) -> f64 {
+    // A requirement of 100% leaves no room for partial progress
+    let fraction = if demon.requirement >= 100 {
+        if progress >= 100 {
+            1.0
+        } else {
+            0.0
+        }
+    } else {
+        ((progress - demon.requirement) as f64 / (100 - demon.requirement) as f64).clamp(0.0, 1.0)
+    };
+
+    fraction * demon.score(formula, 100)
+}
+
+async fn completed_demons(player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Vec<MinimalDemon>> {
+    Ok(approved_records_by(player, connection)
+        .await?
+        .into_iter()
+        .filter(|record| record.progress == 100)
+        .map(|record| record.demon)
+        .collect())
+}
+
+/// Computes the scores needed to reach each of the given ranks on the current ranking
+///
+/// Players with equal scores share a rank, so the threshold for rank `n` is the score of the `n`-th
+/// player in ranking order.
+pub async fn ranking_thresholds(ranks: &[i64], connection: &mut PgConnection) -> Result<Vec<RankThreshold>> {
+    let mut thresholds = Vec::new();
+
+    for &rank in ranks {
+        let score = sqlx::query!(r#"SELECT score AS "score!" FROM ranked_players WHERE index = $1"#, rank)
+            .fetch_optional(&mut *connection)
+            .await?
+            .map(|row| row.score);
+
+        thresholds.push(RankThreshold { rank, score });
+    }
+
+    Ok(thresholds)
 }
 
 #[cfg(test)]
@@ -166,4 +399,31 @@ mod tests {
             Ok(&player)
         );
     }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_by_name_normalized(mut conn: PoolConnection<Postgres>) {
+        let player = DatabasePlayer::by_name_or_create("Plasma", &mut conn).await.unwrap();
+
+        // Cyrillic "а" and full width "Ｐ"
+        assert_eq!(
+            DatabasePlayer::by_name_normalized("\u{FF30}l\u{0430}sma", &mut conn).await.as_ref(),
+            Ok(&player)
+        );
+
+        // Capital look-alikes are folded no matter the database's locale, here cyrillic "Р" and "А"
+        assert_eq!(
+            DatabasePlayer::by_name_normalized("\u{0420}L\u{0410}SMA", &mut conn).await.as_ref(),
+            Ok(&player)
+        );
+
+        // Without normalization, these are different players
+        let impostor = DatabasePlayer::by_name_or_create("Pl\u{0430}sma", &mut conn).await.unwrap();
+        assert_ne!(impostor.id, player.id);
+
+        // ... which makes the normalized lookup ambiguous
+        assert!(matches!(
+            DatabasePlayer::by_name_normalized("plasma", &mut conn).await,
+            Err(DemonlistError::AmbiguousPlayerName { .. })
+        ));
+    }
 }
@@ -1,8 +1,14 @@
 pub use self::{
+    audit::NationalityChange,
+    get::ranking_thresholds,
     paginate::{PlayerPagination, RankedPlayer, RankingPagination},
     patch::PatchPlayer,
 };
-use crate::{demon::MinimalDemon, nationality::Nationality, record::MinimalRecordD};
+use crate::{
+    demon::{Difficulty, MinimalDemon},
+    nationality::Nationality,
+    record::{enjoyment_visible, MinimalRecordD},
+};
 use derive_more::Display;
 use pointercrate_core::{error::CoreError, etag::Taggable};
 use serde::{Deserialize, Serialize};
@@ -12,11 +18,103 @@ use std::{
     hash::{Hash, Hasher},
 };
 
+mod audit;
 pub mod claim;
 mod get;
 mod paginate;
 mod patch;
 
+/// Head-to-head comparison of two players based on the demons they completed
+#[derive(Debug, Serialize)]
+pub struct PlayerComparison {
+    pub a: Player,
+    pub b: Player,
+
+    /// `a`'s score minus `b`'s score
+    pub score_gap: f64,
+
+    /// Demons completed only by `a`, in list order
+    pub a_only: Vec<MinimalDemon>,
+
+    /// Demons completed only by `b`, in list order
+    pub b_only: Vec<MinimalDemon>,
+
+    /// Demons completed by both players, in list order
+    pub both: Vec<MinimalDemon>,
+}
+
+/// What banning a player would do, without actually banning them. See [`DatabasePlayer::ban`]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct BanPreview {
+    /// The number of pending (submitted or under consideration) records that would be deleted
+    pub deleted_submissions: i64,
+
+    /// The number of approved records that would be rejected
+    pub rejected_records: i64,
+
+    /// The score the player currently has, all of which they would lose
+    pub score: f64,
+
+    /// Demons verified by the player. These stay on the list, but should probably be looked at
+    pub verified: Vec<MinimalDemon>,
+
+    /// Demons published by the player
+    pub published: Vec<MinimalDemon>,
+}
+
+/// The demons a player has some role on (such as having published or verified them), for profile
+/// summaries
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RoleStats {
+    /// The number of demons in [`RoleStats::demons`]
+    pub count: usize,
+
+    /// The demons themselves, in list order
+    pub demons: Vec<MinimalDemon>,
+}
+
+impl From<Vec<MinimalDemon>> for RoleStats {
+    fn from(mut demons: Vec<MinimalDemon>) -> Self {
+        demons.sort_by_key(|demon| demon.position);
+
+        RoleStats {
+            count: demons.len(),
+            demons,
+        }
+    }
+}
+
+/// The score needed to reach some position on the stats viewer
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RankThreshold {
+    pub rank: i64,
+
+    /// The score of the player currently at this position of the ranking. `None` if fewer players
+    /// are ranked.
+    pub score: Option<f64>,
+}
+
+/// A player's rank among the players of their nation, or of their subdivision if they have one
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct NationalRank {
+    pub rank: i64,
+
+    /// The nationality the rank applies to
+    pub nationality: Nationality,
+}
+
+/// The part of a player's score coming from demons of a single difficulty tier
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct TierScore {
+    pub difficulty: Difficulty,
+
+    /// The points awarded for demons of this tier
+    pub score: f64,
+
+    /// The part of [`TierScore::score`] awarded for demons on the main list
+    pub main_list_score: f64,
+}
+
 #[derive(Debug, Hash, Eq, PartialEq, Serialize, Display, Clone, Deserialize)]
 #[display("{} (ID: {})", name, id)]
 pub struct DatabasePlayer {
@@ -81,6 +179,18 @@ impl Taggable for FullPlayer {
     }
 }
 
+impl FullPlayer {
+    /// Removes the enjoyment ratings from this player's records, unless they are
+    /// [publicly visible](enjoyment_visible)
+    pub fn hide_private_enjoyment(&mut self) {
+        if !enjoyment_visible(false) {
+            for record in &mut self.records {
+                record.enjoyment = None;
+            }
+        }
+    }
+}
+
 impl DatabasePlayer {
     /// Recomputes this player's score and updates it in the database.
     pub async fn update_score(&self, connection: &mut PgConnection) -> Result<f64, CoreError> {
@@ -102,6 +212,48 @@ impl DatabasePlayer {
     }
 }
 
+/// Like [`DatabasePlayer::update_score`], but updates the scores of all the given players at once
+pub async fn update_scores(player_ids: &[i32], connection: &mut PgConnection) -> Result<(), CoreError> {
+    sqlx::query!("UPDATE players SET score = coalesce(score_of_player(id), 0) WHERE id = ANY($1)", player_ids)
+        .execute(&mut *connection)
+        .await?;
+    sqlx::query!(
+        "UPDATE nationalities SET score = coalesce(score_of_nation(iso_country_code), 0) WHERE iso_country_code IN (SELECT nationality \
+         FROM players WHERE id = ANY($1))",
+        player_ids
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE subdivisions SET score = coalesce(score_of_subdivision(nation, iso_code), 0) WHERE (nation, iso_code) IN (SELECT \
+         nationality, subdivision FROM players WHERE id = ANY($1))",
+        player_ids
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!("REFRESH MATERIALIZED VIEW CONCURRENTLY player_ranks;")
+        .execute(&mut *connection)
+        .await?;
+
+    Ok(())
+}
+
+/// Recomputes all scores if any demon's grace period (see [`crate::config::new_demon_grace_hours`]) ran out
+/// since the last call, so that it starts awarding points. Returns whether any did.
+///
+/// Scores are cached, so this needs to be called periodically for them to reflect ended grace periods.
+pub async fn end_grace_periods(connection: &mut PgConnection) -> Result<bool, CoreError> {
+    let ended = sqlx::query!("UPDATE demons SET scores_from = NULL WHERE scores_from <= (NOW() AT TIME ZONE 'utc') RETURNING id")
+        .fetch_all(&mut *connection)
+        .await?;
+
+    if !ended.is_empty() {
+        recompute_scores(connection).await?;
+    }
+
+    Ok(!ended.is_empty())
+}
+
 pub async fn recompute_scores(connection: &mut PgConnection) -> Result<(), CoreError> {
     sqlx::query!("SELECT recompute_player_scores();").execute(&mut *connection).await?;
     sqlx::query!("SELECT recompute_nation_scores();").execute(&mut *connection).await?;
@@ -1,4 +1,5 @@
 use crate::{
+    config,
     nationality::{Continent, Nationality, Subdivision},
     player::{DatabasePlayer, Player},
 };
@@ -64,6 +65,7 @@ impl Paginatable<PlayerPagination> for Player {
             .bind(query.nation == Some(None))
             .bind(&query.subdivision)
             .bind(query.params.limit + 1)
+            .bind(config::strip_emoji_in_search())
             .fetch(connection);
 
         let mut players = Vec::new();
@@ -175,6 +177,7 @@ impl Paginatable<RankingPagination> for RankedPlayer {
             .bind(query.continent.as_ref().map(|c| c.to_sql()))
             .bind(&query.subdivision)
             .bind(query.params.limit + 1)
+            .bind(config::strip_emoji_in_search())
             .fetch(connection);
 
         let mut players = Vec::new();
@@ -1,7 +1,9 @@
 use crate::{
+    config,
+    demon::{published_by, verified_by},
     error::{DemonlistError, Result},
     nationality::Nationality,
-    player::{claim::PlayerClaim, DatabasePlayer, FullPlayer, Player},
+    player::{claim::PlayerClaim, BanPreview, DatabasePlayer, FullPlayer, Player},
     record::{approved_records_by, FullRecord},
 };
 use log::info;
@@ -251,7 +253,55 @@ impl Player {
     }
 }
 
+impl Player {
+    /// Computes what [`DatabasePlayer::ban`] would do to this player, without changing anything
+    pub async fn preview_ban(&self, connection: &mut PgConnection) -> Result<BanPreview> {
+        let counts = sqlx::query!(
+            r#"SELECT COUNT(*) FILTER (WHERE status_ = 'SUBMITTED' OR status_ = 'UNDER_CONSIDERATION') AS "deleted_submissions!",
+                      COUNT(*) FILTER (WHERE status_ = 'APPROVED') AS "rejected_records!"
+               FROM records WHERE player = $1"#,
+            self.base.id
+        )
+        .fetch_one(&mut *connection)
+        .await?;
+
+        Ok(BanPreview {
+            deleted_submissions: counts.deleted_submissions,
+            rejected_records: counts.rejected_records,
+            score: self.score,
+            verified: verified_by(&self.base, &mut *connection).await?,
+            published: published_by(&self.base, connection).await?,
+        })
+    }
+}
+
 impl DatabasePlayer {
+    /// Merges the player with id `loser` into the player with id `winner`, deleting `loser`
+    ///
+    /// All records, creator entries, verifier/publisher entries and claims of `loser` are transferred
+    /// to `winner`. If both players have a record on the same demon, the one with higher progress
+    /// is kept. Afterwards, the score of `winner` is recomputed.
+    ///
+    /// Must run inside a transaction!
+    pub async fn merge_into(loser: i32, winner: i32, connection: &mut PgConnection) -> Result<FullPlayer> {
+        if loser == winner {
+            return Err(DemonlistError::SelfMerge { player_id: winner });
+        }
+
+        let loser = DatabasePlayer::by_id(loser, &mut *connection).await?;
+        let mut winner = Player::by_id(winner, &mut *connection).await?.upgrade(&mut *connection).await?;
+
+        winner.merge(loser, &mut *connection).await?;
+        winner.player.base.update_score(&mut *connection).await?;
+
+        // The merge moved creator, verifier and publisher entries, and the score changed the rank, so
+        // load everything afresh instead of patching up `winner`
+        Player::by_id(winner.player.base.id, &mut *connection)
+            .await?
+            .upgrade(connection)
+            .await
+    }
+
     pub async fn unban(&mut self, connection: &mut PgConnection) -> Result<()> {
         sqlx::query!("UPDATE players SET banned = false WHERE id=$1", self.id)
             .execute(connection)
@@ -278,12 +328,25 @@ impl DatabasePlayer {
         // 'rejected' since rejected records are globally unique!
 
         // Now, reject all previously accepted records
-        let updated = sqlx::query!("UPDATE records SET status_ = 'REJECTED' WHERE player = $1", self.id)
+        let updated = sqlx::query!("UPDATE records SET status_ = 'REJECTED', failed_requirement = NULL WHERE player = $1", self.id)
             .execute(&mut *connection)
             .await?;
 
         info!("Rejected {} records while banning {}", updated.rows_affected(), self);
 
+        if config::revoke_claims_on_ban() {
+            let revoked = sqlx::query_scalar!(
+                "UPDATE player_claims SET verified = false WHERE player_id = $1 AND verified RETURNING member_id",
+                self.id
+            )
+            .fetch_all(&mut *connection)
+            .await?;
+
+            for member_id in revoked {
+                info!("Revoked verified claim of user {} while banning {}", member_id, self);
+            }
+        }
+
         // Actually ban the player
         sqlx::query!("UPDATE players SET banned = true WHERE id = $1", self.id)
             .execute(connection)
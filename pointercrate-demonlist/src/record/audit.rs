@@ -13,6 +13,7 @@ pub struct RecordModificationData {
     player: Option<NamedId>,
     demon: Option<NamedId>,
     enjoyment: Option<i16>,
+    progress_verified: Option<bool>,
 }
 
 /// Gets all audit log entries for the given record, in chronological order
@@ -51,6 +52,7 @@ pub async fn audit_log_for_record(record_id: i32, connection: &mut PgConnection)
                   userid,
                   progress,
                   enjoyment,
+                  progress_verified,
                   record_modifications.video,
                   status_::TEXT,
                   players.name::TEXT AS player_name,
@@ -77,6 +79,7 @@ pub async fn audit_log_for_record(record_id: i32, connection: &mut PgConnection)
                 r#type: AuditLogEntryType::Modification(RecordModificationData {
                     progress: modification.progress,
                     enjoyment: modification.enjoyment,
+                    progress_verified: modification.progress_verified,
                     status: modification.status_.as_deref().map(RecordStatus::from_sql),
                     player: match modification.player_id {
                         Some(id) => Some(NamedId {
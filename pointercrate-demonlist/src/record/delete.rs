@@ -1,4 +1,4 @@
-use crate::{error::Result, record::FullRecord};
+use crate::{config, error::Result, record::FullRecord};
 use log::info;
 use sqlx::PgConnection;
 
@@ -25,3 +25,36 @@ impl FullRecord {
         Ok(())
     }
 }
+
+/// Deletes all rejected records that were last submitted or modified more than
+/// [`config::rejected_record_retention_days`] days ago, returning how many were deleted
+///
+/// The deletions go through the usual audit log triggers, so a copy of each purged record is kept
+/// in the record audit log. Rejected records without any audit log entries (e.g. because they
+/// predate the audit log) are kept, as their age is unknown. Does nothing if no retention period
+/// is configured.
+pub async fn purge_rejected_records(connection: &mut PgConnection) -> Result<u64> {
+    purge_rejected_records_older_than(config::rejected_record_retention_days(), connection).await
+}
+
+/// Like [`purge_rejected_records`], but with a retention period of `retention_days` days instead of
+/// the configured one
+pub async fn purge_rejected_records_older_than(retention_days: i32, connection: &mut PgConnection) -> Result<u64> {
+    if retention_days <= 0 {
+        return Ok(0);
+    }
+
+    let purged = sqlx::query!(
+        "DELETE FROM records WHERE status_ = 'REJECTED' AND GREATEST((SELECT MAX(time) FROM record_additions WHERE record_additions.id = \
+         records.id), (SELECT MAX(time) FROM record_modifications WHERE record_modifications.id = records.id)) < (NOW() AT TIME ZONE \
+         'utc') - make_interval(days => $1)",
+        retention_days
+    )
+    .execute(connection)
+    .await?
+    .rows_affected();
+
+    info!("Purged {} rejected records older than {} days", purged, retention_days);
+
+    Ok(purged)
+}
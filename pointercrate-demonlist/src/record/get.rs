@@ -1,16 +1,20 @@
 use crate::{
+    config,
     demon::MinimalDemon,
     error::{DemonlistError, Result},
     nationality::Nationality,
     player::DatabasePlayer,
-    record::{FullRecord, MinimalRecordD, MinimalRecordP, RecordStatus},
+    record::{enjoyment_visible, FullRecord, MinimalRecordD, MinimalRecordP, RecentRecord, RecentRecordPagination, RecordStatus},
     submitter::Submitter,
 };
-use futures::stream::StreamExt;
-use sqlx::{Error, PgConnection};
+use chrono::NaiveDateTime;
+use futures::stream::{Stream, StreamExt};
+use serde::Deserialize;
+use sqlx::{Error, PgConnection, Row};
 
 // Required until https://github.com/launchbadge/sqlx/pull/108 is merged
 struct FetchedRecord {
+    id: i32,
     progress: i16,
     video: Option<String>,
     raw_footage: Option<String>,
@@ -24,6 +28,37 @@ struct FetchedRecord {
     submitter_id: i32,
     submitter_banned: bool,
     enjoyment: Option<i16>,
+    approved_by: Option<i32>,
+    progress_verified: bool,
+}
+
+impl From<FetchedRecord> for FullRecord {
+    fn from(row: FetchedRecord) -> Self {
+        FullRecord {
+            id: row.id,
+            progress: row.progress,
+            video: row.video,
+            raw_footage: row.raw_footage,
+            status: RecordStatus::from_sql(&row.status),
+            enjoyment: row.enjoyment,
+            approved_by: row.approved_by,
+            progress_verified: row.progress_verified,
+            player: DatabasePlayer {
+                id: row.player_id,
+                name: row.player_name,
+                banned: row.player_banned,
+            },
+            demon: MinimalDemon {
+                id: row.demon_id,
+                position: row.position,
+                name: row.demon_name,
+            },
+            submitter: Some(Submitter {
+                id: row.submitter_id,
+                banned: row.submitter_banned,
+            }),
+        }
+    }
 }
 
 impl FullRecord {
@@ -33,40 +68,28 @@ impl FullRecord {
             .await;
 
         match result {
-            Ok(row) => Ok(FullRecord {
-                id,
-                progress: row.progress,
-                video: row.video,
-                raw_footage: row.raw_footage,
-                status: RecordStatus::from_sql(&row.status),
-                enjoyment: row.enjoyment,
-                player: DatabasePlayer {
-                    id: row.player_id,
-                    name: row.player_name,
-                    banned: row.player_banned,
-                },
-                demon: MinimalDemon {
-                    id: row.demon_id,
-                    position: row.position,
-                    name: row.demon_name,
-                },
-                submitter: Some(Submitter {
-                    id: row.submitter_id,
-                    banned: row.submitter_banned,
-                }),
-            }),
-
+            Ok(row) => Ok(row.into()),
             Err(Error::RowNotFound) => Err(DemonlistError::RecordNotFound { record_id: id }),
             Err(err) => Err(err.into()),
         }
     }
 }
 
+/// Every record in the database, in order of their ids, yielded one by one as they are read
+///
+/// Meant for full backups, so videos are included even for link banned players.
+pub fn stream_all_records(connection: &mut PgConnection) -> impl Stream<Item = Result<FullRecord>> + '_ {
+    sqlx::query_file_as!(FetchedRecord, "sql/all_records.sql")
+        .fetch(connection)
+        .map(|row| row.map(FullRecord::from).map_err(DemonlistError::from))
+}
+
 pub async fn approved_records_by(player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Vec<MinimalRecordD>> {
     let mut stream = sqlx::query!(
         r#"SELECT records.id, progress, enjoyment, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END, demons.id AS demon_id, 
          demons.name, demons.position FROM records INNER JOIN demons ON records.demon = demons.id INNER JOIN players ON players.id 
-         = $1 WHERE status_ = 'APPROVED' AND records.player = $1"#,
+         = $1 WHERE status_ = 'APPROVED' AND records.player = $1 AND demons.deleted_at IS NULL
+         AND records.progress >= demons.requirement"#,
         player.id
     )
     .fetch(connection);
@@ -93,10 +116,44 @@ pub async fn approved_records_by(player: &DatabasePlayer, connection: &mut PgCon
     Ok(records)
 }
 
-pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut PgConnection) -> Result<Vec<MinimalRecordP>> {
+/// The order in which the approved records on a demon are listed
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordOrder {
+    /// Highest progress first. Records with equal progress are ordered by when they were approved,
+    /// earliest first unless [`config::latest_approval_first`] is set. Records without an approval
+    /// date come after those with one, and remaining ties are broken by record id
+    #[default]
+    Progress,
+
+    /// Highest enjoyment rating first, records without a rating last. Ties are broken as for
+    /// [`RecordOrder::Progress`]
+    Enjoyment,
+
+    /// Earliest approved first
+    Date,
+}
+
+impl RecordOrder {
+    /// The order to actually use when listing records for someone who may or may not see enjoyment
+    /// ratings. Ordering by enjoyment would reveal the hidden ratings, so it falls back to the default
+    /// order in that case.
+    pub fn respecting_enjoyment_visibility(self, enjoyment_visible: bool) -> RecordOrder {
+        match self {
+            RecordOrder::Enjoyment if !enjoyment_visible => RecordOrder::default(),
+            order => order,
+        }
+    }
+}
+
+/// The approved records on the given demon in the given order. The records are meant for public display, so
+/// [`RecordOrder::Enjoyment`] is ignored unless enjoyment ratings are [publicly visible](enjoyment_visible).
+pub async fn approved_records_on(demon: &MinimalDemon, order: RecordOrder, connection: &mut PgConnection) -> Result<Vec<MinimalRecordP>> {
     struct Fetched {
         id: i32,
         progress: i16,
+        enjoyment: Option<i16>,
+        approved_at: Option<NaiveDateTime>,
         video: Option<String>,
         player_id: i32,
         name: String,
@@ -105,20 +162,28 @@ pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut PgConnec
         iso_country_code: Option<String>,
     }
 
-    let mut stream = sqlx::query_as!(
+    let mut fetched = sqlx::query_as!(
         Fetched,
-        r#"SELECT records.id, progress, CASE WHEN players.link_banned THEN NULL ELSE video::text END, players.id AS player_id, 
+        r#"SELECT records.id, progress, enjoyment, approved_at, CASE WHEN players.link_banned THEN NULL ELSE video::text END, players.id AS player_id, 
          players.name, players.banned, nation::TEXT, iso_country_code::TEXT FROM records INNER JOIN players ON records.player = players.id LEFT OUTER JOIN nationalities ON nationality = iso_country_code WHERE status_ = 'APPROVED' AND 
-         records.demon = $1 ORDER BY progress DESC, id ASC"#,
-        demon.id
+         records.demon = $1 AND records.progress >= (SELECT requirement FROM demons WHERE id = $1)
+         ORDER BY progress DESC, CASE WHEN $2 THEN approved_at END DESC NULLS LAST, approved_at ASC NULLS LAST, id ASC"#,
+        demon.id,
+        config::latest_approval_first()
     )
-    .fetch(connection);
+    .fetch_all(connection)
+    .await?;
 
-    let mut records = Vec::new();
+    // The sorts are stable, so ties retain the default order from above
+    match order.respecting_enjoyment_visibility(enjoyment_visible(false)) {
+        RecordOrder::Progress => (),
+        RecordOrder::Enjoyment => fetched.sort_by_key(|row| std::cmp::Reverse(row.enjoyment)),
+        RecordOrder::Date => fetched.sort_by_key(|row| (row.approved_at.is_none(), row.approved_at)),
+    }
 
-    while let Some(row) = stream.next().await {
-        let row = row?;
+    let mut records = Vec::new();
 
+    for row in fetched {
         records.push(MinimalRecordP {
             id: row.id,
             progress: row.progress,
@@ -143,6 +208,56 @@ pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut PgConnec
     Ok(records)
 }
 
+/// The approved records, most recently approved first, starting after the cursor given by `query`
+pub async fn recent_records(query: &RecentRecordPagination, connection: &mut PgConnection) -> Result<Vec<RecentRecord>> {
+    query.validate()?;
+
+    let mut stream = sqlx::query(include_str!("../../sql/recent_records.sql"))
+        .bind(query.after_approved_at)
+        .bind(query.after_id)
+        .bind(query.limit as i64)
+        .fetch(connection);
+
+    let mut records = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        records.push(RecentRecord {
+            record: MinimalRecordP {
+                id: row.try_get("id")?,
+                progress: row.try_get("progress")?,
+                video: row.try_get("video")?,
+                status: RecordStatus::Approved,
+                player: DatabasePlayer {
+                    id: row.try_get("player_id")?,
+                    name: row.try_get("player_name")?,
+                    banned: row.try_get("player_banned")?,
+                },
+                nationality: match (
+                    row.try_get::<Option<String>, _>("nation")?,
+                    row.try_get::<Option<String>, _>("iso_country_code")?,
+                ) {
+                    (Some(nation), Some(iso_country_code)) => Some(Nationality {
+                        iso_country_code,
+                        nation,
+                        subdivision: None,
+                    }),
+                    _ => None,
+                },
+            },
+            demon: MinimalDemon {
+                id: row.try_get("demon_id")?,
+                position: row.try_get("position")?,
+                name: row.try_get("demon_name")?,
+            },
+            approved_at: row.try_get("approved_at")?,
+        })
+    }
+
+    Ok(records)
+}
+
 pub async fn submission_count(connection: &mut PgConnection) -> Result<i64> {
     Ok(sqlx::query!("SELECT COUNT(*) FROM records WHERE status_='SUBMITTED'")
         .fetch_one(connection)
@@ -155,10 +270,20 @@ pub async fn submission_count(connection: &mut PgConnection) -> Result<i64> {
 mod test {
     use sqlx::{pool::PoolConnection, Postgres};
 
-    use crate::record::get::submission_count;
+    use crate::record::get::{submission_count, RecordOrder};
 
     #[sqlx::test(migrations = "../migrations")]
     fn test_submission_count(mut conn: PoolConnection<Postgres>) {
         assert_eq!(submission_count(&mut conn).await.unwrap(), 0);
     }
+
+    #[test]
+    fn test_enjoyment_order_requires_visible_enjoyment() {
+        assert_eq!(RecordOrder::Enjoyment.respecting_enjoyment_visibility(true), RecordOrder::Enjoyment);
+        assert_eq!(RecordOrder::Enjoyment.respecting_enjoyment_visibility(false), RecordOrder::Progress);
+
+        for order in [RecordOrder::Progress, RecordOrder::Date] {
+            assert_eq!(order.respecting_enjoyment_visibility(false), order);
+        }
+    }
 }
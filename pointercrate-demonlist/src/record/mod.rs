@@ -20,14 +20,19 @@
 //! * 'under consideration' means essentially the same as 'submitted', only that all further
 //!   submissions for this (demon, player) tuple are disallowed. Note that this does not mean that
 //!   the 'under consideration' status makes. A record under consideration IS NOT UNIQUE!
+//!
+//! Records can move freely between 'submitted', 'approved' and 'rejected'. A record can only be put
+//! 'under consideration' while it is waiting in the queue (that is, while it is 'submitted'), and
+//! from there it either goes back to 'submitted', or gets 'approved' or 'rejected'.
 
 pub use self::{
-    get::{approved_records_by, approved_records_on, submission_count},
-    paginate::RecordPagination,
-    patch::PatchRecord,
-    post::Submission,
+    delete::{purge_rejected_records, purge_rejected_records_older_than},
+    get::{approved_records_by, approved_records_on, recent_records, stream_all_records, submission_count, RecordOrder},
+    paginate::{PendingRecord, PendingRecordPagination, RecentRecord, RecentRecordPagination, RecordPagination},
+    patch::{import_enjoyment, EnjoymentImportError, EnjoymentImportResult, EnjoymentImportRow, PatchRecord},
+    post::{Submission, SubmissionRequirements},
 };
-use crate::{demon::MinimalDemon, error::Result, nationality::Nationality, player::DatabasePlayer, submitter::Submitter};
+use crate::{config, demon::MinimalDemon, error::Result, nationality::Nationality, player::DatabasePlayer, submitter::Submitter};
 use derive_more::Display;
 use pointercrate_core::etag::Taggable;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -46,6 +51,16 @@ mod paginate;
 mod patch;
 mod post;
 
+/// Whether the enjoyment ratings of individual records may be shown to someone, or be used to order
+/// the records shown to them. List staff can always see them, everyone else only if
+/// [`config::public_enjoyment`] is set.
+///
+/// This is the only place deciding on the visibility of enjoyment ratings, everything that hides
+/// them goes through here.
+pub fn enjoyment_visible(is_staff: bool) -> bool {
+    is_staff || config::public_enjoyment()
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash, Default)]
 pub enum RecordStatus {
     #[default]
@@ -66,6 +81,12 @@ impl RecordStatus {
         .to_owned()
     }
 
+    /// Whether a record with this status may be moved to the given status, see the module
+    /// documentation
+    pub fn can_transition_to(self, status: RecordStatus) -> bool {
+        self == status || status != RecordStatus::UnderConsideration || self == RecordStatus::Submitted
+    }
+
     fn from_sql(sql: &str) -> Self {
         match sql {
             "SUBMITTED" => RecordStatus::Submitted,
@@ -117,7 +138,7 @@ impl<'de> Deserialize<'de> for RecordStatus {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Display, Hash)]
+#[derive(Debug, Deserialize, Serialize, Display, Hash, Clone)]
 #[display("{} {}% on {} (ID: {})", player, progress, demon, id)]
 pub struct FullRecord {
     pub id: i32,
@@ -129,6 +150,14 @@ pub struct FullRecord {
     pub submitter: Option<Submitter>,
     pub raw_footage: Option<String>,
     pub enjoyment: Option<i16>,
+
+    /// The id of the pointercrate user that approved this record, if it is approved
+    pub approved_by: Option<i32>,
+
+    /// Whether a moderator confirmed that the claimed progress matches what is shown in the video.
+    /// Records can be approved without this being set (e.g. if they were accepted on trust). Reset whenever the
+    /// progress or video of the record changes.
+    pub progress_verified: bool,
 }
 
 impl Taggable for FullRecord {
@@ -140,6 +169,7 @@ impl Taggable for FullRecord {
         self.status.hash(&mut hasher);
         self.player.id.hash(&mut hasher);
         self.demon.id.hash(&mut hasher);
+        self.progress_verified.hash(&mut hasher);
         // notes have sub-endpoint -> no hash
         // submitter cannot be patched -> no hash
         // raw footage cannot be patched -> no hash
@@ -147,6 +177,54 @@ impl Taggable for FullRecord {
     }
 }
 
+/// A [`FullRecord`] as it may be shown to people that are not list staff.
+///
+/// Instead of nulling out the staff-only fields (submitter, raw footage and approver), this type does not have
+/// them at all, so they are left out of the serialized form and cannot accidentally end up in a public response.
+/// The enjoyment rating is left out unless it is [publicly visible](enjoyment_visible).
+#[derive(Debug, Serialize, Display, Hash)]
+#[display("{} {}% on {} (ID: {})", player, progress, demon, id)]
+pub struct PublicRecord {
+    pub id: i32,
+    pub progress: i16,
+    pub video: Option<String>,
+    pub status: RecordStatus,
+    pub player: DatabasePlayer,
+    pub demon: MinimalDemon,
+    pub enjoyment: Option<i16>,
+    pub progress_verified: bool,
+}
+
+impl From<FullRecord> for PublicRecord {
+    fn from(record: FullRecord) -> Self {
+        PublicRecord {
+            id: record.id,
+            progress: record.progress,
+            video: record.video,
+            status: record.status,
+            player: record.player,
+            demon: record.demon,
+            enjoyment: record.enjoyment.filter(|_| enjoyment_visible(false)),
+            progress_verified: record.progress_verified,
+        }
+    }
+}
+
+impl Taggable for PublicRecord {
+    fn patch_part(&self) -> u64 {
+        // Same as for FullRecord, so that the ETags line up no matter which view was requested
+        let mut hasher = DefaultHasher::new();
+        self.id.hash(&mut hasher);
+        self.progress.hash(&mut hasher);
+        self.video.hash(&mut hasher);
+        self.status.hash(&mut hasher);
+        self.player.id.hash(&mut hasher);
+        self.demon.id.hash(&mut hasher);
+        self.progress_verified.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
 #[derive(Debug, Hash, Serialize, Display)]
 #[display("{} {}% on {} (ID: {})", player, progress, demon, id)]
 pub struct MinimalRecordPD {
@@ -192,3 +270,46 @@ impl FullRecord {
         .was_modified)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::RecordStatus;
+    use serde::{de::value::StrDeserializer, Deserialize};
+
+    const STATUSES: [RecordStatus; 4] = [
+        RecordStatus::Submitted,
+        RecordStatus::Approved,
+        RecordStatus::Rejected,
+        RecordStatus::UnderConsideration,
+    ];
+
+    #[test]
+    fn test_status_round_trip() {
+        for status in STATUSES {
+            assert_eq!(RecordStatus::from_sql(&status.to_sql()), status);
+
+            let deserializer = StrDeserializer::<serde::de::value::Error>::new(&status.to_string());
+
+            assert_eq!(RecordStatus::deserialize(deserializer), Ok(status));
+        }
+    }
+
+    #[test]
+    fn test_under_consideration_transitions() {
+        use RecordStatus::*;
+
+        assert!(Submitted.can_transition_to(UnderConsideration));
+        assert!(UnderConsideration.can_transition_to(Submitted));
+        assert!(UnderConsideration.can_transition_to(Approved));
+        assert!(UnderConsideration.can_transition_to(Rejected));
+
+        assert!(!Approved.can_transition_to(UnderConsideration));
+        assert!(!Rejected.can_transition_to(UnderConsideration));
+
+        for from in STATUSES {
+            for to in [Submitted, Approved, Rejected] {
+                assert!(from.can_transition_to(to), "{} -> {}", from, to);
+            }
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
     error::{DemonlistError, Result},
-    record::note::Note,
+    record::{note::Note, FullRecord},
 };
+use chrono::NaiveDateTime;
 use futures::StreamExt;
 use sqlx::{Error, PgConnection};
 
@@ -12,21 +13,26 @@ struct PartialNote {
     is_public: bool,
     author: Option<String>,
     transferred: bool,
+    created_at: NaiveDateTime,
 }
 
 impl PartialNote {
     async fn upgrade(self, connection: &mut PgConnection) -> Result<Note> {
         let mut stream = sqlx::query!(
-            "SELECT members.name AS name FROM record_notes_modifications AS rnm INNER JOIN members ON members.member_id = rnm.userid \
-             WHERE id = $1 AND content IS NOT NULL",
+            "SELECT members.name AS name, rnm.time FROM record_notes_modifications AS rnm INNER JOIN members ON members.member_id = \
+             rnm.userid WHERE id = $1 AND content IS NOT NULL ORDER BY rnm.time",
             self.id
         )
         .fetch(connection);
 
         let mut editors = Vec::new();
+        let mut last_edited = None;
 
         while let Some(row) = stream.next().await {
-            editors.push(row?.name)
+            let row = row?;
+
+            editors.push(row.name);
+            last_edited = Some(row.time);
         }
 
         Ok(Note {
@@ -36,7 +42,9 @@ impl PartialNote {
             is_public: self.is_public,
             author: self.author,
             transferred: self.transferred,
+            created_at: self.created_at,
             editors,
+            last_edited,
         })
     }
 }
@@ -47,7 +55,7 @@ impl Note {
         let row = sqlx::query_as!(
             PartialNote,
             r#"SELECT id, record, content, is_public, members.name AS "author?: String", EXISTS(SELECT 1 FROM record_notes_modifications WHERE record IS NOT NULL 
-             AND id = $1) AS "transferred!: bool" FROM record_notes NATURAL JOIN record_notes_additions LEFT OUTER JOIN members on 
+             AND id = $1) AS "transferred!: bool", record_notes_additions.time AS created_at FROM record_notes NATURAL JOIN record_notes_additions LEFT OUTER JOIN members on 
              members.member_id = record_notes_additions.userid WHERE id = $1 and record = $2"#,
             note_id, record_id
         )
@@ -62,12 +70,19 @@ impl Note {
     }
 }
 
+impl FullRecord {
+    /// Gets all notes on this record, including non-public ones, in the order they were added
+    pub async fn notes(&self, connection: &mut PgConnection) -> Result<Vec<Note>> {
+        notes_on(self.id, false, connection).await
+    }
+}
+
 pub async fn notes_on(record_id: i32, public_only: bool, connection: &mut PgConnection) -> Result<Vec<Note>> {
     let partials = sqlx::query_as!(
         PartialNote,
         r#"SELECT id, record, content, is_public, members.name AS "author?: String", EXISTS(SELECT 1 FROM record_notes_modifications WHERE record IS NOT NULL AND 
-         id = $1) AS "transferred!: bool"  FROM record_notes NATURAL JOIN record_notes_additions LEFT OUTER JOIN members on members.member_id = 
-         record_notes_additions.userid WHERE record = $1 AND (NOT $2 OR is_public)"#,
+         id = $1) AS "transferred!: bool", record_notes_additions.time AS created_at FROM record_notes NATURAL JOIN record_notes_additions LEFT OUTER JOIN 
+         members on members.member_id = record_notes_additions.userid WHERE record = $1 AND (NOT $2 OR is_public) ORDER BY id"#,
         record_id, public_only
     )
         .fetch_all(&mut *connection)
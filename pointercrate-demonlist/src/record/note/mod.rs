@@ -4,6 +4,7 @@ mod patch;
 mod post;
 
 pub use self::{get::notes_on, patch::PatchNote, post::NewNote};
+use chrono::NaiveDateTime;
 use pointercrate_core::etag::Taggable;
 use serde::Deserialize;
 use serde::Serialize;
@@ -36,6 +37,12 @@ pub struct Note {
     ///
     /// If the user had a display name set, this is the display name
     pub editors: Vec<String>,
+
+    /// When this note was created (UTC)
+    pub created_at: NaiveDateTime,
+
+    /// When the content of this note was last edited (UTC). None if it was never edited
+    pub last_edited: Option<NaiveDateTime>,
 }
 
 impl Taggable for Note {
@@ -22,9 +22,19 @@ impl Note {
                 return Err(DemonlistError::NoteEmpty);
             }
 
-            sqlx::query!("UPDATE record_notes SET content = $1 WHERE id = $2", content, self.id)
-                .execute(&mut *connection)
-                .await?;
+            // The audit trigger timestamps the modification with the transaction's start time, so use the same here
+            let edited_at = sqlx::query!(
+                r#"UPDATE record_notes SET content = $1 WHERE id = $2 RETURNING NOW() AT TIME ZONE 'utc' AS "edited_at!""#,
+                content,
+                self.id
+            )
+            .fetch_one(&mut *connection)
+            .await?
+            .edited_at;
+
+            if content != self.content {
+                self.last_edited = Some(edited_at);
+            }
 
             self.content = content;
         }
@@ -2,6 +2,8 @@ use crate::{
     error::{DemonlistError, Result},
     record::{note::Note, FullRecord},
 };
+use pointercrate_core::pool::audit_connection;
+use pointercrate_user::User;
 use serde::Deserialize;
 use sqlx::PgConnection;
 
@@ -13,6 +15,12 @@ pub struct NewNote {
     is_public: bool,
 }
 
+impl NewNote {
+    pub fn new(content: String, is_public: bool) -> Self {
+        NewNote { content, is_public }
+    }
+}
+
 impl Note {
     /// Creates a new note on the given records
     ///
@@ -23,24 +31,42 @@ impl Note {
             return Err(DemonlistError::NoteEmpty);
         }
 
-        let note_id = sqlx::query!(
-            "INSERT INTO record_notes (record, content, is_public) VALUES ($1, $2, $3) RETURNING id",
+        let row = sqlx::query!(
+            "INSERT INTO record_notes (record, content, is_public) VALUES ($1, $2, $3) RETURNING id, NOW() AT TIME ZONE 'utc' AS \
+             \"created_at!\"",
             record.id,
             new_note.content,
             new_note.is_public,
         )
         .fetch_one(connection)
-        .await?
-        .id;
+        .await?;
 
         Ok(Note {
-            id: note_id,
+            id: row.id,
             record: record.id,
             content: new_note.content,
             is_public: new_note.is_public,
             transferred: false,
             author: None,
+            created_at: row.created_at,
             editors: vec![],
+            last_edited: None,
         })
     }
 }
+
+impl FullRecord {
+    /// Adds an internal (non-public) note by the given user to this record
+    ///
+    /// The connection is audited as `author`, so that the note's creation is attributed to them.
+    /// Like [`Note::create_on`], this does not insert the note into any notes vector.
+    pub async fn add_note(&self, content: String, author: &User, connection: &mut PgConnection) -> Result<Note> {
+        audit_connection(&mut *connection, author.id).await?;
+
+        let mut note = Note::create_on(self, NewNote::new(content, false), connection).await?;
+
+        note.author = Some(author.name.clone());
+
+        Ok(note)
+    }
+}
@@ -1,12 +1,16 @@
 use crate::{
     demon::MinimalDemon,
     player::DatabasePlayer,
-    record::{MinimalRecordPD, RecordStatus},
+    record::{MinimalRecordP, MinimalRecordPD, RecordStatus},
 };
+use chrono::NaiveDateTime;
 use futures::StreamExt;
 use pointercrate_core::{
+    error::CoreError,
     first_and_last,
-    pagination::{PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat},
+    pagination::{
+        PageContext, Paginatable, PaginationParameters, PaginationQuery, __pagination_compat, DEFAULT_ENTRIES_PER_PAGE, ENTRIES_PER_PAGE,
+    },
     util::{non_nullable, nullable},
 };
 use serde::{Deserialize, Serialize};
@@ -27,6 +31,15 @@ pub struct RecordPagination {
     #[serde(rename = "progress__gt")]
     progress_gt: Option<i16>,
 
+    /// Inclusive lower bound on the progress, e.g. `progress_min=80&progress_max=90` for all records
+    /// between 80% and 90%
+    #[serde(default, deserialize_with = "non_nullable")]
+    progress_min: Option<i16>,
+
+    /// Inclusive upper bound on the progress
+    #[serde(default, deserialize_with = "non_nullable")]
+    progress_max: Option<i16>,
+
     demon_position: Option<i16>,
 
     #[serde(default, deserialize_with = "non_nullable")]
@@ -54,6 +67,20 @@ pub struct RecordPagination {
 
     #[serde(default, deserialize_with = "non_nullable")]
     pub submitter: Option<i32>,
+
+    /// Only return records by players of the nation with the given ISO country code
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub nationality: Option<String>,
+
+    /// Only return records approved at or after the given time. Records whose approval time is
+    /// unknown are never included
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub since: Option<NaiveDateTime>,
+
+    /// Whether the enjoyment ratings of the returned records should be left out. Not settable via the
+    /// query string, endpoints decide this based on who is asking
+    #[serde(skip)]
+    pub hide_enjoyment: bool,
 }
 
 impl PaginationQuery for RecordPagination {
@@ -94,6 +121,10 @@ impl Paginatable<RecordPagination> for MinimalRecordPD {
             .bind(query.player)
             .bind(query.submitter)
             .bind(query.params.limit + 1)
+            .bind(query.progress_min)
+            .bind(query.progress_max)
+            .bind(query.nationality.as_deref())
+            .bind(query.since)
             .fetch(&mut *connection);
 
         let mut records = Vec::new();
@@ -106,7 +137,7 @@ impl Paginatable<RecordPagination> for MinimalRecordPD {
                 progress: row.try_get("progress")?,
                 video: row.try_get("video")?,
                 status: RecordStatus::from_sql(&row.try_get::<String, _>("status")?),
-                enjoyment: row.try_get("enjoyment")?,
+                enjoyment: if query.hide_enjoyment { None } else { row.try_get("enjoyment")? },
                 player: DatabasePlayer {
                     id: row.try_get("player_id")?,
                     name: row.try_get("player_name")?,
@@ -127,3 +158,123 @@ impl Paginatable<RecordPagination> for MinimalRecordPD {
         self.id
     }
 }
+
+/// A submitted record together with how long it has been waiting for review
+#[derive(Debug, Serialize)]
+pub struct PendingRecord {
+    #[serde(flatten)]
+    pub record: MinimalRecordPD,
+
+    /// When this record was submitted. `None` if the record predates the audit log
+    pub submitted_at: Option<NaiveDateTime>,
+
+    /// The number of seconds this record has been in the queue for
+    pub pending_seconds: Option<i64>,
+}
+
+/// Pagination over all submitted records, oldest submission first
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct PendingRecordPagination {
+    #[serde(flatten)]
+    pub params: PaginationParameters,
+}
+
+impl PaginationQuery for PendingRecordPagination {
+    fn parameters(&self) -> PaginationParameters {
+        self.params
+    }
+
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        Self { params: parameters }
+    }
+}
+
+impl Paginatable<PendingRecordPagination> for PendingRecord {
+    first_and_last!("records");
+
+    async fn page(
+        query: &PendingRecordPagination, connection: &mut PgConnection,
+    ) -> Result<(Vec<PendingRecord>, PageContext), sqlx::Error> {
+        let sql_query = format!(include_str!("../../sql/paginate_pending_records.sql"), query.params.order());
+
+        let mut stream = sqlx::query(&sql_query)
+            .bind(query.params.before)
+            .bind(query.params.after)
+            .bind(query.params.limit + 1)
+            .fetch(&mut *connection);
+
+        let mut records = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row: PgRow = row?;
+
+            records.push(PendingRecord {
+                record: MinimalRecordPD {
+                    id: row.try_get("id")?,
+                    progress: row.try_get("progress")?,
+                    video: row.try_get("video")?,
+                    status: RecordStatus::Submitted,
+                    enjoyment: row.try_get("enjoyment")?,
+                    player: DatabasePlayer {
+                        id: row.try_get("player_id")?,
+                        name: row.try_get("player_name")?,
+                        banned: row.try_get("player_banned")?,
+                    },
+                    demon: MinimalDemon {
+                        id: row.try_get("demon_id")?,
+                        position: row.try_get("position")?,
+                        name: row.try_get("demon_name")?,
+                    },
+                },
+                submitted_at: row.try_get("submitted_at")?,
+                pending_seconds: row.try_get("pending_seconds")?,
+            })
+        }
+
+        Ok(__pagination_compat(&query.params, records))
+    }
+
+    fn pagination_id(&self) -> i32 {
+        self.record.id
+    }
+}
+
+/// An approved record together with the demon it is on and when it was approved
+#[derive(Debug, Serialize)]
+pub struct RecentRecord {
+    #[serde(flatten)]
+    pub record: MinimalRecordP,
+    pub demon: MinimalDemon,
+    pub approved_at: NaiveDateTime,
+}
+
+/// Cursor for walking through the approved records, most recently approved first.
+///
+/// Records approved at the same time are ordered by id (highest first), so the next page is obtained by
+/// passing the approval time and id of the last record on the current page as `after_approved_at` and
+/// `after_id`. Records whose approval time is unknown are never included.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecentRecordPagination {
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub after_approved_at: Option<NaiveDateTime>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub after_id: Option<i32>,
+
+    #[serde(default = "default_limit")]
+    pub limit: i32,
+}
+
+const fn default_limit() -> i32 {
+    DEFAULT_ENTRIES_PER_PAGE
+}
+
+impl RecentRecordPagination {
+    pub fn validate(&self) -> Result<(), CoreError> {
+        if !(1..=ENTRIES_PER_PAGE).contains(&self.limit) {
+            return Err(CoreError::InvalidPaginationLimit);
+        }
+
+        Ok(())
+    }
+}
@@ -1,16 +1,21 @@
 use crate::{
+    config,
     demon::MinimalDemon,
     error::{DemonlistError, Result},
     player::DatabasePlayer,
-    record::{FullRecord, RecordStatus},
+    record::{
+        note::{NewNote, Note},
+        FullRecord, RecordStatus,
+    },
 };
 use log::{info, warn};
 use pointercrate_core::{
-    error::CoreError,
+    error::{CoreError, PointercrateError},
     util::{non_nullable, nullable},
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sqlx::PgConnection;
+use std::collections::HashSet;
 
 #[derive(Debug, Deserialize)]
 pub struct PatchRecord {
@@ -34,14 +39,61 @@ pub struct PatchRecord {
 
     #[serde(default, deserialize_with = "nullable")]
     enjoyment: Option<Option<i16>>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    progress_verified: Option<bool>,
+
+    /// Allows lowering the progress by more than [`config::max_progress_decrease`]
+    #[serde(default)]
+    force: bool,
+
+    /// The rejection reason whose [configured note](config::rejection_note_template) should be
+    /// attached to the record. Only allowed when rejecting the record
+    #[serde(default, deserialize_with = "non_nullable")]
+    reason_template: Option<String>,
+}
+
+impl PatchRecord {
+    /// Whether this patch sets or removes the enjoyment rating, and changes nothing else
+    pub fn only_changes_enjoyment(&self) -> bool {
+        self.enjoyment.is_some()
+            && self.progress.is_none()
+            && self.video.is_none()
+            && self.status.is_none()
+            && self.player.is_none()
+            && self.demon.is_none()
+            && self.demon_id.is_none()
+            && self.progress_verified.is_none()
+            && self.reason_template.is_none()
+    }
 }
 
 impl FullRecord {
     /// Must be called inside a transaction
-    pub async fn apply_patch(mut self, data: PatchRecord, connection: &mut PgConnection) -> Result<Self> {
+    pub async fn apply_patch(self, data: PatchRecord, connection: &mut PgConnection) -> Result<Self> {
+        self.apply_patch_with(data, config::rejection_note_template, connection).await
+    }
+
+    /// Like [`FullRecord::apply_patch`], but looks up rejection notes via the given function instead
+    /// of [`config::rejection_note_template`]
+    pub async fn apply_patch_with(
+        mut self, data: PatchRecord, rejection_note_template: impl Fn(&str) -> Option<String>, connection: &mut PgConnection,
+    ) -> Result<Self> {
         info!("Applying patch {:?} for record {}", data, self);
 
+        let rejection_note = match data.reason_template {
+            Some(_) if data.status != Some(RecordStatus::Rejected) => return Err(DemonlistError::ReasonTemplateWithoutRejection),
+            Some(ref reason) => {
+                Some(rejection_note_template(reason).ok_or_else(|| DemonlistError::UnknownReasonTemplate { reason: reason.clone() })?)
+            },
+            None => None,
+        };
+
         if let Some(progress) = data.progress {
+            if !data.force {
+                validate_progress_decrease(self.progress, progress, config::max_progress_decrease())?;
+            }
+
             self.set_progress(progress, connection).await?;
         }
 
@@ -63,6 +115,10 @@ impl FullRecord {
             }
         }
 
+        if let Some(progress_verified) = data.progress_verified {
+            self.set_progress_verified(progress_verified, connection).await?;
+        }
+
         if let Some(player) = data.player {
             let player = DatabasePlayer::by_name_or_create(player.as_ref(), connection).await?;
 
@@ -79,6 +135,10 @@ impl FullRecord {
             _ => (),
         }
 
+        if let Some(template) = rejection_note {
+            Note::create_on(&self, NewNote::new(expand_note_template(&template, &self), true), &mut *connection).await?;
+        }
+
         // Not all record update require recomputing scores (for example, changing status from "submitted" to "under consideration")
         // but the logic for correctly determining this is hard, and updating scores of individual players cheap, so we do not bother.
         self.player.update_score(connection).await?;
@@ -199,15 +259,38 @@ impl FullRecord {
     }
 
     pub async fn delete_video(&mut self, connection: &mut PgConnection) -> Result<()> {
-        sqlx::query!("UPDATE records SET video = NULL WHERE id = $1", self.id)
-            .execute(connection)
-            .await?;
+        if self.status == RecordStatus::Approved && self.video_required(&mut *connection).await? {
+            return Err(DemonlistError::VideoRequired);
+        }
 
+        // Whatever was verified against the old video no longer applies
+        sqlx::query!(
+            "UPDATE records SET video = NULL, progress_verified = progress_verified AND video IS NULL WHERE id = $1",
+            self.id
+        )
+        .execute(connection)
+        .await?;
+
+        self.progress_verified &= self.video.is_none();
         self.video = None;
 
         Ok(())
     }
 
+    /// Whether the video requirement is enabled and this record isn't grandfathered in
+    async fn video_required(&self, connection: &mut PgConnection) -> Result<bool> {
+        if !crate::config::require_video() {
+            return Ok(false);
+        }
+
+        let exempt = sqlx::query!("SELECT video_exempt FROM records WHERE id = $1", self.id)
+            .fetch_one(connection)
+            .await?
+            .video_exempt;
+
+        Ok(!exempt)
+    }
+
     pub async fn set_video(&mut self, video: String, connection: &mut PgConnection) -> Result<()> {
         let video = crate::video::validate(&video)?;
 
@@ -222,11 +305,12 @@ impl FullRecord {
             return Err(DemonlistError::DuplicateVideo { id: row.id });
         }
 
-        sqlx::query!("UPDATE records SET video = $1::text WHERE id = $2", video, self.id)
+        sqlx::query!("UPDATE records SET video = $1::text, progress_verified = FALSE WHERE id = $2", video, self.id)
             .execute(connection)
             .await?;
 
         self.video = Some(video);
+        self.progress_verified = false;
 
         Ok(())
     }
@@ -241,11 +325,7 @@ impl FullRecord {
     }
 
     pub async fn set_enjoyment(&mut self, enjoyment: i16, connection: &mut PgConnection) -> Result<()> {
-
-        
-        if enjoyment < 0 || enjoyment > 10 {
-            return Err(DemonlistError::InvalidEnjoyment)
-        }
+        validate_enjoyment(enjoyment, self.progress, config::enjoyment_requires_completion())?;
 
         sqlx::query!("UPDATE records SET enjoyment = $1 WHERE id = $2", enjoyment, self.id)
             .execute(connection)
@@ -255,6 +335,20 @@ impl FullRecord {
         Ok(())
     }
 
+    pub async fn set_progress_verified(&mut self, progress_verified: bool, connection: &mut PgConnection) -> Result<()> {
+        if self.progress_verified != progress_verified {
+            sqlx::query!("UPDATE records SET progress_verified = $1 WHERE id = $2", progress_verified, self.id)
+                .execute(connection)
+                .await?;
+
+            info!("Marked progress of record {} as verified: {}", self, progress_verified);
+
+            self.progress_verified = progress_verified;
+        }
+
+        Ok(())
+    }
+
     pub async fn set_demon(&mut self, demon: MinimalDemon, connection: &mut PgConnection) -> Result<()> {
         let requirement = demon.requirement(connection).await?;
 
@@ -301,6 +395,21 @@ impl FullRecord {
 
     /// Updates this record's status
     pub async fn set_status(&mut self, status: RecordStatus, connection: &mut PgConnection) -> Result<()> {
+        if !self.status.can_transition_to(status) {
+            return Err(DemonlistError::InvalidStatusTransition {
+                from: self.status,
+                to: status,
+            });
+        }
+
+        if status == RecordStatus::Approved
+            && self.status != status
+            && self.video.is_none()
+            && self.video_required(&mut *connection).await?
+        {
+            return Err(DemonlistError::VideoRequired);
+        }
+
         // To uphold the invariants outlined in the module documentation, we need to do some preparations.
         // What preparation has to be done, depends on what the current and new status are.
         match (self.status, status) {
@@ -365,22 +474,92 @@ impl FullRecord {
             _ => (),
         }
 
-        sqlx::query!(
-            "UPDATE records SET status_ = cast($1::text as record_status) WHERE id = $2", /* FIXME(sqlx) ridiculous query
-                                                                                           * format to trick sqlx into working
-                                                                                           * with custom types */
+        // Approvals are attributed to whoever is using this connection, see `audit_connection`. Reapproving an already approved record
+        // keeps the original attribution and approval time.
+        self.approved_by = sqlx::query!(
+            "UPDATE records SET status_ = cast($1::text as record_status), failed_requirement = NULL, approved_by = CASE WHEN $1::text <> 'APPROVED' THEN NULL WHEN \
+             status_ = 'APPROVED' THEN approved_by ELSE (SELECT id FROM active_user LIMIT 1) END, approved_at = CASE WHEN $1::text <> \
+             'APPROVED' THEN NULL WHEN status_ = 'APPROVED' THEN approved_at ELSE NOW() AT TIME ZONE 'utc' END WHERE id = $2 RETURNING \
+             approved_by", /* FIXME(sqlx) ridiculous query
+                            * format to trick sqlx into working
+                            * with custom types */
             status.to_sql().to_string(),
             self.id
         )
-        .execute(connection)
-        .await?;
+        .fetch_one(connection)
+        .await?
+        .approved_by;
 
         self.status = status;
 
         Ok(())
     }
 
-    /// Updates this record's progress
+    /// Moves all the given records to the given status
+    ///
+    /// All records are checked to exist before any of them is touched. If any of them does not exist,
+    /// or cannot be moved to the new status, an error naming the offending record is returned, and the
+    /// caller is expected to roll back the transaction. Records deleted as a side effect of an earlier
+    /// transition in the batch (e.g. a submission made obsolete by an approval) are skipped.
+    ///
+    /// Each affected player's score is only updated once, regardless of how many of their records are
+    /// part of the batch. Only records that still exist once the whole batch has been processed are
+    /// returned, as a later transition may delete a record moved by an earlier one.
+    ///
+    /// Must run inside a transaction!
+    pub async fn set_status_bulk(ids: &[i32], status: RecordStatus, connection: &mut PgConnection) -> Result<Vec<FullRecord>> {
+        let mut ids = ids.to_vec();
+        ids.sort_unstable();
+        ids.dedup();
+
+        for &id in &ids {
+            FullRecord::by_id(id, &mut *connection).await?;
+        }
+
+        let mut records = Vec::new();
+        let mut players = HashSet::new();
+
+        for id in ids {
+            let mut record = match FullRecord::by_id(id, &mut *connection).await {
+                Err(DemonlistError::RecordNotFound { .. }) => continue,
+                result => result?,
+            };
+
+            match record.set_status(status, &mut *connection).await {
+                Err(DemonlistError::Core(error)) => return Err(error.into()),
+                Err(error) => {
+                    return Err(DemonlistError::BulkStatusChangeFailed {
+                        record_id: id,
+                        reason: error.to_string(),
+                    })
+                },
+                Ok(()) => (),
+            }
+
+            players.insert(record.player.clone());
+            records.push(record);
+        }
+
+        for player in players {
+            player.update_score(&mut *connection).await?;
+        }
+
+        // e.g. approving a 70% and then a 100% record of the same player on the same demon deletes the former again
+        let mut surviving = Vec::with_capacity(records.len());
+
+        for record in records {
+            match FullRecord::by_id(record.id, &mut *connection).await {
+                Err(DemonlistError::RecordNotFound { .. }) => continue,
+                result => surviving.push(result?),
+            }
+        }
+
+        info!("Moved {} records to status {}", surviving.len(), status);
+
+        Ok(surviving)
+    }
+
+    /// Updates this record's progress, resetting [`FullRecord::progress_verified`] if it changed
     ///
     /// If this record is approved, all submissions with lower progress of the same (player,
     /// demon)-tuple are deleted and have their notes transferred to this record.
@@ -421,12 +600,186 @@ impl FullRecord {
             );
         }
 
-        sqlx::query!("UPDATE records SET progress = $1 WHERE id = $2", progress, self.id)
-            .execute(connection)
-            .await?;
+        // A verification of the old progress says nothing about the new one
+        sqlx::query!(
+            "UPDATE records SET progress = $1, progress_verified = progress_verified AND progress = $1 WHERE id = $2",
+            progress,
+            self.id
+        )
+        .execute(connection)
+        .await?;
 
+        self.progress_verified &= self.progress == progress;
         self.progress = progress;
 
         Ok(())
     }
 }
+
+fn expand_note_template(template: &str, record: &FullRecord) -> String {
+    template
+        .replace("{player}", &record.player.name)
+        .replace("{demon}", &record.demon.name)
+        .replace("{progress}", &record.progress.to_string())
+}
+
+/// Guards against accidentally wiping out progress, by rejecting decreases larger than
+/// `max_decrease` (if set)
+fn validate_progress_decrease(current: i16, new: i16, max_decrease: Option<i16>) -> Result<()> {
+    match max_decrease {
+        Some(maximal) if current - new > maximal => Err(DemonlistError::ProgressDecreaseTooLarge { maximal }),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the given enjoyment rating is between 0 and 10, and that a record with the given progress
+/// may be rated at all. If `requires_completion` is set, only completions can be rated, since the player
+/// has not seen all of the level otherwise
+pub(super) fn validate_enjoyment(enjoyment: i16, progress: i16, requires_completion: bool) -> Result<()> {
+    if !(0..=10).contains(&enjoyment) {
+        return Err(DemonlistError::InvalidEnjoyment);
+    }
+
+    if requires_completion && progress != 100 {
+        return Err(DemonlistError::EnjoymentRequiresCompletion);
+    }
+
+    Ok(())
+}
+
+/// A single row of an enjoyment import, setting (or, if `enjoyment` is `None`, removing) the
+/// enjoyment rating of a record
+#[derive(Debug, Deserialize)]
+pub struct EnjoymentImportRow {
+    pub record_id: i32,
+    pub enjoyment: Option<i16>,
+}
+
+/// Why a row of an enjoyment import was not applied
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EnjoymentImportError {
+    pub code: u16,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EnjoymentImportResult {
+    pub record_id: i32,
+
+    /// `None` if the row was applied
+    pub error: Option<EnjoymentImportError>,
+}
+
+/// Applies the given enjoyment ratings, skipping (and reporting) rows that are invalid or refer to
+/// non-existent records. Results are in the same order as the rows.
+///
+/// Must be called inside a transaction
+pub async fn import_enjoyment(rows: Vec<EnjoymentImportRow>, connection: &mut PgConnection) -> Result<Vec<EnjoymentImportResult>> {
+    let requires_completion = config::enjoyment_requires_completion();
+    let mut results = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        let progress = sqlx::query_scalar!("SELECT progress FROM records WHERE id = $1", row.record_id)
+            .fetch_optional(&mut *connection)
+            .await?;
+
+        let outcome = match (progress, row.enjoyment) {
+            (None, _) => Err(DemonlistError::RecordNotFound { record_id: row.record_id }),
+            (Some(progress), Some(enjoyment)) => validate_enjoyment(enjoyment, progress, requires_completion),
+            (Some(_), None) => Ok(()),
+        };
+
+        if outcome.is_ok() {
+            sqlx::query!("UPDATE records SET enjoyment = $1 WHERE id = $2", row.enjoyment, row.record_id)
+                .execute(&mut *connection)
+                .await?;
+        }
+
+        results.push(EnjoymentImportResult {
+            record_id: row.record_id,
+            error: outcome.err().map(|err| EnjoymentImportError {
+                code: err.error_code(),
+                message: err.to_string(),
+            }),
+        });
+    }
+
+    let applied = results.iter().filter(|result| result.error.is_none()).count();
+
+    info!("Imported {} of {} enjoyment ratings", applied, results.len());
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_note_template, validate_enjoyment, validate_progress_decrease};
+    use crate::{
+        demon::MinimalDemon,
+        error::DemonlistError,
+        player::DatabasePlayer,
+        record::{FullRecord, RecordStatus},
+    };
+
+    #[test]
+    fn test_expand_note_template() {
+        let record = FullRecord {
+            id: 1,
+            progress: 60,
+            video: None,
+            raw_footage: None,
+            status: RecordStatus::Submitted,
+            enjoyment: None,
+            approved_by: None,
+            progress_verified: false,
+            player: DatabasePlayer {
+                id: 1,
+                name: "stardust1971".to_string(),
+                banned: false,
+            },
+            demon: MinimalDemon {
+                id: 1,
+                position: 1,
+                name: "Bloodbath".to_string(),
+            },
+            submitter: None,
+        };
+
+        assert_eq!(
+            expand_note_template("Please send raw footage for {player}'s {progress}% on {demon}", &record),
+            "Please send raw footage for stardust1971's 60% on Bloodbath"
+        );
+    }
+
+    #[test]
+    fn test_progress_decrease() {
+        // Unlimited unless configured
+        assert!(validate_progress_decrease(100, 50, None).is_ok());
+
+        assert!(validate_progress_decrease(90, 100, Some(0)).is_ok());
+        assert!(validate_progress_decrease(100, 90, Some(10)).is_ok());
+        assert_eq!(
+            validate_progress_decrease(100, 89, Some(10)),
+            Err(DemonlistError::ProgressDecreaseTooLarge { maximal: 10 })
+        );
+        assert_eq!(
+            validate_progress_decrease(100, 99, Some(0)),
+            Err(DemonlistError::ProgressDecreaseTooLarge { maximal: 0 })
+        );
+    }
+
+    #[test]
+    fn test_enjoyment_requires_completion() {
+        // Completions can always be rated
+        assert_eq!(validate_enjoyment(8, 100, true), Ok(()));
+        assert_eq!(validate_enjoyment(8, 100, false), Ok(()));
+
+        // Partial progress can only be rated if that is not switched off
+        assert_eq!(validate_enjoyment(8, 75, true), Err(DemonlistError::EnjoymentRequiresCompletion));
+        assert_eq!(validate_enjoyment(8, 75, false), Ok(()));
+
+        // The rating itself is checked first
+        assert_eq!(validate_enjoyment(11, 75, true), Err(DemonlistError::InvalidEnjoyment));
+        assert_eq!(validate_enjoyment(-1, 100, false), Err(DemonlistError::InvalidEnjoyment));
+    }
+}
@@ -1,8 +1,13 @@
 use crate::{
-    demon::MinimalDemon,
+    config,
+    demon::{Difficulty, MinimalDemon, SubmissionState},
     error::{DemonlistError, Result},
     player::{claim::PlayerClaim, DatabasePlayer},
-    record::{FullRecord, RecordStatus},
+    record::{
+        note::{NewNote, Note},
+        patch::validate_enjoyment,
+        FullRecord, RecordStatus,
+    },
     submitter::Submitter,
 };
 use derive_more::Display;
@@ -41,6 +46,46 @@ pub struct NormalizedSubmission {
     note: Option<String>,
 }
 
+/// The configurable requirements a submission has to meet, on top of the ones that always apply
+///
+/// None of these apply to records list staff add with a status other than
+/// [`RecordStatus::Submitted`], except for ratings of incomplete records being disallowed.
+#[derive(Debug, Clone)]
+pub struct SubmissionRequirements {
+    /// See [`config::require_video`]
+    pub video: bool,
+
+    /// See [`config::require_video_for_completions`]
+    pub video_for_completions: bool,
+
+    /// See [`config::enjoyment_requires_completion`]
+    pub enjoyment_requires_completion: bool,
+
+    /// The difficulties for which [raw footage is required](config::raw_footage_required_for)
+    pub raw_footage_for: Vec<Difficulty>,
+
+    /// The difficulties for which [an enjoyment rating is required](config::enjoyment_required_for)
+    pub enjoyment_for: Vec<Difficulty>,
+}
+
+impl SubmissionRequirements {
+    pub fn from_config() -> Self {
+        SubmissionRequirements {
+            video: config::require_video(),
+            video_for_completions: config::require_video_for_completions(),
+            enjoyment_requires_completion: config::enjoyment_requires_completion(),
+            raw_footage_for: Difficulty::all()
+                .into_iter()
+                .filter(|&difficulty| config::raw_footage_required_for(difficulty))
+                .collect(),
+            enjoyment_for: Difficulty::all()
+                .into_iter()
+                .filter(|&difficulty| config::enjoyment_required_for(difficulty))
+                .collect(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ValidatedSubmission {
     progress: i16,
@@ -63,12 +108,6 @@ impl Submission {
     }
 
     pub async fn normalize(self, connection: &mut PgConnection) -> Result<NormalizedSubmission> {
-        // validate video
-        let video = match self.video {
-            Some(ref video) => Some(crate::video::validate(video)?),
-            None => None,
-        };
-
         // Resolve player and demon name against the database
         let player = DatabasePlayer::by_name_or_create(self.player.as_ref(), connection).await?;
         let demon = MinimalDemon::by_id(self.demon, connection).await?;
@@ -79,7 +118,7 @@ impl Submission {
             demon,
             status: self.status,
             enjoyment: self.enjoyment,
-            video,
+            video: self.video,
             raw_footage: self.raw_footage,
             note: self.note,
         })
@@ -92,50 +131,102 @@ impl NormalizedSubmission {
     }
 
     pub async fn validate(self, connection: &mut PgConnection) -> Result<ValidatedSubmission> {
+        self.validate_with(&SubmissionRequirements::from_config(), connection).await
+    }
+
+    /// Like [`NormalizedSubmission::validate`], but checking against the given requirements instead
+    /// of the configured ones
+    pub async fn validate_with(self, requirements: &SubmissionRequirements, connection: &mut PgConnection) -> Result<ValidatedSubmission> {
         // Banned player can't have records on the list
         if self.player.banned {
             return Err(DemonlistError::PlayerBanned);
         }
 
         // Cannot submit records for the legacy list (it is possible to directly add them for list mods)
-        if self.demon.position > crate::config::extended_list_size() && self.status == RecordStatus::Submitted {
+        if self.demon.is_legacy() && self.status == RecordStatus::Submitted {
             return Err(DemonlistError::SubmitLegacy);
         }
 
         // Can only submit 100% records for the extended list (it is possible to directly add them for list
         // mods)
-        if self.demon.position > crate::config::list_size() && self.progress != 100 && self.status == RecordStatus::Submitted {
+        if !self.demon.is_main_list() && self.progress != 100 && self.status == RecordStatus::Submitted {
             return Err(DemonlistError::Non100Extended);
         }
 
+        // Archived demons only keep their existing records around, while closing submissions is meant to
+        // be temporary. Both still allow list mods to directly add records, e.g. for history corrections
+        if self.status == RecordStatus::Submitted {
+            match self.demon.submission_state(&mut *connection).await? {
+                SubmissionState::Archived => return Err(DemonlistError::DemonArchived),
+                SubmissionState::Closed => return Err(DemonlistError::SubmissionsClosed),
+                SubmissionState::Open => (),
+            }
+        }
+
         let requirement = self.demon.requirement(&mut *connection).await?;
+        let difficulty = self.demon.difficulty(&mut *connection).await?;
+
+        // Problems with the individual fields are independent of each other, so collect all of them
+        // instead of making the submitter discover them one at a time
+        let mut errors = Vec::new();
 
         // Check if the record meets the record requirement for this demon
         if self.progress > 100 || self.progress < requirement {
-            return Err(DemonlistError::InvalidProgress { requirement });
+            errors.push(DemonlistError::InvalidProgress { requirement });
         }
 
-        // Check if the record's enjoyment is between 0 and 10
-        if let Some(enjoyment) = self.enjoyment {
-            if enjoyment < 0 || enjoyment > 10 {
-                return Err(DemonlistError::InvalidEnjoyment)
-            } 
+        let requires_completion = requirements.enjoyment_requires_completion;
+
+        match self.enjoyment {
+            Some(enjoyment) => errors.extend(validate_enjoyment(enjoyment, self.progress, requires_completion).err()),
+            // list mods can add records without enjoyment. Records that may not be rated in the first place cannot
+            // be required to be either
+            None if self.status == RecordStatus::Submitted
+                && requirements.enjoyment_for.contains(&difficulty)
+                && (self.progress == 100 || !requires_completion) =>
+            {
+                errors.push(DemonlistError::EnjoymentRequired)
+            },
+            _ => (),
         }
 
-        match self.raw_footage {
-            Some(ref raw) => {
-                let _ = Url::parse(raw).map_err(|_| DemonlistError::MalformedRawUrl)?;
+        let video = match self.video {
+            Some(ref video) => match crate::video::validate(video) {
+                Ok(video) => Some(video),
+                Err(err) => {
+                    errors.push(err);
+
+                    None
+                },
+            },
+            None => {
+                // list mods can add records without video even if videos are required
+                if self.status == RecordStatus::Submitted {
+                    if requirements.video {
+                        errors.push(DemonlistError::VideoRequired);
+                    } else if self.progress == 100 && requirements.video_for_completions {
+                        errors.push(DemonlistError::CompletionVideoRequired);
+                    }
+                }
+
+                None
             },
-            None if self.status == RecordStatus::Submitted => {
+        };
+
+        match self.raw_footage {
+            Some(ref raw) if Url::parse(raw).is_err() => errors.push(DemonlistError::MalformedRawUrl),
+            None if self.status == RecordStatus::Submitted && requirements.raw_footage_for.contains(&difficulty) => {
                 // list mods can submit without raw
-                return Err(DemonlistError::RawRequired);
+                errors.push(DemonlistError::RawRequired);
             },
             _ => (),
         }
 
+        DemonlistError::aggregate(errors)?;
+
         Ok(ValidatedSubmission {
             progress: self.progress,
-            video: self.video,
+            video,
             raw_footage: self.raw_footage,
             status: self.status,
             enjoyment: self.enjoyment,
@@ -148,14 +239,25 @@ impl NormalizedSubmission {
 
 impl ValidatedSubmission {
     pub async fn create(self, submitter: Submitter, connection: &mut PgConnection) -> Result<FullRecord> {
+        if submitter.banned {
+            return Err(DemonlistError::SubmitterBanned);
+        }
+
+        // Only list staff can add records without a video while videos are required. Those records are
+        // exempt from the requirement, as they could otherwise not be approved below.
+        let video_exempt = self.video.is_none() && self.status != RecordStatus::Submitted;
+
         let id = sqlx::query!(
-            "INSERT INTO records (progress, video, status_, player, submitter, demon, raw_footage) VALUES ($1, $2::TEXT, 'SUBMITTED', $3, $4, $5, $6) RETURNING id",
+            "INSERT INTO records (progress, video, status_, player, submitter, demon, raw_footage, enjoyment, video_exempt) VALUES ($1, \
+             $2::TEXT, 'SUBMITTED', $3, $4, $5, $6, $7, $8) RETURNING id",
             self.progress,
             self.video,
             self.player.id,
             submitter.id,
             self.demon.id,
-            self.raw_footage
+            self.raw_footage,
+            self.enjoyment,
+            video_exempt
         )
         .fetch_one(&mut *connection)
         .await?
@@ -168,6 +270,8 @@ impl ValidatedSubmission {
             raw_footage: self.raw_footage,
             status: RecordStatus::Submitted,
             enjoyment: self.enjoyment,
+            approved_by: None,
+            progress_verified: false,
             player: self.player,
             demon: self.demon,
             submitter: Some(submitter),
@@ -181,9 +285,7 @@ impl ValidatedSubmission {
 
         if let Some(note) = self.note {
             if !note.trim().is_empty() {
-                sqlx::query!("INSERT INTO record_notes (record, content) VALUES ($1, $2)", record.id, note)
-                    .execute(&mut *connection)
-                    .await?;
+                Note::create_on(&record, NewNote::new(note, false), &mut *connection).await?;
             }
         }
 
@@ -1,9 +1,11 @@
 use crate::{
     error::{DemonlistError, Result},
-    submitter::Submitter,
+    submitter::{Submitter, SubmitterStats},
 };
+use chrono::NaiveDateTime;
+use pointercrate_core::error::CoreError;
 use sqlx::{Error, PgConnection};
-use std::net::IpAddr;
+use std::{net::IpAddr, time::Duration};
 
 impl Submitter {
     pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<Submitter> {
@@ -30,4 +32,83 @@ impl Submitter {
             banned: row.banned,
         }))
     }
+
+    /// Errors out if this submitter has submitted at least `limit` records within the last hour
+    ///
+    /// A `limit` of `0` means no limit. The time of submission is taken from the audit log, and
+    /// submissions that have since been deleted are not counted.
+    pub async fn check_submission_limit(&self, limit: i64, connection: &mut PgConnection) -> Result<()> {
+        if limit <= 0 {
+            return Ok(());
+        }
+
+        // The `limit`-th most recent submission within the last hour. Once it is older than an hour, the
+        // submitter is below the limit again.
+        let retry_after = sqlx::query_scalar!(
+            r#"SELECT EXTRACT(EPOCH FROM q.added + INTERVAL '1 hour' - (NOW() AT TIME ZONE 'utc'))::DOUBLE PRECISION AS "retry_after!"
+               FROM (
+                   SELECT (SELECT MIN(time) FROM record_additions WHERE record_additions.id = records.id) AS added
+                   FROM records
+                   WHERE submitter = $1
+               ) q
+               WHERE q.added > (NOW() AT TIME ZONE 'utc') - INTERVAL '1 hour'
+               ORDER BY q.added DESC
+               OFFSET $2 - 1 LIMIT 1"#,
+            self.id,
+            limit
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        match retry_after {
+            Some(seconds) => Err(CoreError::Ratelimited {
+                message: "You're submitting too many records too fast!".to_string(),
+                remaining: Duration::from_secs_f64(seconds.max(0.0)),
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Per-submitter statistics about the records submitted since the given point in time, with the
+/// most active submitters first
+///
+/// Since submitters are tied to IP addresses, the access is recorded in the audit log as having
+/// been made by the user with id `accessed_by`.
+pub async fn submitter_stats(since: NaiveDateTime, accessed_by: i32, connection: &mut PgConnection) -> Result<Vec<SubmitterStats>> {
+    sqlx::query!(
+        "INSERT INTO submitter_stats_accesses (userid, since) VALUES ($1, $2)",
+        accessed_by,
+        since
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(sqlx::query!(
+        r#"SELECT submitters.submitter_id, submitters.banned, COUNT(*) AS "submissions!",
+               COUNT(*) FILTER (WHERE records.status_ = 'APPROVED') AS "approved!",
+               COUNT(*) FILTER (WHERE records.status_ = 'REJECTED') AS "rejected!",
+               COUNT(DISTINCT records.player) AS "unique_players!"
+        FROM records
+        INNER JOIN submitters ON submitters.submitter_id = records.submitter
+        WHERE (SELECT MIN(time) FROM record_additions WHERE record_additions.id = records.id) >= $1
+        GROUP BY submitters.submitter_id
+        ORDER BY 3 DESC, submitters.submitter_id"#,
+        since
+    )
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .map(|row| SubmitterStats {
+        submitter: Submitter {
+            id: row.submitter_id,
+            banned: row.banned,
+        },
+        submissions: row.submissions,
+        approved: row.approved,
+        rejected: row.rejected,
+        unique_players: row.unique_players,
+    })
+    .collect())
 }
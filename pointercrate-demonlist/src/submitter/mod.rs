@@ -2,6 +2,7 @@ use derive_more::Display;
 use serde::Deserialize;
 use serde::Serialize;
 
+pub use get::submitter_stats;
 pub use paginate::SubmitterPagination;
 pub use patch::PatchSubmitter;
 use pointercrate_core::etag::Taggable;
@@ -19,3 +20,17 @@ pub struct Submitter {
 }
 
 impl Taggable for Submitter {}
+
+/// What a submitter has been submitting within some time window
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SubmitterStats {
+    #[serde(flatten)]
+    pub submitter: Submitter,
+
+    pub submissions: i64,
+    pub approved: i64,
+    pub rejected: i64,
+
+    /// The number of distinct players the submissions were for
+    pub unique_players: i64,
+}
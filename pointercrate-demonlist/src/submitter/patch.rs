@@ -11,32 +11,16 @@ pub struct PatchSubmitter {
 }
 
 impl Submitter {
-    pub async fn ban(&mut self, connection: &mut PgConnection) -> Result<()> {
-        sqlx::query!("UPDATE submitters SET banned = true WHERE submitter_id = $1", self.id)
-            .execute(&mut *connection)
-            .await?;
-
-        let deleted = sqlx::query!("DELETE FROM records WHERE submitter = $1 AND status_ = 'SUBMITTED'", self.id)
-            .execute(connection)
-            .await?;
-
-        info!(
-            "Banning submitter {} caused deletion of {} submissions",
-            self,
-            deleted.rows_affected()
-        );
-
-        self.banned = true;
-
-        Ok(())
-    }
-
-    pub async fn unban(&mut self, connection: &mut PgConnection) -> Result<()> {
-        sqlx::query!("UPDATE submitters SET banned = false WHERE submitter_id = $1", self.id)
+    /// Bans (or unbans) this submitter from submitting records
+    ///
+    /// Records this submitter already submitted are left alone (including those still in the queue),
+    /// only future submissions are rejected.
+    pub async fn set_banned(&mut self, banned: bool, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("UPDATE submitters SET banned = $1 WHERE submitter_id = $2", banned, self.id)
             .execute(connection)
             .await?;
 
-        self.banned = false;
+        self.banned = banned;
 
         Ok(())
     }
@@ -44,10 +28,8 @@ impl Submitter {
     pub async fn apply_patch(mut self, patch: PatchSubmitter, connection: &mut PgConnection) -> Result<Self> {
         info!("Patching submitter {} with {:?}", self, patch);
 
-        match patch.banned {
-            Some(true) => self.ban(connection).await?,
-            Some(false) => self.unban(connection).await?,
-            _ => (),
+        if let Some(banned) = patch.banned {
+            self.set_banned(banned, connection).await?;
         }
 
         Ok(self)
@@ -1,5 +1,6 @@
 use crate::error::{DemonlistError, Result};
 use pointercrate_core::error::CoreError;
+use serde::Serialize;
 use url::Url;
 
 const SCHEMES: [&str; 2] = ["http", "https"];
@@ -15,7 +16,16 @@ const EVERYPLAY_FORMAT: &str = "https://everyplay.com/videos/{video_id}' or'http
 const VIMEO_FORMAT: &str = "https://vimeo.com/{video_id}' or'https://www.vimeo.com/{video_id}";
 const BILIBILI_FORMAT: &str = "'https://www.bilibili.com/video/{video_id}' or'https://bilibili.com/video/{video_id}";
 
+/// Validates and normalizes the given video URL
+///
+/// Only URLs pointing to one of the hosts configured via [`crate::config::video_hosts`] are
+/// accepted. By default, these are the hosts we know how to normalize URLs for.
 pub fn validate(url: &str) -> Result<String> {
+    validate_against(url, crate::config::video_hosts().as_deref())
+}
+
+/// Allowed hosts that we have no special knowledge about are taken as-is, without normalizing the URL
+fn validate_against(url: &str, allowed_hosts: Option<&[String]>) -> Result<String> {
     let url = Url::parse(url).map_err(|_| DemonlistError::MalformedVideoUrl)?;
 
     if !SCHEMES.contains(&url.scheme()) {
@@ -27,6 +37,12 @@ pub fn validate(url: &str) -> Result<String> {
     }
 
     if let Some(host) = url.domain() {
+        if allowed_hosts.is_some_and(|allowed| !allowed.iter().any(|allowed| allowed == host)) {
+            return Err(DemonlistError::UnsupportedVideoHost {
+                host: host.to_string(),
+            });
+        }
+
         match host {
             "www.youtube.com" | "m.youtube.com" | "youtube.com" => {
                 if url.path() == "/watch" {
@@ -103,9 +119,138 @@ pub fn validate(url: &str) -> Result<String> {
                     Err(CoreError::InvalidUrlFormat { expected: VIMEO_FORMAT }.into())
                 }
             },
-            _ => Err(DemonlistError::UnsupportedVideoHost),
+            _ if allowed_hosts.is_some() => Ok(url.to_string()),
+            _ => Err(DemonlistError::UnsupportedVideoHost {
+                host: host.to_string(),
+            }),
         }
     } else {
         Err(CoreError::UnprocessableEntity.into())
     }
 }
+
+/// The different forms of a video URL a frontend might need for displaying a video
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct VideoPreview {
+    /// The video URL as it would be stored, see [`validate`]
+    pub video: String,
+    pub thumbnail: Option<String>,
+
+    /// URL for embedding the video in an iframe, if the host supports it without further parameters
+    pub embed: Option<String>,
+}
+
+/// Validates the given video URL, and derives everything needed to preview it
+pub fn preview(url: &str) -> Result<VideoPreview> {
+    let video = validate(url)?;
+
+    Ok(VideoPreview {
+        thumbnail: thumbnail(&video),
+        embed: embed(&video),
+        video,
+    })
+}
+
+/// Expects a video URL as normalized by [`validate`].
+fn embed(video: &str) -> Option<String> {
+    let url = Url::parse(video).ok()?;
+    let path_segments = url.path_segments()?.collect::<Vec<_>>();
+
+    match (url.domain()?, &path_segments[..]) {
+        ("www.youtube.com", _) => url
+            .query_pairs()
+            .find_map(|(key, value)| if key == "v" { Some(value) } else { None })
+            .map(|video_id| format!("https://www.youtube.com/embed/{}", video_id)),
+        ("vimeo.com", [video_id]) => Some(format!("https://player.vimeo.com/video/{}", video_id)),
+        ("www.bilibili.com", ["video", video_id]) => Some(format!("https://player.bilibili.com/player.html?bvid={}", video_id)),
+        _ => None,
+    }
+}
+
+/// The thumbnail YouTube generates for the given video, if it is a YouTube video
+///
+/// Expects a video URL as normalized by [`validate`].
+pub fn thumbnail(video: &str) -> Option<String> {
+    let url = Url::parse(video).ok()?;
+
+    if url.domain() != Some("www.youtube.com") || url.path() != "/watch" {
+        return None;
+    }
+
+    url.query_pairs()
+        .find_map(|(key, value)| if key == "v" { Some(value) } else { None })
+        .map(|video_id| format!("https://i.ytimg.com/vi/{}/mqdefault.jpg", video_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{preview, validate_against, VideoPreview};
+    use crate::error::DemonlistError;
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(
+            preview("https://youtu.be/dQw4w9WgXcQ"),
+            Ok(VideoPreview {
+                video: "https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string(),
+                thumbnail: Some("https://i.ytimg.com/vi/dQw4w9WgXcQ/mqdefault.jpg".to_string()),
+                embed: Some("https://www.youtube.com/embed/dQw4w9WgXcQ".to_string()),
+            })
+        );
+        assert_eq!(
+            preview("https://www.vimeo.com/123456"),
+            Ok(VideoPreview {
+                video: "https://vimeo.com/123456".to_string(),
+                thumbnail: None,
+                embed: Some("https://player.vimeo.com/video/123456".to_string()),
+            })
+        );
+        assert_eq!(
+            preview("https://example.com/video.mp4"),
+            Err(DemonlistError::UnsupportedVideoHost {
+                host: "example.com".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_video_host_allowlist() {
+        let allowed = ["www.youtube.com".to_string(), "videos.example.com".to_string()];
+
+        assert_eq!(
+            validate_against("https://www.youtube.com/watch?v=dQw4w9WgXcQ", Some(&allowed)),
+            Ok("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string())
+        );
+        assert_eq!(
+            validate_against("https://videos.example.com/bloodbath.mp4", Some(&allowed)),
+            Ok("https://videos.example.com/bloodbath.mp4".to_string())
+        );
+        assert_eq!(
+            validate_against("https://youtu.be/dQw4w9WgXcQ", Some(&allowed)),
+            Err(DemonlistError::UnsupportedVideoHost {
+                host: "youtu.be".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_video_host_with_port() {
+        let allowed = ["videos.example.com".to_string()];
+
+        // The port is not part of the host as far as the allowlist is concerned
+        assert_eq!(
+            validate_against("https://videos.example.com:8080/bloodbath.mp4", Some(&allowed)),
+            Ok("https://videos.example.com:8080/bloodbath.mp4".to_string())
+        );
+        assert_eq!(
+            validate_against("https://www.youtube.com:8080/watch?v=dQw4w9WgXcQ", None),
+            Ok("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string())
+        );
+        assert_eq!(
+            validate_against("https://example.com:8080/bloodbath.mp4", Some(&allowed)),
+            Err(DemonlistError::UnsupportedVideoHost {
+                host: "example.com".to_string()
+            })
+        );
+    }
+}
@@ -2,9 +2,10 @@
 use pointercrate_core::pool::PointercratePool;
 use pointercrate_core::error::CoreError;
 use pointercrate_core_api::{error::ErrorResponder, maintenance::MaintenanceFairing, preferences::PreferenceManager};
-use pointercrate_demonlist_api::GeolocationProvider;
+use pointercrate_demonlist_api::{CachingGeolocationProvider, GeolocationProvider};
 use rocket::{async_trait, serde, Request};
 use std::net::IpAddr;
+use std::time::Duration;
 use dotenv;
 
 /// A catcher for 404 errors (e.g. when a user tried to navigate to a URL that
@@ -98,9 +99,13 @@ async fn rocket() -> _ {
     let rocket = rocket.manage(preference_manager);
 
     // Register the geolocation provider, so that we can geolocate player claims. The type erasure is important, otherwise you'll get internal server errors!
-    let rocket = rocket.manage(Box::new(IpWhoIsGeolocationProvider) as Box<dyn GeolocationProvider>);
+    // Results are cached for a day, so that repeated claims from the same address do not count against the ipwho.is quota again.
+    // At most 10000 addresses are remembered at a time.
+    let geolocation_provider = CachingGeolocationProvider::new(IpWhoIsGeolocationProvider, Duration::from_secs(24 * 60 * 60), 10_000);
+    let rocket = rocket.manage(Box::new(geolocation_provider) as Box<dyn GeolocationProvider>);
 
     // Changing `false` to `true` here will put your website into "maintenance mode", which will disable all mutating request handlers and always return 503 SERVICE UNAVAILABLE responses for non-GET requests.
+    // Maintenance mode can also be toggled while the server is running, via the `Maintenance` switch this puts into managed state.
     let rocket = rocket.attach(MaintenanceFairing::new(false));
 
     // Register all the endpoints related to the demonlist to our server (this is
@@ -7,24 +7,33 @@ use pointercrate_demonlist::{
     player::{claim::PlayerClaim, FullPlayer},
     record::RecordStatus,
     submitter::Submitter,
-    LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
+    ENJOYMENT_CURATOR, LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
 use pointercrate_user::auth::{AuthenticatedUser, PasswordOrBrowser};
-use rocket::{http::Status, local::asynchronous::Client};
+use rocket::{http::Status, local::asynchronous::Client, Build, Rocket};
 use sqlx::{pool::PoolConnection, PgConnection, Pool, Postgres};
 use std::{net::IpAddr, str::FromStr};
 
 pub async fn setup_rocket(pool: Pool<Postgres>) -> (TestClient, PoolConnection<Postgres>) {
+    setup_rocket_with(pool, |rocket| rocket).await
+}
+
+/// Like [`setup_rocket`], but allows managing additional state (such as a custom
+/// `LevelIdResolver`) before the demonlist API is mounted
+pub async fn setup_rocket_with(
+    pool: Pool<Postgres>, customize: impl FnOnce(Rocket<Build>) -> Rocket<Build>,
+) -> (TestClient, PoolConnection<Postgres>) {
     let _ = dotenv::dotenv();
 
     let mut connection = pool.acquire().await.unwrap();
 
-    let permissions = PermissionsManager::new(vec![LIST_HELPER, LIST_MODERATOR, LIST_ADMINISTRATOR])
+    let permissions = PermissionsManager::new(vec![LIST_HELPER, LIST_MODERATOR, LIST_ADMINISTRATOR, ENJOYMENT_CURATOR])
         .assigns(LIST_ADMINISTRATOR, LIST_MODERATOR)
         .implies(LIST_ADMINISTRATOR, LIST_MODERATOR)
-        .implies(LIST_MODERATOR, LIST_HELPER);
+        .implies(LIST_MODERATOR, LIST_HELPER)
+        .implies(LIST_MODERATOR, ENJOYMENT_CURATOR);
 
-    let rocket = pointercrate_demonlist_api::setup(rocket::build().manage(PointercratePool::from(pool)))
+    let rocket = pointercrate_demonlist_api::setup(customize(rocket::build().manage(PointercratePool::from(pool))))
         .manage(permissions)
         .manage(PreferenceManager::default().preference("locale", "en"));
 
@@ -40,7 +49,8 @@ pub async fn add_demon(
     name: impl Into<String>, position: i16, requirement: i16, verifier_id: i32, publisher_id: i32, connection: &mut PgConnection,
 ) -> i32 {
     sqlx::query!(
-        "INSERT INTO demons (name, position, requirement, verifier, publisher) VALUES ($1::TEXT::CITEXT, $2, $3, $4, $5) RETURNING id",
+        "INSERT INTO demons (name, position, requirement, verifier, publisher, difficulty) VALUES ($1::TEXT::CITEXT, $2, $3, $4, $5, \
+         'extreme') RETURNING id",
         name.into(),
         position,
         requirement,
@@ -114,7 +124,7 @@ impl TestClient {
         &self, auth_context: &AuthenticatedUser<PasswordOrBrowser>, name: impl Into<String>, position: i16, requirement: i16,
         verifier: impl Into<String>, publisher: impl Into<String>,
     ) -> FullDemon {
-        self.post("/api/v2/demons/", &serde_json::json!({"name": name.into(), "position": position, "requirement": requirement, "verifier": verifier.into(), "publisher": publisher.into(), "creators": []}))
+        self.post("/api/v2/demons/", &serde_json::json!({"name": name.into(), "position": position, "requirement": requirement, "verifier": verifier.into(), "publisher": publisher.into(), "creators": [], "difficulty": "extreme"}))
             .expect_status(Status::Created)
             .authorize_as(auth_context)
             .get_success_result()
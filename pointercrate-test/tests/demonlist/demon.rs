@@ -1,10 +1,17 @@
-use pointercrate_core::pagination::PaginationParameters;
+use pointercrate_core::{etag::Taggable, pagination::PaginationParameters};
 use pointercrate_core_api::pagination::LinksBuilder;
 use pointercrate_demonlist::{
-    demon::{Demon, DemonPositionPagination},
-    player::DatabasePlayer,
-    LIST_MODERATOR,
+    demon::{
+        current_list, total_awardable_points, DefaultScoreFormula, Demon, DemonNeighbors, DemonPosition, DemonPositionPagination,
+        FullDemon, MinimalDemon, PostDemon, ScoreBand, ScoreFormula, ScoreFormulaDescription, SubmissionState,
+    },
+    error::DemonlistError,
+    player::{DatabasePlayer, FullPlayer},
+    record::{approved_records_on, note::notes_on, FullRecord, RecordOrder, RecordStatus},
+    submitter::Submitter,
+    LIST_ADMINISTRATOR, LIST_MODERATOR,
 };
+use pointercrate_demonlist_api::{LevelIdResolution, LevelIdResolver};
 use rocket::http::Status;
 use sqlx::{Pool, Postgres};
 
@@ -14,7 +21,7 @@ async fn test_add_demon_ratelimits(pool: Pool<Postgres>) {
 
     let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
 
-    let demon = serde_json::json! {{"name": "Bloodbath", "requirement": 90, "position": 1, "verifier": "Riot", "publisher": "Riot", "creators": [], "level_id": 10565740}};
+    let demon = serde_json::json! {{"name": "Bloodbath", "requirement": 90, "position": 1, "verifier": "Riot", "publisher": "Riot", "creators": [], "level_id": 10565740, "difficulty": "extreme"}};
 
     // first one should succeed
     clnt.post("/api/v2/demons/", &demon)
@@ -34,6 +41,127 @@ async fn test_add_demon_ratelimits(pool: Pool<Postgres>) {
     assert_eq!(result["code"].as_i64(), Some(42900))
 }
 
+#[sqlx::test(migrations = "../migrations")]
+async fn test_requirement_raise_and_lower(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+
+    // Rejected for some other reason while it met the requirement
+    let rejected = pointercrate_test::demonlist::add_simple_record(60, player.id, demon, RecordStatus::Rejected, &mut connection).await;
+    let submitted = pointercrate_test::demonlist::add_simple_record(65, player.id, demon, RecordStatus::Submitted, &mut connection).await;
+
+    let mut bloodbath = Demon::by_id(demon, &mut connection).await.unwrap();
+
+    bloodbath.set_requirement(70, true, &mut connection).await.unwrap();
+
+    assert_eq!(FullRecord::by_id(submitted, &mut connection).await.unwrap().status, RecordStatus::Rejected);
+    assert_eq!(FullRecord::by_id(rejected, &mut connection).await.unwrap().status, RecordStatus::Rejected);
+
+    bloodbath.set_requirement(55, true, &mut connection).await.unwrap();
+
+    // Only the record that was rejected for not meeting the requirement comes back
+    assert_eq!(FullRecord::by_id(submitted, &mut connection).await.unwrap().status, RecordStatus::Submitted);
+    assert_eq!(notes_on(submitted, false, &mut connection).await.unwrap().len(), 1);
+    assert_eq!(FullRecord::by_id(rejected, &mut connection).await.unwrap().status, RecordStatus::Rejected);
+    assert!(notes_on(rejected, false, &mut connection).await.unwrap().is_empty());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_patch_archived(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+    pointercrate_test::demonlist::add_demon("Slaughterhouse", 2, 50, verifier.id, verifier.id, &mut connection).await;
+    pointercrate_test::demonlist::add_demon("Tartarus", 3, 50, verifier.id, verifier.id, &mut connection).await;
+
+    let submitted = pointercrate_test::demonlist::add_simple_record(60, player.id, demon, RecordStatus::Submitted, &mut connection).await;
+
+    let seen: FullDemon = clnt
+        .get(format!("/api/v2/demons/{}/", demon))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    // Archived demons are kept below all others
+    let archived: FullDemon = clnt
+        .patch(format!("/api/v2/demons/{}/", demon), &serde_json::json!({"archived": true}))
+        .authorize_as(&moderator)
+        .header("If-Match", seen.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(archived.position(), 3);
+    assert_eq!(archived.demon.base.submission_state(&mut connection).await.unwrap(), SubmissionState::Archived);
+
+    // By default, submissions already in the queue can still be reviewed
+    assert_eq!(FullRecord::by_id(submitted, &mut connection).await.unwrap().status, RecordStatus::Submitted);
+
+    let unarchived: FullDemon = clnt
+        .patch(format!("/api/v2/demons/{}/", demon), &serde_json::json!({"archived": false}))
+        .authorize_as(&moderator)
+        .header("If-Match", archived.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(unarchived.position(), 3);
+    assert_eq!(unarchived.demon.base.submission_state(&mut connection).await.unwrap(), SubmissionState::Open);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_archive_rejects_pending(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+
+    let submitted = pointercrate_test::demonlist::add_simple_record(60, player.id, demon, RecordStatus::Submitted, &mut connection).await;
+    let approved = pointercrate_test::demonlist::add_simple_record(100, verifier.id, demon, RecordStatus::Approved, &mut connection).await;
+
+    let mut bloodbath = Demon::by_id(demon, &mut connection).await.unwrap();
+
+    bloodbath.set_archived_with(true, true, &mut connection).await.unwrap();
+
+    assert_eq!(FullRecord::by_id(submitted, &mut connection).await.unwrap().status, RecordStatus::Rejected);
+    assert_eq!(notes_on(submitted, false, &mut connection).await.unwrap().len(), 1);
+
+    // Only the queue is cleared out
+    assert_eq!(FullRecord::by_id(approved, &mut connection).await.unwrap().status, RecordStatus::Approved);
+    assert!(notes_on(approved, false, &mut connection).await.unwrap().is_empty());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_requirement_raise_without_reevaluation(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+
+    let rejected = pointercrate_test::demonlist::add_simple_record(60, player.id, demon, RecordStatus::Rejected, &mut connection).await;
+    let submitted = pointercrate_test::demonlist::add_simple_record(65, player.id, demon, RecordStatus::Submitted, &mut connection).await;
+
+    let mut bloodbath = Demon::by_id(demon, &mut connection).await.unwrap();
+
+    bloodbath.set_requirement(70, false, &mut connection).await.unwrap();
+
+    // The submission can never be approved anymore, but the rejection stays around
+    assert!(FullRecord::by_id(submitted, &mut connection).await.is_err());
+    assert_eq!(FullRecord::by_id(rejected, &mut connection).await.unwrap().status, RecordStatus::Rejected);
+
+    bloodbath.set_requirement(55, false, &mut connection).await.unwrap();
+
+    assert_eq!(FullRecord::by_id(rejected, &mut connection).await.unwrap().status, RecordStatus::Rejected);
+}
+
 #[sqlx::test(migrations = "../migrations")]
 async fn test_demon_pagination(pool: Pool<Postgres>) {
     /// The URL of the endpoint we are testing
@@ -93,7 +221,11 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
     assert_eq!(demons.len(), 1);
     assert_eq!(demons[0].base.id, id2);
 
-    let expected = LinksBuilder::new(URL).with_first(0).with_last(4).with_next(2).with_previous(2);
+    let expected = LinksBuilder::new(URL)
+        .with_first(0)
+        .with_last(4)
+        .with_anchored_next(2, Some(id2))
+        .with_anchored_previous(2, Some(id2));
     assert_eq!(links, expected.generate(&base).unwrap());
 
     // The same, but in reverse Get the demon at position 2 via before=3 and limit=1. We should get both "next" and "previous" pages
@@ -115,7 +247,11 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
     assert_eq!(demons.len(), 1);
     assert_eq!(demons[0].base.id, id2);
 
-    let expected = LinksBuilder::new(URL).with_first(0).with_last(4).with_next(2).with_previous(2);
+    let expected = LinksBuilder::new(URL)
+        .with_first(0)
+        .with_last(4)
+        .with_anchored_next(2, Some(id2))
+        .with_anchored_previous(2, Some(id2));
     assert_eq!(links, expected.generate(&base).unwrap());
 
     // Query an empty page by only setting before=1. We should still get a "next" link, with after=0 (e.g. before minus one),
@@ -195,8 +331,1696 @@ async fn test_demon_pagination(pool: Pool<Postgres>) {
     let expected = LinksBuilder::new(URL)
         .with_first(0)
         .with_last(4)
-        .with_next(3) // FIXME: This `next` link should not have been returned (currently we always return a "next" link if a `before` parameter is set though)
-        .with_previous(2);
+        .with_anchored_next(3, Some(id3)) // FIXME: This `next` link should not have been returned (currently we always return a "next" link if a `before` parameter is set though)
+        .with_anchored_previous(2, Some(id2));
 
     assert_eq!(links, expected.generate(&base).unwrap());
 }
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_pagination_stable_across_insertions(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let mut expected = Vec::new();
+
+    for position in 1..=5 {
+        let name = format!("Bloodbath {}", position);
+
+        expected.push(pointercrate_test::demonlist::add_demon(&name, position, 100, player.id, player.id, &mut connection).await);
+    }
+
+    let next_link = |links: &str| {
+        links
+            .split(',')
+            .find(|link| link.ends_with("rel=next"))
+            .map(|link| link[1..link.find('>').unwrap()].to_string())
+    };
+
+    let (first_page, links) = clnt.get("/api/v2/demons/listed/?limit=2").get_pagination_result::<Demon>().await;
+    let mut seen: Vec<i32> = first_page.iter().map(|demon| demon.base.id).collect();
+
+    // Someone places a new demon at the top of the list while we're still scrolling
+    sqlx::query!("UPDATE demons SET position = position + 1")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+    pointercrate_test::demonlist::add_demon("Sonic Wave", 1, 60, player.id, player.id, &mut connection).await;
+
+    let mut next = next_link(&links);
+
+    while let Some(url) = next {
+        let (page, links) = clnt.get(url).get_pagination_result::<Demon>().await;
+
+        seen.extend(page.iter().map(|demon| demon.base.id));
+        next = next_link(&links);
+    }
+
+    assert_eq!(seen, expected);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_pagination_by_difficulty(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let mut demons = Vec::new();
+
+    for (position, name, difficulty) in [
+        (1, "Bloodbath", "silent"),
+        (2, "Sonic Wave", "extreme"),
+        (3, "Cataclysm", "insane"),
+        (4, "Erebus", "easy"),
+    ] {
+        let id = pointercrate_test::demonlist::add_demon(name, position, 100, player.id, player.id, &mut connection).await;
+
+        sqlx::query!("UPDATE demons SET difficulty = $1::TEXT::level_difficulty WHERE id = $2", difficulty, id)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+
+        demons.push(id);
+    }
+
+    for (filter, expected) in [
+        ("difficulty__gte=extreme", vec![demons[0], demons[1]]),
+        ("difficulty__lte=insane", vec![demons[2], demons[3]]),
+        ("difficulty__gte=easy&difficulty__lte=extreme", vec![demons[1], demons[2], demons[3]]),
+        ("difficulty=insane", vec![demons[2]]),
+    ] {
+        let (result, _) = clnt
+            .get(format!("/api/v2/demons/listed/?{}", filter))
+            .get_pagination_result::<Demon>()
+            .await;
+
+        assert_eq!(result.iter().map(|demon| demon.base.id).collect::<Vec<_>>(), expected, "{}", filter);
+    }
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_total_points(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+
+    let result: serde_json::Value = clnt.get("/api/v1/scoring/total/").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(result["total"].as_f64(), Some(0.0));
+
+    // Adding a demon through the API must invalidate the cached total
+    let demon = clnt.add_demon(&user, "Bloodbath", 1, 87, "Riot", "Riot").await;
+
+    let result: serde_json::Value = clnt.get("/api/v1/scoring/total/").expect_status(Status::Ok).get_result().await;
+
+    assert!((result["total"].as_f64().unwrap() - demon.demon.score(&DefaultScoreFormula, 100)).abs() < 1e-9);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_total_points_skip_grace_period(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let demon = clnt.add_demon(&user, "Bloodbath", 1, 87, "Riot", "Riot").await;
+
+    sqlx::query!(
+        "UPDATE demons SET scores_from = (NOW() AT TIME ZONE 'utc') + INTERVAL '1 hour' WHERE id = $1",
+        demon.demon.base.id
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    // Like stored scores, the total does not include demons that do not give points yet
+    assert_eq!(total_awardable_points(&mut connection).await.unwrap(), 0.0);
+
+    sqlx::query!(
+        "UPDATE demons SET scores_from = (NOW() AT TIME ZONE 'utc') - INTERVAL '1 hour' WHERE id = $1",
+        demon.demon.base.id
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    assert!(pointercrate_demonlist::player::end_grace_periods(&mut connection).await.unwrap());
+    assert!((total_awardable_points(&mut connection).await.unwrap() - demon.demon.score(&DefaultScoreFormula, 100)).abs() < 1e-9);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_total_points_difficulty_change(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let demon = clnt.add_demon(&user, "Bloodbath", 1, 87, "Riot", "Riot").await;
+
+    // What storing a configured `extreme:2.0` on startup would have done
+    sqlx::query!("INSERT INTO difficulty_multipliers (difficulty, multiplier) VALUES ('extreme', 2.0)")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let completion = demon.demon.score(&DefaultScoreFormula, 100);
+    let result: serde_json::Value = clnt.get("/api/v1/scoring/total/").expect_status(Status::Ok).get_result().await;
+
+    assert!((result["total"].as_f64().unwrap() - 2.0 * completion).abs() < 1e-9);
+
+    // Changing the difficulty changes the multiplier, which must invalidate the cached total
+    let demon: FullDemon = clnt
+        .get(format!("/api/v2/demons/{}/", demon.demon.base.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    clnt.patch(format!("/api/v2/demons/{}/", demon.demon.base.id), &serde_json::json!({"difficulty": "insane"}))
+        .authorize_as(&user)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let result: serde_json::Value = clnt.get("/api/v1/scoring/total/").expect_status(Status::Ok).get_result().await;
+
+    assert!((result["total"].as_f64().unwrap() - completion).abs() < 1e-9);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_scoring_config(pool: Pool<Postgres>) {
+    let (clnt, _) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let result: serde_json::Value = clnt.get("/api/v1/scoring/config/").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(result["extended_list_size"], 100);
+
+    // The bands of the default formula cover the scoring positions without gaps
+    let bands = result["formula"]["bands"].as_array().unwrap();
+    let mut next = 1;
+
+    for band in bands {
+        assert_eq!(band["first_position"], next);
+        next = band["last_position"].as_i64().unwrap() + 1;
+    }
+
+    assert_eq!(next, 151);
+
+    // Without configured multipliers, every tier is worth exactly what its position gives
+    assert_eq!(result["difficulty_multipliers"]["extreme"], 1.0);
+    assert_eq!(result["difficulty_multipliers"].as_object().unwrap().len(), 9);
+}
+
+struct FlatFormula;
+
+impl ScoreFormula for FlatFormula {
+    fn score(&self, _position: i16, progress: i16, requirement: i16) -> f64 {
+        if progress < requirement {
+            0.0
+        } else {
+            1.0
+        }
+    }
+
+    fn describe(&self) -> ScoreFormulaDescription {
+        ScoreFormulaDescription {
+            bands: vec![ScoreBand {
+                first_position: 1,
+                last_position: 150,
+                score: "1".to_string(),
+            }],
+            progress: "score".to_string(),
+        }
+    }
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_custom_score_formula(pool: Pool<Postgres>) {
+    let (clnt, mut connection) =
+        pointercrate_test::demonlist::setup_rocket_with(pool, |rocket| rocket.manage(Box::new(FlatFormula) as Box<dyn ScoreFormula>)).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let demon = clnt.add_demon(&user, "Bloodbath", 1, 87, "Riot", "Riot").await;
+
+    let result: serde_json::Value = clnt.get("/api/v1/scoring/total/").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(result["total"].as_f64(), Some(1.0));
+
+    // The verifier's stored score follows the managed formula as well
+    let verifier: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}/", demon.demon.verifier.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(verifier.player.score, 1.0);
+
+    // And so does the published formula
+    let result: serde_json::Value = clnt.get("/api/v1/scoring/config/").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(result["formula"], serde_json::to_value(FlatFormula.describe()).unwrap());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_placeholder_thumbnail(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 100, player.id, player.id, &mut connection).await;
+
+    let fetched: serde_json::Value = clnt
+        .get(format!("/api/v2/demons/{}/", demon))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+    let thumbnail = fetched["thumbnail"].as_str().unwrap();
+
+    assert_eq!(thumbnail, pointercrate_demonlist::config::placeholder_thumbnail());
+
+    // The default placeholder is served by the demonlist API itself
+    clnt.get(thumbnail)
+        .expect_status(Status::Ok)
+        .expect_header("Content-Type", "image/svg+xml")
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_neighbors(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    let id1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 100, player.id, player.id, &mut connection).await;
+    let id2 = pointercrate_test::demonlist::add_demon("Bloodbath 2", 2, 100, player.id, player.id, &mut connection).await;
+    let id3 = pointercrate_test::demonlist::add_demon("Bloodbath 3", 3, 100, player.id, player.id, &mut connection).await;
+
+    let neighbors: DemonNeighbors = clnt
+        .get(format!("/api/v2/demons/{}/neighbors/", id2))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(neighbors.previous.map(|demon| demon.id), Some(id1));
+    assert_eq!(neighbors.next.map(|demon| demon.id), Some(id3));
+
+    let neighbors: DemonNeighbors = clnt
+        .get(format!("/api/v2/demons/{}/neighbors/", id1))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(neighbors.previous, None);
+    assert_eq!(neighbors.next.map(|demon| demon.id), Some(id2));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_neighbors_skip_archived(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    let id1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 100, player.id, player.id, &mut connection).await;
+    let id2 = pointercrate_test::demonlist::add_demon("Bloodbath 2", 2, 100, player.id, player.id, &mut connection).await;
+    let id3 = pointercrate_test::demonlist::add_demon("Bloodbath 3", 3, 100, player.id, player.id, &mut connection).await;
+
+    sqlx::query!("UPDATE demons SET archived = TRUE WHERE id = $1", id2)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let neighbors: DemonNeighbors = clnt
+        .get(format!("/api/v2/demons/{}/neighbors/", id1))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(neighbors.previous, None);
+    assert_eq!(neighbors.next.map(|demon| demon.id), Some(id3));
+
+    let neighbors: DemonNeighbors = clnt
+        .get(format!("/api/v2/demons/{}/neighbors/", id3))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(neighbors.previous.map(|demon| demon.id), Some(id1));
+    assert_eq!(neighbors.next, None);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_add_creator(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 100, player.id, player.id, &mut connection).await;
+
+    let url = format!("/api/v2/demons/{}/creators/", demon);
+
+    let result: serde_json::Value = clnt
+        .post(&url, &serde_json::json!({"creator": "   "}))
+        .authorize_as(&user)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"], 42242);
+
+    clnt.post(&url, &serde_json::json!({"creator": "stardust1971"}))
+        .authorize_as(&user)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+
+    // Same player, just with different whitespace and casing
+    let result: serde_json::Value = clnt
+        .post(&url, &serde_json::json!({"creator": " Stardust1971 "}))
+        .authorize_as(&user)
+        .expect_status(Status::Conflict)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"], 40905);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_creator_limit(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 100, player.id, player.id, &mut connection).await;
+
+    // One creator short of the default limit of 100
+    sqlx::query!("INSERT INTO players (name) SELECT 'creator' || n FROM generate_series(1, 99) AS n")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+    sqlx::query!("INSERT INTO creators (demon, creator) SELECT $1, id FROM players WHERE name LIKE 'creator%'", demon)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let url = format!("/api/v2/demons/{}/creators/", demon);
+
+    clnt.post(&url, &serde_json::json!({"creator": "stardust1971"}))
+        .authorize_as(&user)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+
+    let result: serde_json::Value = clnt
+        .post(&url, &serde_json::json!({"creator": "stardust1972"}))
+        .authorize_as(&user)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"], 42243);
+    assert_eq!(result["data"]["maximum"], 100);
+
+    let creators = sqlx::query_scalar!("SELECT COUNT(*) FROM creators WHERE demon = $1", demon)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(creators, Some(100));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_reorder_creators(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+
+    let demon = serde_json::json! {{"name": "Bloodbath", "requirement": 90, "position": 1, "verifier": "Riot", "publisher": "Riot", "creators": ["A", "B", "C"], "difficulty": "extreme"}};
+
+    let demon: FullDemon = clnt
+        .post("/api/v2/demons/", &demon)
+        .authorize_as(&moderator)
+        .expect_status(Status::Created)
+        .get_success_result()
+        .await;
+
+    let creator_names = |demon: &FullDemon| demon.creators.iter().map(|creator| creator.name.clone()).collect::<Vec<_>>();
+
+    // Creators are initially ordered the way they were added
+    assert_eq!(creator_names(&demon), ["A", "B", "C"]);
+
+    let c = demon.creators.iter().find(|creator| creator.name == "C").unwrap();
+
+    let creators: Vec<DatabasePlayer> = clnt
+        .patch(
+            format!("/api/v2/demons/{}/creators/{}/", demon.demon.base.id, c.id),
+            &serde_json::json!({"priority": 0}),
+        )
+        .authorize_as(&moderator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(creators.iter().map(|creator| creator.name.clone()).collect::<Vec<_>>(), ["C", "A", "B"]);
+
+    let demon: FullDemon = clnt
+        .get(format!("/api/v2/demons/{}/", demon.demon.base.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(creator_names(&demon), ["C", "A", "B"]);
+
+    // Priorities past the end move the creator to the back
+    let a = demon.creators.iter().find(|creator| creator.name == "A").unwrap();
+
+    clnt.patch(
+        format!("/api/v2/demons/{}/creators/{}/", demon.demon.base.id, a.id),
+        &serde_json::json!({"priority": 10}),
+    )
+    .authorize_as(&moderator)
+    .expect_status(Status::Ok)
+    .execute()
+    .await;
+
+    let demon: FullDemon = clnt
+        .get(format!("/api/v2/demons/{}/", demon.demon.base.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(creator_names(&demon), ["C", "B", "A"]);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_history(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+    let record = pointercrate_test::demonlist::add_simple_record(100, player.id, demon, RecordStatus::Submitted, &mut connection).await;
+
+    let url = format!("/api/v2/demons/{}/history/", demon);
+
+    clnt.get(&url).expect_status(Status::Unauthorized).execute().await;
+
+    let history: Vec<serde_json::Value> = clnt.get(&url).authorize_as(&user).expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["source"], "demon");
+    assert_eq!(history[0]["entry"]["type"], "Addition");
+    assert_eq!(history[1]["source"], "record");
+    assert_eq!(history[1]["entry"]["id"], record);
+
+    let history: Vec<serde_json::Value> = clnt
+        .get(format!("{}?limit=1", url))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["source"], "demon");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_history_pages(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+
+    sqlx::query!("UPDATE demon_additions SET time = '2024-01-01' WHERE id = $1", demon)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let mut records = Vec::new();
+
+    for name in ["stardust1971", "stardust1972", "stardust1973"] {
+        let player = DatabasePlayer::by_name_or_create(name, &mut connection).await.unwrap();
+        let record = pointercrate_test::demonlist::add_simple_record(100, player.id, demon, RecordStatus::Submitted, &mut connection).await;
+
+        // As if all records were added in the same transaction
+        sqlx::query!("UPDATE record_additions SET time = '2024-01-02' WHERE id = $1", record)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+
+        records.push(record);
+    }
+
+    let url = format!("/api/v2/demons/{}/history/", demon);
+    let ids = |page: &[serde_json::Value]| {
+        page.iter()
+            .map(|entry| (entry["source"].as_str().unwrap().to_owned(), entry["entry"]["id"].as_i64().unwrap() as i32))
+            .collect::<Vec<_>>()
+    };
+    let cursor = |direction: &str, entry: &serde_json::Value| {
+        format!("{0}={1}&{0}_entry={2}", direction, entry["entry"]["time"].as_str().unwrap(), entry["entry"]["entry_id"])
+    };
+
+    let first: Vec<serde_json::Value> = clnt
+        .get(format!("{}?limit=2", url))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(ids(&first), vec![("demon".to_owned(), demon), ("record".to_owned(), records[0])]);
+
+    // The next page starts in the middle of the events sharing a time, without skipping any of them
+    let second: Vec<serde_json::Value> = clnt
+        .get(format!("{}?limit=2&{}", url, cursor("after", &first[1])))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(ids(&second), vec![("record".to_owned(), records[1]), ("record".to_owned(), records[2])]);
+
+    let third: Vec<serde_json::Value> = clnt
+        .get(format!("{}?limit=2&{}", url, cursor("after", &second[1])))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert!(third.is_empty());
+
+    // Paging backwards yields the events right before the given one, still oldest first
+    let previous: Vec<serde_json::Value> = clnt
+        .get(format!("{}?limit=2&{}", url, cursor("before", &second[1])))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(ids(&previous), vec![("record".to_owned(), records[0]), ("record".to_owned(), records[1])]);
+
+    // Without an entry id, a bound excludes everything at its time
+    let later: Vec<serde_json::Value> = clnt
+        .get(format!("{}?after={}", url, first[1]["entry"]["time"].as_str().unwrap()))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert!(later.is_empty());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_equal_progress_ordered_by_approval(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+
+    let mut records = Vec::new();
+
+    for name in ["stardust1971", "stardust1972", "stardust1973"] {
+        let player = DatabasePlayer::by_name_or_create(name, &mut connection).await.unwrap();
+
+        records.push(pointercrate_test::demonlist::add_simple_record(100, player.id, demon, RecordStatus::Approved, &mut connection).await);
+    }
+
+    // The last record was approved first, and the first record has no approval date at all
+    for (record, approved_at) in [(records[1], "2024-02-01"), (records[2], "2024-01-01")] {
+        sqlx::query!("UPDATE records SET approved_at = $1::TEXT::TIMESTAMP WHERE id = $2", approved_at, record)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+    }
+
+    let expected = vec![records[2], records[1], records[0]];
+
+    // Repeated requests must agree on the order
+    for _ in 0..2 {
+        let listed: Vec<serde_json::Value> = clnt
+            .get(format!("/api/v2/demons/{}/records/", demon))
+            .expect_status(Status::Ok)
+            .get_result()
+            .await;
+
+        assert_eq!(listed.iter().map(|record| record["id"].as_i64().unwrap() as i32).collect::<Vec<_>>(), expected);
+    }
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_peak_position(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 3, 50, player.id, player.id, &mut connection).await;
+
+    let url = format!("/api/v2/demons/{}/", demon);
+
+    let fetched: serde_json::Value = clnt.get(&url).expect_status(Status::Ok).get_success_result().await;
+
+    assert_eq!(fetched["peak_position"]["position"], 3);
+    assert!(fetched["peak_position"]["reached"].is_string());
+
+    sqlx::query!("UPDATE demons SET position = 1 WHERE id = $1", demon)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE demons SET position = 2 WHERE id = $1", demon)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let fetched: serde_json::Value = clnt.get(&url).expect_status(Status::Ok).get_success_result().await;
+
+    assert_eq!(fetched["position"], 2);
+    assert_eq!(fetched["peak_position"]["position"], 1);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_verified_by_banned(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let mut banned = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+
+    pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+    let verified = pointercrate_test::demonlist::add_demon("Bloodbath 2", 2, 50, banned.id, player.id, &mut connection).await;
+    let published = pointercrate_test::demonlist::add_demon("Bloodbath 3", 3, 50, player.id, banned.id, &mut connection).await;
+
+    banned.ban(&mut connection).await.unwrap();
+
+    let demons: Vec<serde_json::Value> = clnt
+        .get("/api/v2/demons/verified-by-banned/")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    let ids: Vec<_> = demons.iter().map(|demon| demon["id"].as_i64().unwrap() as i32).collect();
+
+    assert_eq!(ids, vec![verified, published]);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demons_by_enjoyment(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    let bloodbath = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+    let slaughterhouse = pointercrate_test::demonlist::add_demon("Slaughterhouse", 2, 50, verifier.id, verifier.id, &mut connection).await;
+    let tartarus = pointercrate_test::demonlist::add_demon("Tartarus", 3, 50, verifier.id, verifier.id, &mut connection).await;
+    // Never rated, so it does not show up at all
+    pointercrate_test::demonlist::add_demon("Acheron", 4, 50, verifier.id, verifier.id, &mut connection).await;
+
+    for (demon, enjoyment) in [(bloodbath, 6), (slaughterhouse, 9), (tartarus, 6)] {
+        let record = pointercrate_test::demonlist::add_simple_record(100, player.id, demon, RecordStatus::Approved, &mut connection).await;
+
+        sqlx::query!("UPDATE records SET enjoyment = $1 WHERE id = $2", enjoyment, record)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+    }
+
+    // Rejected records do not count towards the mean
+    let rejected =
+        pointercrate_test::demonlist::add_simple_record(60, verifier.id, bloodbath, RecordStatus::Rejected, &mut connection).await;
+
+    sqlx::query!("UPDATE records SET enjoyment = 10 WHERE id = $1", rejected)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let ranked: Vec<serde_json::Value> = clnt
+        .get("/api/v2/demons/?sort=enjoyment")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(
+        ranked.iter().map(|demon| demon["id"].as_i64().unwrap() as i32).collect::<Vec<_>>(),
+        vec![slaughterhouse, bloodbath, tartarus]
+    );
+    assert_eq!(ranked[0]["enjoyment"], 9.0);
+    assert_eq!(ranked[1]["ratings"], 1);
+
+    // Continuing after bloodbath must still include tartarus, which has the same enjoyment
+    let next_page: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v2/demons/?sort=enjoyment&after_enjoyment=6&after_id={}", bloodbath))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(next_page.len(), 1);
+    assert_eq!(next_page[0]["id"], tartarus);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_records_by_enjoyment(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+
+    let mut records = Vec::new();
+
+    for (name, progress, enjoyment) in [("stardust1971", 100, Some(3)), ("stardust1972", 80, Some(9)), ("stardust1973", 90, None)] {
+        let player = DatabasePlayer::by_name_or_create(name, &mut connection).await.unwrap();
+        let record =
+            pointercrate_test::demonlist::add_simple_record(progress, player.id, demon, RecordStatus::Approved, &mut connection).await;
+
+        sqlx::query!("UPDATE records SET enjoyment = $1 WHERE id = $2", enjoyment, record)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+
+        records.push(record);
+    }
+
+    let by_progress: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v2/demons/{}/records/", demon))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(
+        by_progress.iter().map(|record| record["id"].as_i64().unwrap() as i32).collect::<Vec<_>>(),
+        vec![records[0], records[2], records[1]]
+    );
+
+    // Enjoyment ratings are public by default, so they can be used for ordering. Unrated records come last
+    let by_enjoyment: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v2/demons/{}/records/?order=enjoyment", demon))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(
+        by_enjoyment.iter().map(|record| record["id"].as_i64().unwrap() as i32).collect::<Vec<_>>(),
+        vec![records[1], records[0], records[2]]
+    );
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_records_order(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+
+    let mut records = Vec::new();
+
+    for (name, progress, approved_at) in [
+        ("stardust1971", 100, "2020-01-02T00:00:00"),
+        ("stardust1972", 100, "2020-01-01T00:00:00"),
+        ("stardust1973", 90, "2019-01-01T00:00:00"),
+        ("stardust1974", 40, "2018-01-01T00:00:00"),
+    ] {
+        let player = DatabasePlayer::by_name_or_create(name, &mut connection).await.unwrap();
+        let record =
+            pointercrate_test::demonlist::add_simple_record(progress, player.id, demon, RecordStatus::Approved, &mut connection).await;
+
+        sqlx::query!("UPDATE records SET approved_at = $1::TEXT::TIMESTAMP WHERE id = $2", approved_at, record)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+
+        records.push(record);
+    }
+
+    let ids = |records: &[serde_json::Value]| records.iter().map(|record| record["id"].as_i64().unwrap() as i32).collect::<Vec<_>>();
+
+    // Equal progress is broken by approval time, and records below the requirement are not listed
+    let by_progress: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v2/demons/{}/records/", demon))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(ids(&by_progress), vec![records[1], records[0], records[2]]);
+
+    let by_date: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v2/demons/{}/records/?order=date", demon))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(ids(&by_date), vec![records[2], records[1], records[0]]);
+
+    // The demon itself lists its records in the default order
+    let full_demon: serde_json::Value = clnt
+        .get(format!("/api/v2/demons/{}/", demon))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(ids(full_demon["records"].as_array().unwrap()), ids(&by_progress));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_list_positions(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Slaughterhouse", 2, 50, verifier.id, verifier.id, &mut connection).await;
+
+    let response = clnt.get("/api/v2/demons/positions/").expect_status(Status::Ok).execute().await;
+    let etag = response.headers().get_one("etag").unwrap().to_string();
+    let body: serde_json::Value = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+
+    assert_eq!(body["data"], serde_json::json!([{"id": demon1, "position": 1}, {"id": demon2, "position": 2}]));
+
+    clnt.get("/api/v2/demons/positions/")
+        .header("If-None-Match", etag)
+        .expect_status(Status::NotModified)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_csv_export(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let publisher = DatabasePlayer::by_name_or_create("Riot, \"the\" verifier", &mut connection).await.unwrap();
+    let formula = DatabasePlayer::by_name_or_create("=HYPERLINK(\"x\")", &mut connection).await.unwrap();
+    pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, publisher.id, &mut connection).await;
+    pointercrate_test::demonlist::add_demon("Slaughterhouse", 2, 60, verifier.id, verifier.id, &mut connection).await;
+    pointercrate_test::demonlist::add_demon("-Sonic Wave", 3, 70, formula.id, formula.id, &mut connection).await;
+
+    // Names that spreadsheets would evaluate as formulas are escaped
+    let expected = "position,name,difficulty,requirement,publisher,verifier,level_id\n\
+                    1,Bloodbath,extreme,50,\"Riot, \"\"the\"\" verifier\",stardust1970,\n\
+                    2,Slaughterhouse,extreme,60,stardust1970,stardust1970,\n\
+                    3,'-Sonic Wave,extreme,70,\"'=HYPERLINK(\"\"x\"\")\",\"'=HYPERLINK(\"\"x\"\")\",\n";
+
+    let response = clnt.get("/api/v2/demons/listed.csv").expect_status(Status::Ok).execute().await;
+
+    assert_eq!(response.content_type(), Some(rocket::http::ContentType::CSV));
+    assert_eq!(response.headers().get_one("Content-Disposition"), Some("attachment; filename=\"demonlist.csv\""));
+    assert_eq!(response.into_string().await.unwrap(), expected);
+
+    // The paginated listing keeps returning JSON, regardless of what the client accepts
+    let demons: Vec<Demon> = clnt.get("/api/v2/demons/listed/").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(demons.len(), 3);
+
+    let demons: Vec<Demon> = clnt
+        .get("/api/v2/demons/listed/")
+        .header("Accept", "*/*")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(demons.len(), 3);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_conditional_get(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+
+    let response = clnt.get(format!("/api/v2/demons/{}/", demon)).expect_status(Status::Ok).execute().await;
+    let etag = response.headers().get_one("etag").unwrap().to_string();
+
+    let response = clnt
+        .get(format!("/api/v2/demons/{}/", demon))
+        .header("If-None-Match", etag.clone())
+        .expect_status(Status::NotModified)
+        .execute()
+        .await;
+
+    assert_eq!(response.headers().get_one("etag"), Some(etag.as_str()));
+    assert!(response.into_string().await.map_or(true, |body| body.is_empty()));
+
+    // A new record does not touch the demon itself, but must still invalidate the ETag
+    pointercrate_test::demonlist::add_simple_record(100, player.id, demon, RecordStatus::Approved, &mut connection).await;
+
+    let response = clnt
+        .get(format!("/api/v2/demons/{}/", demon))
+        .header("If-None-Match", etag.clone())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    assert_ne!(response.headers().get_one("etag"), Some(etag.as_str()));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_stale_demon_patch_rejected(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+
+    // Both moderators load the demon before either of them saves
+    let seen: FullDemon = clnt
+        .get(format!("/api/v2/demons/{}/", demon))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    clnt.patch(format!("/api/v2/demons/{}/", demon), &serde_json::json!({"requirement": 60}))
+        .authorize_as(&moderator)
+        .header("If-Match", seen.etag_string())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let result: serde_json::Value = clnt
+        .patch(format!("/api/v2/demons/{}/", demon), &serde_json::json!({"difficulty": "insane"}))
+        .authorize_as(&moderator)
+        .header("If-Match", seen.etag_string())
+        .expect_status(Status::PreconditionFailed)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"], 41200);
+
+    // Patching without any ETag is not allowed either
+    clnt.patch(format!("/api/v2/demons/{}/", demon), &serde_json::json!({"difficulty": "insane"}))
+        .authorize_as(&moderator)
+        .expect_status(Status::PreconditionFailed)
+        .execute()
+        .await;
+
+    let current: FullDemon = clnt
+        .get(format!("/api/v2/demons/{}/", demon))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(current.demon.requirement, 60);
+    assert_eq!(current.demon.difficulty, seen.demon.difficulty);
+
+    // With the up to date ETag, the second edit goes through
+    clnt.patch(format!("/api/v2/demons/{}/", demon), &serde_json::json!({"difficulty": "insane"}))
+        .authorize_as(&moderator)
+        .header("If-Match", current.etag_string())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_concurrent_moves_keep_positions_unique(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool.clone()).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let mut demons = Vec::new();
+
+    for (position, name) in [(1, "Bloodbath"), (2, "Slaughterhouse"), (3, "Sonic Wave"), (4, "Cataclysm")] {
+        demons.push(pointercrate_test::demonlist::add_demon(name, position, 50, verifier.id, verifier.id, &mut connection).await);
+    }
+
+    let move_demon = |demon: i32, to: i16| {
+        let pool = pool.clone();
+
+        rocket::tokio::spawn(async move {
+            let mut transaction = pool.begin().await.unwrap();
+            let mut demon = MinimalDemon::by_id(demon, &mut *transaction).await.unwrap();
+
+            if demon.mv(to, &mut *transaction).await.is_ok() {
+                transaction.commit().await.unwrap();
+            }
+        })
+    };
+
+    // Either both moves go through one after another, or one of them fails, but they never interleave
+    let (a, b) = rocket::tokio::join!(move_demon(demons[0], 4), move_demon(demons[3], 1));
+    a.unwrap();
+    b.unwrap();
+
+    let duplicates = sqlx::query_scalar!(r#"SELECT COUNT(*) - COUNT(DISTINCT position) AS "duplicates!" FROM demons"#)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(duplicates, 0);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_concurrent_inserts_keep_positions_unique(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool.clone()).await;
+
+    let insert_demon = |name: &'static str| {
+        let pool = pool.clone();
+
+        rocket::tokio::spawn(async move {
+            let data: PostDemon = serde_json::from_value(serde_json::json!({
+                "name": name,
+                "position": 1,
+                "requirement": 50,
+                "verifier": "stardust1970",
+                "publisher": "stardust1970",
+                "creators": [],
+                "video": null,
+                "difficulty": "extreme"
+            }))
+            .unwrap();
+
+            let mut transaction = pool.begin().await.unwrap();
+            FullDemon::create_from(data, &mut *transaction).await.unwrap();
+            transaction.commit().await.unwrap();
+        })
+    };
+
+    let (a, b) = rocket::tokio::join!(insert_demon("Bloodbath"), insert_demon("Slaughterhouse"));
+    a.unwrap();
+    b.unwrap();
+
+    let positions = sqlx::query_scalar!("SELECT position FROM demons ORDER BY position")
+        .fetch_all(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(positions, vec![1, 2]);
+}
+
+fn capped_post_demon(name: &str, position: i16) -> PostDemon {
+    serde_json::from_value(serde_json::json!({
+        "name": name,
+        "position": position,
+        "requirement": 50,
+        "verifier": "stardust1970",
+        "publisher": "stardust1970",
+        "creators": [],
+        "video": null,
+        "difficulty": "extreme"
+    }))
+    .unwrap()
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_list_overflow_archives_lowest_demons(pool: Pool<Postgres>) {
+    let mut connection = pool.acquire().await.unwrap();
+
+    for (position, name) in ["Bloodbath", "Slaughterhouse", "Acheron"].into_iter().enumerate() {
+        FullDemon::create_capped(capped_post_demon(name, position as i16 + 1), Some(3), true, &mut connection)
+            .await
+            .unwrap();
+    }
+
+    // Appending right past the end of a full list archives the new demon itself
+    let (appended, archived) = FullDemon::create_capped(capped_post_demon("Sakupen Circles", 4), Some(3), true, &mut connection)
+        .await
+        .unwrap();
+
+    assert_eq!(archived, vec![appended.demon.base.clone()]);
+
+    // Placing a demon into a full list archives the one it pushes out, which ends up above all previously archived demons
+    let (_, archived) = FullDemon::create_capped(capped_post_demon("Tartarus", 1), Some(3), true, &mut connection)
+        .await
+        .unwrap();
+
+    assert_eq!(archived.iter().map(|demon| demon.name.as_str()).collect::<Vec<_>>(), vec!["Acheron"]);
+
+    let list = sqlx::query!("SELECT name::TEXT AS \"name!\", position, archived FROM demons ORDER BY position")
+        .fetch_all(&mut *connection)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|row| (row.name, row.position, row.archived))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        list,
+        vec![
+            ("Tartarus".to_owned(), 1, false),
+            ("Bloodbath".to_owned(), 2, false),
+            ("Slaughterhouse".to_owned(), 3, false),
+            ("Acheron".to_owned(), 4, true),
+            ("Sakupen Circles".to_owned(), 5, true),
+        ]
+    );
+
+    // The archived demons are below the list proper, so nothing can be placed between them
+    let result = FullDemon::create_capped(capped_post_demon("Kyouki", 5), Some(3), true, &mut connection).await;
+
+    assert!(matches!(result, Err(DemonlistError::InvalidPosition { maximal: 4 })), "{:?}", result);
+
+    // And if overflowing demons are not archived, a full list rejects any further placements
+    let result = FullDemon::create_capped(capped_post_demon("Kyouki", 4), Some(3), false, &mut connection).await;
+
+    assert!(matches!(result, Err(DemonlistError::ListFull { maximal: 3 })), "{:?}", result);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_enjoyment_stats(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+    let demon = Demon::by_id(demon, &mut connection).await.unwrap();
+
+    let stats = demon.enjoyment_stats(&mut connection).await.unwrap();
+
+    assert_eq!(stats.mean, None);
+    assert_eq!(stats.ratings, 0);
+
+    for (name, status, enjoyment) in [
+        ("stardust1971", RecordStatus::Approved, Some(6)),
+        ("stardust1972", RecordStatus::Approved, Some(9)),
+        ("stardust1973", RecordStatus::Approved, None),
+        ("stardust1974", RecordStatus::Rejected, Some(0)),
+    ] {
+        let player = DatabasePlayer::by_name_or_create(name, &mut connection).await.unwrap();
+        let record = pointercrate_test::demonlist::add_simple_record(100, player.id, demon.base.id, status, &mut connection).await;
+
+        sqlx::query!("UPDATE records SET enjoyment = $1 WHERE id = $2", enjoyment, record)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+    }
+
+    let stats = demon.enjoyment_stats(&mut connection).await.unwrap();
+
+    assert_eq!(stats.mean, Some(7.5));
+    assert_eq!(stats.ratings, 2);
+    assert_eq!(stats.histogram[6], 1);
+    assert_eq!(stats.histogram[9], 1);
+    assert_eq!(stats.histogram[0], 0);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_progress_distribution(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let bloodbath = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 60, verifier.id, verifier.id, &mut connection).await;
+    let tartarus = pointercrate_test::demonlist::add_demon("Tartarus", 2, 100, verifier.id, verifier.id, &mut connection).await;
+
+    for (name, progress, status) in [
+        ("stardust1971", 61, RecordStatus::Approved),
+        ("stardust1972", 65, RecordStatus::Approved),
+        ("stardust1973", 87, RecordStatus::Approved),
+        ("stardust1974", 100, RecordStatus::Approved),
+        ("stardust1975", 90, RecordStatus::Rejected),
+    ] {
+        let player = DatabasePlayer::by_name_or_create(name, &mut connection).await.unwrap();
+        pointercrate_test::demonlist::add_simple_record(progress, player.id, bloodbath, status, &mut connection).await;
+        pointercrate_test::demonlist::add_simple_record(100, player.id, tartarus, status, &mut connection).await;
+    }
+
+    let bloodbath = Demon::by_id(bloodbath, &mut connection).await.unwrap();
+
+    assert_eq!(bloodbath.progress_distribution(10, &mut connection).await.unwrap(), vec![(60, 2), (70, 0), (80, 1), (90, 0), (100, 1)]);
+    assert_eq!(bloodbath.progress_distribution(20, &mut connection).await.unwrap(), vec![(60, 2), (80, 1), (100, 1)]);
+
+    let tartarus = Demon::by_id(tartarus, &mut connection).await.unwrap();
+
+    assert_eq!(tartarus.progress_distribution(10, &mut connection).await.unwrap(), vec![(100, 4)]);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_difficulty_change_audit(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut connection).await;
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+
+    for difficulty in ["hard", "insane"] {
+        let full_demon: FullDemon = clnt
+            .get(format!("/api/v2/demons/{}/", demon))
+            .expect_status(Status::Ok)
+            .get_success_result()
+            .await;
+
+        clnt.patch(format!("/api/v2/demons/{}/", demon), &serde_json::json!({"difficulty": difficulty}))
+            .authorize_as(&admin)
+            .header("If-Match", full_demon.etag_string())
+            .expect_status(Status::Ok)
+            .execute()
+            .await;
+    }
+
+    let log: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v2/demons/{}/audit/", demon))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    let changes: Vec<_> = log
+        .iter()
+        .filter_map(|entry| entry["type"].get("Modification").map(|data| (entry, data)))
+        .filter(|(_, data)| !data["difficulty"].is_null())
+        .collect();
+
+    assert_eq!(changes.len(), 2);
+
+    for ((entry, data), (old, new)) in changes.into_iter().zip([("extreme", "hard"), ("hard", "insane")]) {
+        assert_eq!(data["difficulty"], old);
+        assert_eq!(data["new_difficulty"], new);
+        assert_eq!(entry["user"]["id"], admin.user().id);
+        assert!(entry["time"].is_string());
+    }
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_time_machine(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let bloodbath = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+    let slaughterhouse = pointercrate_test::demonlist::add_demon("Slaughterhouse", 2, 50, verifier.id, verifier.id, &mut connection).await;
+
+    sqlx::query!("UPDATE demon_additions SET time = '2020-01-01T00:00:00'")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    // Slaughterhouse overtakes Bloodbath in 2021
+    let mut demon = MinimalDemon::by_id(slaughterhouse, &mut connection).await.unwrap();
+    demon.mv(1, &mut connection).await.unwrap();
+
+    sqlx::query!("UPDATE demon_modifications SET time = '2021-01-01T00:00:00'")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    // Sonic Wave is added in 2022
+    let sonic_wave = pointercrate_test::demonlist::add_demon("Sonic Wave", 3, 50, verifier.id, verifier.id, &mut connection).await;
+
+    sqlx::query!("UPDATE demon_additions SET time = '2022-01-01T00:00:00' WHERE id = $1", sonic_wave)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let snapshot = |at: &'static str| {
+        let clnt = &clnt;
+
+        async move {
+            let demons: Vec<serde_json::Value> = clnt
+                .get(format!("/api/v2/demons/?at={}", at))
+                .expect_status(Status::Ok)
+                .get_result()
+                .await;
+
+            demons
+                .iter()
+                .map(|demon| {
+                    (
+                        demon["id"].as_i64().unwrap() as i32,
+                        demon["position"].as_i64().unwrap(),
+                        demon["position_now"].as_i64().unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(snapshot("2019-01-01T00:00:00").await, vec![]);
+    assert_eq!(snapshot("2020-06-01T00:00:00").await, vec![(bloodbath, 1, 2), (slaughterhouse, 2, 1)]);
+    assert_eq!(snapshot("2021-06-01T00:00:00").await, vec![(slaughterhouse, 1, 1), (bloodbath, 2, 2)]);
+    assert_eq!(snapshot("2023-01-01T00:00:00").await, vec![(slaughterhouse, 1, 1), (bloodbath, 2, 2), (sonic_wave, 3, 3)]);
+
+    clnt.get("/api/v2/demons/?at=yesterday")
+        .expect_status(Status::UnprocessableEntity)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_search(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let bloodbath = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id, player.id, &mut connection).await;
+    let bloodlust = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 58, player.id, player.id, &mut connection).await;
+    let sonic_wave = pointercrate_test::demonlist::add_demon("Sonic Wave", 3, 60, player.id, player.id, &mut connection).await;
+
+    // Typos are tolerated, and the closest match comes first
+    let results: Vec<MinimalDemon> = clnt
+        .get("/api/v2/demons/search/?q=bloodbat")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(results[0].id, bloodbath);
+    assert_eq!(results[0].position, 1);
+    assert!(results.iter().all(|demon| demon.id != sonic_wave));
+
+    // Partial names match, regardless of case
+    let results: Vec<MinimalDemon> = clnt
+        .get("/api/v2/demons/search/?q=WAVE")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(results.iter().map(|demon| demon.id).collect::<Vec<_>>(), vec![sonic_wave]);
+
+    let results: Vec<MinimalDemon> = clnt
+        .get("/api/v2/demons/search/?q=blood&limit=1")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(results.len(), 1);
+    assert!(results[0].id == bloodbath || results[0].id == bloodlust);
+
+    let result: serde_json::Value = clnt
+        .get("/api/v2/demons/search/?q=%20bl%20")
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"], 42258);
+}
+
+/// Resolver that looks up level ids from a fixed table instead of the Geometry Dash servers
+struct FixedLevelIdResolver(Vec<(&'static str, LevelIdResolution)>);
+
+#[rocket::async_trait]
+impl LevelIdResolver for FixedLevelIdResolver {
+    async fn resolve(&self, name: &str) -> LevelIdResolution {
+        self.0
+            .iter()
+            .find(|(level_name, _)| *level_name == name)
+            .map(|(_, resolution)| resolution.clone())
+            .unwrap_or(LevelIdResolution::NotFound)
+    }
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_refresh_level_id(pool: Pool<Postgres>) {
+    let resolver = FixedLevelIdResolver(vec![
+        ("Bloodbath", LevelIdResolution::Found(10565740)),
+        ("Sonic Wave", LevelIdResolution::Ambiguous(vec![26681070, 26681071])),
+        ("Tartarus", LevelIdResolution::Found(u64::MAX)),
+    ]);
+
+    let (clnt, mut connection) =
+        pointercrate_test::demonlist::setup_rocket_with(pool, |rocket| rocket.manage(Box::new(resolver) as Box<dyn LevelIdResolver>)).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let bloodbath = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 90, player.id, player.id, &mut connection).await;
+    let sonic_wave = pointercrate_test::demonlist::add_demon("Sonic Wave", 2, 60, player.id, player.id, &mut connection).await;
+    let tartarus = pointercrate_test::demonlist::add_demon("Tartarus", 3, 60, player.id, player.id, &mut connection).await;
+
+    sqlx::query!("UPDATE demons SET level_id = 1 WHERE id = $1", sonic_wave)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let refreshed: serde_json::Value = clnt
+        .post(format!("/api/v2/demons/{}/level_id/", bloodbath), &())
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(refreshed["level_id"], 10565740);
+    assert_eq!(Demon::by_id(bloodbath, &mut connection).await.unwrap().level_id, Some(10565740));
+
+    // Ambiguous names report every candidate, and leave the current level id alone
+    let result: serde_json::Value = clnt
+        .post(format!("/api/v2/demons/{}/level_id/", sonic_wave), &())
+        .authorize_as(&admin)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"], 42259);
+    assert_eq!(result["data"]["candidates"], serde_json::json!([26681070, 26681071]));
+    assert_eq!(Demon::by_id(sonic_wave, &mut connection).await.unwrap().level_id, Some(1));
+
+    // Level ids that do not fit into the database are rejected instead of wrapping around
+    let result: serde_json::Value = clnt
+        .post(format!("/api/v2/demons/{}/level_id/", tartarus), &())
+        .authorize_as(&admin)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"], 42235);
+    assert_eq!(Demon::by_id(tartarus, &mut connection).await.unwrap().level_id, None);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_reorder_demons(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+
+    let names = ["Bloodbath", "Slaughterhouse", "Tartarus", "Sonic Wave", "Acheron"];
+    let mut demons = Vec::new();
+
+    for (name, position) in names.into_iter().zip(1..) {
+        demons.push(pointercrate_test::demonlist::add_demon(name, position, 50, verifier.id, verifier.id, &mut connection).await);
+    }
+
+    // Acheron to the top and Bloodbath to #3, everything else keeps its relative order
+    let positions: Vec<DemonPosition> = clnt
+        .patch(
+            "/api/v2/demons/positions/",
+            &serde_json::json!([{"id": demons[4], "position": 1}, {"id": demons[0], "position": 3}]),
+        )
+        .authorize_as(&moderator)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    let ordering = positions.iter().map(|demon| demon.id).collect::<Vec<_>>();
+
+    assert_eq!(ordering, vec![demons[4], demons[1], demons[0], demons[2], demons[3]]);
+    assert!(positions.iter().zip(1..).all(|(demon, position)| demon.position == position));
+
+    // Two demons targeting the same position must leave the list untouched
+    let result: serde_json::Value = clnt
+        .patch(
+            "/api/v2/demons/positions/",
+            &serde_json::json!([{"id": demons[2], "position": 5}, {"id": demons[3], "position": 5}]),
+        )
+        .authorize_as(&moderator)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"], 42261);
+
+    clnt.patch("/api/v2/demons/positions/", &serde_json::json!([{"id": demons[2], "position": 6}]))
+        .authorize_as(&moderator)
+        .expect_status(Status::UnprocessableEntity)
+        .execute()
+        .await;
+
+    let positions: Vec<DemonPosition> = clnt.get("/api/v2/demons/positions/").get_success_result().await;
+
+    assert_eq!(positions.iter().map(|demon| demon.id).collect::<Vec<_>>(), ordering);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demons_verified_by_banned(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let banned = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    let verified = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, banned.id, player.id, &mut connection).await;
+    pointercrate_test::demonlist::add_demon("Bloodlust", 2, 50, player.id, player.id, &mut connection).await;
+    let published = pointercrate_test::demonlist::add_demon("Acheron", 3, 50, player.id, banned.id, &mut connection).await;
+    let deleted = pointercrate_test::demonlist::add_demon("Tartarus", 4, 50, banned.id, banned.id, &mut connection).await;
+
+    sqlx::query!("UPDATE players SET banned = TRUE WHERE id = $1", banned.id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE demons SET deleted_at = NOW() WHERE id = $1", deleted)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let demons: Vec<serde_json::Value> = clnt
+        .get("/api/v2/demons/verified-by-banned/")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(
+        demons.iter().map(|demon| demon["id"].as_i64().unwrap() as i32).collect::<Vec<_>>(),
+        vec![verified, published]
+    );
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_soft_delete_demon(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    let bloodbath = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+    let tartarus = pointercrate_test::demonlist::add_demon("Tartarus", 2, 50, player.id, player.id, &mut connection).await;
+    let slaughterhouse = pointercrate_test::demonlist::add_demon("Slaughterhouse", 3, 50, player.id, player.id, &mut connection).await;
+    let record = pointercrate_test::demonlist::add_simple_record(100, player.id, tartarus, RecordStatus::Approved, &mut connection).await;
+
+    let demon: FullDemon = clnt
+        .get(format!("/api/v2/demons/{}/", tartarus))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    clnt.delete(format!("/api/v2/demons/{}/", tartarus))
+        .authorize_as(&admin)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    // The demon is gone from the list, and the demons below it moved up
+    let list = current_list(&mut connection).await.unwrap();
+
+    assert_eq!(
+        list.iter().map(|demon| (demon.base.id, demon.base.position)).collect::<Vec<_>>(),
+        vec![(bloodbath, 1), (slaughterhouse, 2)]
+    );
+
+    clnt.get(format!("/api/v2/demons/{}/", tartarus))
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+
+    // ... but its records are still around
+    let record = FullRecord::by_id(record, &mut connection).await.unwrap();
+
+    assert_eq!(record.demon.id, tartarus);
+    assert_eq!(record.status, RecordStatus::Approved);
+
+    // ... just no longer shown anywhere
+    let profile: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}/", player.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(profile.records.iter().all(|record| record.demon.id != tartarus));
+
+    let listed: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v1/records/?player={}", player.id))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert!(listed.is_empty());
+
+    let restored: FullDemon = clnt
+        .post(format!("/api/v2/demons/{}/restore/", tartarus), &())
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(restored.position(), 2);
+    assert_eq!(restored.records.len(), 1);
+    assert_eq!(Demon::by_position(3, &mut connection).await.unwrap().base.id, slaughterhouse);
+
+    // Only deleted demons can be restored
+    clnt.post(format!("/api/v2/demons/{}/restore/", tartarus), &())
+        .authorize_as(&admin)
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+
+    let log: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v2/demons/{}/audit/", tartarus))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    let deletions: Vec<_> = log
+        .iter()
+        .filter_map(|entry| entry["type"].get("Modification"))
+        .filter_map(|data| data["deletion"].as_str())
+        .collect();
+
+    assert_eq!(deletions, vec!["deleted", "restored"]);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_post_demon_with_verification_record(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+
+    let demon = serde_json::json! {{"name": "Bloodbath", "requirement": 90, "position": 1, "verifier": "Riot", "publisher": "Riot", "creators": [], "difficulty": "extreme", "verification_record": true}};
+
+    let mut demon: FullDemon = clnt
+        .post("/api/v2/demons/", &demon)
+        .authorize_as(&moderator)
+        .expect_status(Status::Created)
+        .get_success_result()
+        .await;
+
+    assert_eq!(demon.records.len(), 1);
+    assert_eq!(demon.records[0].player.id, demon.demon.verifier.id);
+
+    let record = FullRecord::by_id(demon.records[0].id, &mut connection).await.unwrap();
+
+    assert_eq!(record.demon.id, demon.demon.base.id);
+    assert_eq!(record.progress, 100);
+    assert_eq!(record.status, RecordStatus::Approved);
+    assert_eq!(record.video, None, "Verification record duplicated the verification video");
+
+    let video_exempt = sqlx::query_scalar!("SELECT video_exempt FROM records WHERE id = $1", record.id)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert!(video_exempt);
+
+    // Adding it a second time does nothing
+    let submitter = Submitter::by_ip("127.0.0.1".parse().unwrap(), &mut connection).await.unwrap().unwrap();
+
+    demon.add_verification_record(&submitter, &mut connection).await.unwrap();
+
+    assert_eq!(demon.records.len(), 1);
+    assert_eq!(
+        approved_records_on(&demon.demon.base, RecordOrder::default(), &mut connection)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_demon_search_key(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Blood 🔥 Bath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+
+    let search_key: String = sqlx::query_scalar!("SELECT search_key AS \"search_key!\" FROM demons WHERE id = $1", demon)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    // Emoji are removed and the whitespace left behind is collapsed, the display name is untouched
+    assert_eq!(search_key, "blood bath");
+    assert_eq!(Demon::by_id(demon, &mut connection).await.unwrap().base.name, "Blood 🔥 Bath");
+
+    // Emoji stripping is disabled by default, so only plain substrings of the name match
+    let demons: Vec<serde_json::Value> = clnt
+        .get("/api/v2/demons/?name_contains=blood")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+    assert_eq!(demons.len(), 1);
+
+    let demons: Vec<serde_json::Value> = clnt
+        .get("/api/v2/demons/?name_contains=blood%20bath")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+    assert!(demons.is_empty());
+}
@@ -1,6 +1,8 @@
 use pointercrate_demonlist::{
-    nationality::{Nationality, RankedNation},
+    demon::Demon,
+    nationality::{Nationality, RankedNation, Subdivision},
     player::{DatabasePlayer, Player},
+    record::RecordStatus,
     LIST_MODERATOR,
 };
 use rocket::http::Status;
@@ -33,3 +35,196 @@ pub async fn test_search_nation(pool: Pool<Postgres>) {
     assert_eq!(json[0].nationality.iso_country_code, "DE");
     assert_eq!(json[0].nationality.nation, "Germany");
 }
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_nation_verifications(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let german = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let stateless = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+    Player::by_id(german.id, &mut connection)
+        .await
+        .unwrap()
+        .set_nationality(
+            Some(Nationality {
+                iso_country_code: "DE".into(),
+                nation: "Germany".into(),
+                subdivision: None,
+            }),
+            &mut connection,
+        )
+        .await
+        .unwrap();
+
+    pointercrate_test::demonlist::add_demon("Bloodbath", 1, 100, stateless.id, german.id, &mut connection).await;
+    let verified = pointercrate_test::demonlist::add_demon("Bloodbath 2", 2, 100, german.id, stateless.id, &mut connection).await;
+
+    let (demons, _) = client
+        .get("/api/v1/nationalities/DE/verifications/")
+        .expect_status(Status::Ok)
+        .get_pagination_result::<Demon>()
+        .await;
+
+    assert_eq!(demons.len(), 1);
+    assert_eq!(demons[0].base.id, verified);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_nation_records(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let german = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let stateless = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+    Player::by_id(german.id, &mut connection)
+        .await
+        .unwrap()
+        .set_nationality(
+            Some(Nationality {
+                iso_country_code: "DE".into(),
+                nation: "Germany".into(),
+                subdivision: None,
+            }),
+            &mut connection,
+        )
+        .await
+        .unwrap();
+
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, stateless.id, stateless.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Bloodbath 2", 2, 50, stateless.id, stateless.id, &mut connection).await;
+    let demon3 = pointercrate_test::demonlist::add_demon("Bloodbath 3", 3, 50, stateless.id, stateless.id, &mut connection).await;
+
+    let recent = pointercrate_test::demonlist::add_simple_record(100, german.id, demon1, RecordStatus::Approved, &mut connection).await;
+    let old = pointercrate_test::demonlist::add_simple_record(100, german.id, demon2, RecordStatus::Approved, &mut connection).await;
+    pointercrate_test::demonlist::add_simple_record(100, german.id, demon3, RecordStatus::Submitted, &mut connection).await;
+    pointercrate_test::demonlist::add_simple_record(100, stateless.id, demon1, RecordStatus::Approved, &mut connection).await;
+
+    for (record, approved_at) in [(recent, "2024-06-01 12:00:00"), (old, "2010-06-01 12:00:00")] {
+        sqlx::query!("UPDATE records SET approved_at = $1::TEXT::TIMESTAMP WHERE id = $2", approved_at, record)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+    }
+
+    let (records, _) = client
+        .get("/api/v1/nationalities/DE/records/")
+        .expect_status(Status::Ok)
+        .get_pagination_result::<serde_json::Value>()
+        .await;
+
+    assert_eq!(records.iter().map(|record| record["id"].as_i64().unwrap()).collect::<Vec<_>>(), vec![recent as i64, old as i64]);
+
+    let (records, _) = client
+        .get("/api/v1/nationalities/DE/records/?since=2020-01-01T00:00:00")
+        .expect_status(Status::Ok)
+        .get_pagination_result::<serde_json::Value>()
+        .await;
+
+    assert_eq!(records.iter().map(|record| record["id"].as_i64().unwrap()).collect::<Vec<_>>(), vec![recent as i64]);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_subdivision_leaderboard(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let germany = Nationality::by_country_code_or_name("DE", &mut connection).await.unwrap();
+    let bavaria = germany.subdivision_by_code("BY", &mut connection).await.unwrap();
+    let berlin = germany.subdivision_by_code("BE", &mut connection).await.unwrap();
+
+    let players = [
+        ("stardust1971", Some(&bavaria), 10.0),
+        ("stardust1972", Some(&bavaria), 15.0),
+        ("stardust1973", Some(&berlin), 20.0),
+        ("stardust1974", None, 5.0),
+        ("stardust1975", None, 1.5),
+    ];
+
+    for (name, subdivision, score) in players {
+        let player = DatabasePlayer::by_name_or_create(name, &mut connection).await.unwrap();
+        let mut player = Player::by_id(player.id, &mut connection).await.unwrap();
+        let nationality = Nationality {
+            subdivision: subdivision.cloned(),
+            ..germany.clone()
+        };
+
+        player.set_nationality(Some(nationality), &mut connection).await.unwrap();
+
+        sqlx::query!("UPDATE players SET score = $1 WHERE id = $2", score, player.base.id)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+    }
+
+    // Players of other countries must not show up
+    let french = DatabasePlayer::by_name_or_create("stardust1976", &mut connection).await.unwrap();
+    let france = Nationality::by_country_code_or_name("FR", &mut connection).await.unwrap();
+    Player::by_id(french.id, &mut connection)
+        .await
+        .unwrap()
+        .set_nationality(Some(france), &mut connection)
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE players SET score = 100 WHERE id = $1", french.id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let leaderboard = Nationality::subdivision_leaderboard("DE", &mut connection).await.unwrap();
+
+    assert_eq!(leaderboard, vec![(bavaria, 25.0), (berlin, 20.0), (Subdivision::unknown(), 6.5)]);
+
+    assert!(Nationality::subdivision_leaderboard("XY", &mut connection).await.is_err());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_nationality_stats(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+
+    let players = [
+        ("stardust1971", "DE", 10.0, false),
+        ("stardust1972", "DE", 20.0, false),
+        ("stardust1973", "DE", 60.0, false),
+        ("stardust1974", "AT", 5.0, false),
+        // Banned players are not counted
+        ("stardust1975", "DE", 1000.0, true),
+    ];
+
+    for (name, nation, score, banned) in players {
+        let player = DatabasePlayer::by_name_or_create(name, &mut connection).await.unwrap();
+
+        sqlx::query!(
+            "UPDATE players SET nationality = $1, score = $2, banned = $3 WHERE id = $4",
+            nation,
+            score,
+            banned,
+            player.id
+        )
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+        if name == "stardust1971" {
+            pointercrate_test::demonlist::put_claim(user.user().id, player.id, true, false, &mut connection).await;
+        }
+    }
+
+    let stats = Nationality::with_stats(&mut connection).await.unwrap();
+    let stats_of = |code: &str| stats.iter().find(|stats| stats.nation.iso_country_code == code).unwrap();
+
+    let germany = stats_of("DE");
+    assert_eq!(germany.players, 3);
+    assert_eq!(germany.claimed_players, 1);
+    assert_eq!(germany.mean_score, 30.0);
+
+    let austria = stats_of("AT");
+    assert_eq!(austria.players, 1);
+    assert_eq!(austria.claimed_players, 0);
+    assert_eq!(austria.mean_score, 5.0);
+
+    // Nations without players are still listed
+    let france = stats_of("FR");
+    assert_eq!(france.players, 0);
+    assert_eq!(france.claimed_players, 0);
+    assert_eq!(france.mean_score, 0.0);
+}
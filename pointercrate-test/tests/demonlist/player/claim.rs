@@ -1,4 +1,10 @@
-use pointercrate_demonlist::player::{claim::PlayerClaim, DatabasePlayer, FullPlayer};
+use pointercrate_core::error::PointercrateError;
+use pointercrate_demonlist::{
+    error::DemonlistError,
+    player::{claim::PlayerClaim, DatabasePlayer, FullPlayer},
+    LIST_HELPER, LIST_MODERATOR,
+};
+use pointercrate_user::MODERATOR;
 use rocket::http::Status;
 use sqlx::{Pool, Postgres};
 
@@ -67,3 +73,307 @@ async fn test_players_me(pool: Pool<Postgres>) {
 
     assert_eq!(claimed.player.base.id, player_id);
 }
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_claimed_players(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let player_id = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap().id;
+
+    client
+        .put(format!("/api/v1/players/{}/claims/", player_id))
+        .authorize_as(&user)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+
+    client
+        .get("/api/v1/players/claimed/")
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    let claims: Vec<serde_json::Value> = client
+        .get("/api/v1/players/claimed/")
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(claims.len(), 1);
+    assert_eq!(claims[0]["player"]["id"], player_id);
+    assert_eq!(claims[0]["verified"], false);
+    assert!(claims[0]["user"].is_null());
+
+    // Helpers cannot find out who made a claim by searching for them
+    let claims: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/players/claimed/?any_name_contains={}", user.user().name))
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert!(claims.is_empty());
+
+    let claims: Vec<serde_json::Value> = client
+        .get("/api/v1/players/claimed/")
+        .authorize_as(&moderator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(claims[0]["user"]["id"], user.user().id);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_cannot_verify_claim_on_banned_player(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+    let moderator = pointercrate_test::user::system_user_with_perms(MODERATOR, &mut connection).await;
+    let mut player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    client
+        .put(format!("/api/v1/players/{}/claims/", player.id))
+        .authorize_as(&user)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+
+    player.ban(&mut connection).await.unwrap();
+
+    let json: serde_json::Value = client
+        .patch(format!("/api/v1/players/{}/claims/{}/", player.id, user.user().id), &serde_json::json!({"verified": true}))
+        .authorize_as(&moderator)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(DemonlistError::ClaimOnBannedPlayer.error_code() as i64));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_verify_claim(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+    let other = pointercrate_test::user::add_normal_user(&mut connection).await;
+    let moderator = pointercrate_test::user::system_user_with_perms(MODERATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    for claimant in [&user, &other] {
+        client
+            .put(format!("/api/v1/players/{}/claims/", player.id))
+            .authorize_as(claimant)
+            .expect_status(Status::Created)
+            .execute()
+            .await;
+    }
+
+    let claim: PlayerClaim = client
+        .patch(format!("/api/v1/players/{}/claims/{}/", player.id, user.user().id), &serde_json::json!({"verified": true}))
+        .authorize_as(&moderator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert!(claim.verified);
+
+    // Verifying a claim removes all competing claims on the same player
+    let claimants = sqlx::query_scalar!("SELECT member_id FROM player_claims WHERE player_id = $1", player.id)
+        .fetch_all(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(claimants, vec![user.user().id]);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_verify_claim_strict_geolocation(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    sqlx::query!("INSERT INTO player_claims (member_id, player_id) VALUES ($1, $2)", user.user().id, player.id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE players SET nationality = 'DE' WHERE id = $1", player.id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let mut claim = PlayerClaim {
+        user_id: user.user().id,
+        player_id: player.id,
+        verified: false,
+        lock_submissions: false,
+    };
+
+    claim.set_geolocated_country(Some("GB"), &mut connection).await.unwrap();
+
+    // Without strict mode, a mismatch is left for moderators to judge
+    claim.set_verified_with(true, false, &mut connection).await.unwrap();
+    assert!(claim.verified);
+
+    claim.set_verified_with(false, true, &mut connection).await.unwrap();
+
+    assert!(matches!(
+        claim.set_verified_with(true, true, &mut connection).await,
+        Err(DemonlistError::GeolocationMismatch { .. })
+    ));
+    assert!(!claim.verified);
+
+    claim.set_geolocated_country(Some("DE"), &mut connection).await.unwrap();
+    claim.set_verified_with(true, true, &mut connection).await.unwrap();
+
+    let verified = sqlx::query_scalar!(
+        "SELECT verified FROM player_claims WHERE member_id = $1 AND player_id = $2",
+        user.user().id,
+        player.id
+    )
+    .fetch_one(&mut *connection)
+    .await
+    .unwrap();
+
+    assert!(verified);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_my_claims(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+    let verified = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let unverified = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+
+    // Claiming through the API clears all other claims of the user, so set them up directly
+    sqlx::query!(
+        "INSERT INTO player_claims (member_id, player_id, verified) VALUES ($1, $2, TRUE), ($1, $3, FALSE)",
+        user.user().id,
+        verified.id,
+        unverified.id
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+    sqlx::query!("UPDATE players SET nationality = 'DE' WHERE id = $1", verified.id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    for (player, country) in [(&verified, "DE"), (&unverified, "GB")] {
+        PlayerClaim {
+            user_id: user.user().id,
+            player_id: player.id,
+            verified: false,
+            lock_submissions: false,
+        }
+        .set_geolocated_country(Some(country), &mut connection)
+        .await
+        .unwrap();
+    }
+
+    client
+        .get("/api/v1/players/me/claims/")
+        .expect_status(Status::Unauthorized)
+        .execute()
+        .await;
+
+    let claims: Vec<serde_json::Value> = client
+        .get("/api/v1/players/me/claims/")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(claims.len(), 2);
+
+    assert_eq!(claims[0]["player_id"], verified.id);
+    assert_eq!(claims[0]["player_name"], "stardust1971");
+    assert_eq!(claims[0]["verified"], true);
+    assert_eq!(claims[0]["geolocation_verified"], true);
+
+    // Geolocated to a country that does not match the player's (non-existent) nationality
+    assert_eq!(claims[1]["player_id"], unverified.id);
+    assert_eq!(claims[1]["player_name"], "stardust1972");
+    assert_eq!(claims[1]["verified"], false);
+    assert_eq!(claims[1]["geolocation_verified"], false);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_claim_moderation_queue(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let moderator = pointercrate_test::user::system_user_with_perms(MODERATOR, &mut connection).await;
+    let pending = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let verified = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+
+    sqlx::query!(
+        "INSERT INTO player_claims (member_id, player_id, verified) VALUES ($1, $2, FALSE), ($3, $4, TRUE)",
+        user.user().id,
+        pending.id,
+        helper.user().id,
+        verified.id
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+    sqlx::query!("UPDATE players SET nationality = 'DE' WHERE id = $1", pending.id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    PlayerClaim {
+        user_id: user.user().id,
+        player_id: pending.id,
+        verified: false,
+        lock_submissions: false,
+    }
+    .set_geolocated_country(Some("DE"), &mut connection)
+    .await
+    .unwrap();
+
+    client
+        .get("/api/v1/players/claims/?status=unverified")
+        .authorize_as(&helper)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    let claims: Vec<serde_json::Value> = client
+        .get("/api/v1/players/claims/?status=unverified")
+        .authorize_as(&moderator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(claims.len(), 1);
+    assert_eq!(claims[0]["player"]["id"], pending.id);
+    assert_eq!(claims[0]["user"]["id"], user.user().id);
+    assert_eq!(claims[0]["geolocated_country"], "DE");
+    assert_eq!(claims[0]["geolocation_verified"], true);
+
+    let claims: Vec<serde_json::Value> = client
+        .get("/api/v1/players/claims/?status=verified")
+        .authorize_as(&moderator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(claims.len(), 1);
+    assert_eq!(claims[0]["player"]["id"], verified.id);
+    assert!(claims[0]["geolocated_country"].is_null());
+    assert_eq!(claims[0]["geolocation_verified"], false);
+
+    // Where a claimant is located is as private as who they are
+    let claims: Vec<serde_json::Value> = client
+        .get("/api/v1/players/claimed/?status=unverified")
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert!(claims[0]["geolocated_country"].is_null());
+}
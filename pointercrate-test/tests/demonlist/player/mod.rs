@@ -1,5 +1,6 @@
 use pointercrate_demonlist::record::RecordStatus;
 use pointercrate_demonlist::{
+    error::DemonlistError,
     nationality::{Nationality, Subdivision},
     player::{DatabasePlayer, FullPlayer, Player},
     LIST_HELPER, LIST_MODERATOR,
@@ -172,6 +173,44 @@ async fn test_patch_player_nationality(pool: Pool<Postgres>) {
     assert_eq!(result["data"]["subdivision_code"], "ENG");
 }
 
+#[sqlx::test(migrations = "../migrations")]
+async fn test_nationality_history(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let mut player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let user = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut connection).await;
+
+    client
+        .patch_player(player.id, &user, json!({"nationality": "United Kingdom", "subdivision": "ENG"}))
+        .await
+        .execute()
+        .await;
+    client
+        .patch_player(player.id, &user, json!({"nationality": "Germany"}))
+        .await
+        .execute()
+        .await;
+
+    // Changes not touching the nationality are not part of the history
+    player.ban(&mut connection).await.unwrap();
+
+    let history = player.nationality_history(&mut connection).await.unwrap();
+
+    assert_eq!(history.len(), 2);
+
+    assert_eq!(history[0].old_nationality, None);
+    assert_eq!(history[0].old_subdivision, None);
+    assert_eq!(history[0].new_nationality.as_deref(), Some("GB"));
+    assert_eq!(history[0].new_subdivision.as_deref(), Some("ENG"));
+
+    assert_eq!(history[1].old_nationality.as_deref(), Some("GB"));
+    assert_eq!(history[1].old_subdivision.as_deref(), Some("ENG"));
+    assert_eq!(history[1].new_nationality.as_deref(), Some("DE"));
+    assert_eq!(history[1].new_subdivision, None);
+
+    assert!(history[0].time <= history[1].time);
+    assert!(history.iter().all(|change| change.user.id == user.user().id));
+}
+
 #[sqlx::test(migrations = "../migrations")]
 async fn test_me(pool: Pool<Postgres>) {
     let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
@@ -371,3 +410,257 @@ async fn test_player_merge(pool: Pool<Postgres>) {
         .execute()
         .await;
 }
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_merge_into(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let winner = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let loser = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, winner.id, winner.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 50, winner.id, winner.id, &mut connection).await;
+
+    // Both players hold records on demon1, only the loser on demon2
+    pointercrate_test::demonlist::add_simple_record(100, winner.id, demon1, RecordStatus::Approved, &mut connection).await;
+    pointercrate_test::demonlist::add_simple_record(95, loser.id, demon1, RecordStatus::Approved, &mut connection).await;
+    pointercrate_test::demonlist::add_simple_record(60, winner.id, demon2, RecordStatus::Approved, &mut connection).await;
+    pointercrate_test::demonlist::add_simple_record(100, loser.id, demon2, RecordStatus::Approved, &mut connection).await;
+
+    sqlx::query!("INSERT INTO creators (demon, creator) VALUES ($1, $2)", demon2, loser.id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE demons SET verifier = $1, publisher = $1 WHERE id = $2", loser.id, demon2)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let score_before = Player::by_id(winner.id, &mut connection).await.unwrap().score;
+
+    let merged = DatabasePlayer::merge_into(loser.id, winner.id, &mut connection).await.unwrap();
+
+    assert_eq!(merged.player.base.id, winner.id);
+    assert_eq!(merged.records.len(), 2);
+    assert!(merged.records.iter().all(|record| record.progress == 100));
+    assert!(merged.player.score > score_before);
+    assert_eq!(Player::by_id(winner.id, &mut connection).await.unwrap().score, merged.player.score);
+    assert!(merged.created.iter().any(|demon| demon.id == demon2));
+    assert!(merged.verified.iter().any(|demon| demon.id == demon2));
+    assert!(merged.published.iter().any(|demon| demon.id == demon2));
+
+    let creator = sqlx::query_scalar!("SELECT creator FROM creators WHERE demon = $1", demon2)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+    assert_eq!(creator, winner.id);
+
+    assert!(DatabasePlayer::by_id(loser.id, &mut connection).await.is_err());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_merge_into_invalid(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    assert!(matches!(
+        DatabasePlayer::merge_into(player.id + 1, player.id, &mut connection).await,
+        Err(DemonlistError::PlayerNotFound { .. })
+    ));
+    assert!(matches!(
+        DatabasePlayer::merge_into(player.id, player.id + 1, &mut connection).await,
+        Err(DemonlistError::PlayerNotFound { .. })
+    ));
+    assert!(matches!(
+        DatabasePlayer::merge_into(player.id, player.id, &mut connection).await,
+        Err(DemonlistError::SelfMerge { .. })
+    ));
+
+    // Failed merges must not touch either player
+    assert!(DatabasePlayer::by_id(player.id, &mut connection).await.is_ok());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_get_by_name(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (_, player) = create_players(&mut connection).await;
+
+    let fetched: FullPlayer = client
+        .get("/api/v1/players/by-name/STARDUST1972/")
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(fetched.player.base, player);
+
+    let result: serde_json::Value = client
+        .get("/api/v1/players/by-name/stardust1973/")
+        .expect_status(Status::NotFound)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"], 40401);
+
+    // Lookups must never create players
+    assert!(DatabasePlayer::by_name("stardust1973", &mut connection).await.is_err());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_compare_players(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let a = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let b = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, a.id, a.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Bloodbath 2", 2, 50, a.id, a.id, &mut connection).await;
+
+    pointercrate_test::demonlist::add_simple_record(100, a.id, demon1, RecordStatus::Approved, &mut connection).await;
+    pointercrate_test::demonlist::add_simple_record(100, b.id, demon1, RecordStatus::Approved, &mut connection).await;
+    pointercrate_test::demonlist::add_simple_record(100, b.id, demon2, RecordStatus::Approved, &mut connection).await;
+    // Progress records do not count as beating a demon
+    pointercrate_test::demonlist::add_simple_record(70, a.id, demon2, RecordStatus::Approved, &mut connection).await;
+
+    let comparison: serde_json::Value = clnt
+        .get(format!("/api/v1/players/compare/?a={}&b={}", a.id, b.id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(comparison["a"]["id"], a.id);
+    assert_eq!(comparison["a_only"], json!([]));
+    assert_eq!(comparison["b_only"][0]["id"], demon2);
+    assert_eq!(comparison["both"][0]["id"], demon1);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_preview_ban(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let other = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, other.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Bloodbath 2", 2, 50, other.id, other.id, &mut connection).await;
+
+    pointercrate_test::demonlist::add_simple_record(100, player.id, demon1, RecordStatus::Approved, &mut connection).await;
+    pointercrate_test::demonlist::add_simple_record(100, player.id, demon2, RecordStatus::Submitted, &mut connection).await;
+
+    let url = format!("/api/v1/players/{}/preview-ban/", player.id);
+
+    clnt.post(&url, &()).expect_status(Status::Unauthorized).execute().await;
+
+    let preview: serde_json::Value = clnt.post(&url, &()).authorize_as(&user).expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(preview["deleted_submissions"], 1);
+    assert_eq!(preview["rejected_records"], 1);
+    assert_eq!(preview["verified"][0]["id"], demon1);
+    assert_eq!(preview["published"], json!([]));
+
+    // Nothing was actually changed
+    let player = Player::by_id(player.id, &mut connection).await.unwrap();
+
+    assert!(!player.base.banned);
+    assert_eq!(preview["score"], player.score);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_national_rank(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1970", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Slaughterhouse", 2, 50, verifier.id, verifier.id, &mut connection).await;
+
+    let german1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let german2 = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+    let briton = DatabasePlayer::by_name_or_create("stardust1973", &mut connection).await.unwrap();
+
+    // The briton has the highest score, but is not part of the german ranking
+    for (player, demons) in [(german1.id, vec![demon1]), (german2.id, vec![demon2]), (briton.id, vec![demon1, demon2])] {
+        for demon in demons {
+            pointercrate_test::demonlist::add_simple_record(100, player, demon, RecordStatus::Approved, &mut connection).await;
+        }
+    }
+
+    for (player, nation) in [(german1.id, "DE"), (german2.id, "DE"), (briton.id, "GB")] {
+        sqlx::query!("UPDATE players SET nationality = $1 WHERE id = $2", nation, player)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+    }
+
+    pointercrate_demonlist::player::recompute_scores(&mut connection).await.unwrap();
+
+    let rank: serde_json::Value = client
+        .get(format!("/api/v1/players/{}/national-rank/", german2.id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(rank["rank"], 2);
+    assert_eq!(rank["nationality"]["country_code"], "DE");
+
+    let rank: serde_json::Value = client
+        .get(format!("/api/v1/players/{}/national-rank/", verifier.id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert!(rank.is_null());
+}
+
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_publisher_and_verifier_stats(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let other = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+
+    let published1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, other.id, player.id, &mut connection).await;
+    let published2 = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 50, other.id, player.id, &mut connection).await;
+    let both = pointercrate_test::demonlist::add_demon("Slaughterhouse", 3, 50, player.id, player.id, &mut connection).await;
+    let verified = pointercrate_test::demonlist::add_demon("Tartarus", 4, 50, player.id, other.id, &mut connection).await;
+    pointercrate_test::demonlist::add_demon("Acheron", 5, 50, other.id, other.id, &mut connection).await;
+
+    let publisher_stats = player.publisher_stats(&mut connection).await.unwrap();
+
+    assert_eq!(publisher_stats.count, 3);
+    assert_eq!(
+        publisher_stats.demons.iter().map(|demon| demon.id).collect::<Vec<_>>(),
+        vec![published1, published2, both]
+    );
+
+    let verifier_stats = player.verifier_stats(&mut connection).await.unwrap();
+
+    assert_eq!(verifier_stats.count, 2);
+    assert_eq!(verifier_stats.demons.iter().map(|demon| demon.id).collect::<Vec<_>>(), vec![both, verified]);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_player_search_key(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("✨ stardust 1971 ✨", &mut connection).await.unwrap();
+
+    let search_key: String = sqlx::query_scalar!("SELECT search_key AS \"search_key!\" FROM players WHERE id = $1", player.id)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(search_key, "stardust 1971");
+    assert_eq!(player.name, "✨ stardust 1971 ✨");
+
+    // Emoji stripping is disabled by default, so the plain name filter applies
+    let players: Vec<Player> = client
+        .get("/api/v1/players/?name_contains=stardust")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+    assert_eq!(players.len(), 1);
+    assert_eq!(players[0].base.id, player.id);
+}
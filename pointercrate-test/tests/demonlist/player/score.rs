@@ -2,8 +2,9 @@
 
 use pointercrate_core::etag::Taggable;
 use pointercrate_demonlist::{
-    player::{DatabasePlayer, FullPlayer},
-    record::FullRecord,
+    demon::{current_list, DefaultScoreFormula, Difficulty, FullDemon},
+    player::{DatabasePlayer, FullPlayer, Player, RankThreshold, TierScore},
+    record::{FullRecord, RecordStatus},
     LIST_MODERATOR,
 };
 use rocket::http::Status;
@@ -77,6 +78,14 @@ async fn nationality_score(iso_country_code: &str, connection: &mut PgConnection
         .score
 }
 
+async fn score_of(player_id: i32, connection: &mut PgConnection) -> f64 {
+    sqlx::query!("SELECT score FROM players WHERE id = $1", player_id)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap()
+        .score
+}
+
 async fn subdivision_score(nation: &str, iso_code: &str, connection: &mut PgConnection) -> f64 {
     sqlx::query!(
         "SELECT score FROM subdivisions WHERE nation = $1 AND iso_code = $2",
@@ -233,3 +242,279 @@ pub async fn test_score_resets_if_last_record_removed(pool: Pool<Postgres>) {
         "Removal of player's last record did not reset their score to 0"
     );
 }
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_ranking_thresholds(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let demon = clnt.add_demon(&helper, "Bloodbath", 1, 100, "stardust1972", "stardust1972").await;
+    let verifier = Player::by_id(demon.demon.verifier.id, &mut connection).await.unwrap();
+
+    // Only the verifier has any points
+    let thresholds = pointercrate_demonlist::player::ranking_thresholds(&[1, 2], &mut connection).await.unwrap();
+
+    assert_eq!(
+        thresholds,
+        vec![
+            RankThreshold {
+                rank: 1,
+                score: Some(verifier.score)
+            },
+            RankThreshold { rank: 2, score: None }
+        ]
+    );
+
+    let thresholds: Vec<RankThreshold> = clnt
+        .get("/api/v1/players/ranking/thresholds/")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(thresholds.iter().map(|threshold| threshold.rank).collect::<Vec<_>>(), vec![10, 50, 100]);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_grace_period_withholds_score(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let demon = clnt.add_demon(&helper, "Bloodbath", 1, 100, "stardust1971", "stardust1971").await;
+
+    sqlx::query!(
+        "UPDATE demons SET scores_from = (NOW() AT TIME ZONE 'utc') + INTERVAL '1 hour' WHERE id = $1",
+        demon.demon.base.id
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+    pointercrate_demonlist::player::recompute_scores(&mut connection).await.unwrap();
+
+    let player: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}/", demon.demon.verifier.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(player.player.score, 0.0f64, "Demon in grace period gave points");
+
+    // Once the grace period is over, the next sweep picks it up
+    sqlx::query!(
+        "UPDATE demons SET scores_from = (NOW() AT TIME ZONE 'utc') - INTERVAL '1 hour' WHERE id = $1",
+        demon.demon.base.id
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    let player: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}/", demon.demon.verifier.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(player.player.score, 0.0f64, "Reading scores ended a grace period");
+
+    let modifications_before = sqlx::query_scalar!("SELECT COUNT(*) FROM demon_modifications WHERE id = $1", demon.demon.base.id)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert!(pointercrate_demonlist::player::end_grace_periods(&mut connection).await.unwrap());
+    assert!(!pointercrate_demonlist::player::end_grace_periods(&mut connection).await.unwrap());
+
+    let modifications_after = sqlx::query_scalar!("SELECT COUNT(*) FROM demon_modifications WHERE id = $1", demon.demon.base.id)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(modifications_before, modifications_after, "Ending a grace period was recorded as a demon modification");
+
+    let player: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}/", demon.demon.verifier.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_ne!(player.player.score, 0.0f64, "Demon did not give points after grace period ended");
+}
+
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_score_by_tier(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+    pointercrate_test::demonlist::add_demon("Slaughterhouse", 2, 50, verifier.id, verifier.id, &mut connection).await;
+    let extended = pointercrate_test::demonlist::add_demon("Cataclysm", 60, 100, verifier.id, verifier.id, &mut connection).await;
+
+    sqlx::query!("UPDATE demons SET difficulty = 'insane' WHERE id = $1", extended)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+    pointercrate_demonlist::player::recompute_scores(&mut connection).await.unwrap();
+
+    let tiers: Vec<TierScore> = clnt
+        .get(format!("/api/v1/players/{}/score-by-tier/", verifier.id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(tiers.len(), 2);
+    assert_eq!(tiers[0].difficulty, Difficulty::Extreme);
+    assert_eq!(tiers[0].score, tiers[0].main_list_score);
+    assert_eq!(tiers[1].difficulty, Difficulty::Insane);
+    assert_eq!(tiers[1].main_list_score, 0.0);
+
+    let player = Player::by_id(verifier.id, &mut connection).await.unwrap();
+
+    assert!((tiers.iter().map(|tier| tier.score).sum::<f64>() - player.score).abs() < 1e-9);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_score_breakdown(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Slaughterhouse", 2, 50, verifier.id, verifier.id, &mut connection).await;
+    let demon3 = pointercrate_test::demonlist::add_demon("Sonic Wave", 3, 50, verifier.id, verifier.id, &mut connection).await;
+
+    pointercrate_test::demonlist::add_simple_record(70, player.id, demon1, RecordStatus::Approved, &mut connection).await;
+    pointercrate_test::demonlist::add_simple_record(100, player.id, demon2, RecordStatus::Approved, &mut connection).await;
+    pointercrate_test::demonlist::add_simple_record(100, player.id, demon3, RecordStatus::Rejected, &mut connection).await;
+
+    let score = player.update_score(&mut connection).await.unwrap();
+    let breakdown = player.score_breakdown(&mut connection).await.unwrap();
+
+    // The completion is worth more than the 70%, even though it is on a lower demon
+    assert_eq!(breakdown.iter().map(|(demon, _)| demon.id).collect::<Vec<_>>(), vec![demon2, demon1]);
+    assert!(breakdown[0].1 >= breakdown[1].1);
+    assert!((breakdown.iter().map(|(_, score)| score).sum::<f64>() - score).abs() < 1e-9);
+
+    // Players without any points have an empty breakdown
+    let nobody = DatabasePlayer::by_name_or_create("stardust1973", &mut connection).await.unwrap();
+
+    assert!(nobody.score_breakdown(&mut connection).await.unwrap().is_empty());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_score_update_on_requirement_change(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let demon = clnt.add_demon(&moderator, "Bloodbath", 1, 50, "stardust1972", "stardust1972").await;
+    let url = format!("/api/v2/demons/{}/", demon.demon.base.id);
+
+    let submissions = [
+        serde_json::json! {{"progress": 60, "demon": demon.demon.base.id, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "status": "Approved"}},
+        serde_json::json! {{"progress": 100, "demon": demon.demon.base.id, "player": "stardust1973", "video": "https://youtube.com/watch?v=0987654321", "status": "Approved"}},
+    ];
+    let mut records = Vec::new();
+
+    for submission in submissions {
+        let record: FullRecord = clnt
+            .post("/api/v1/records/", &submission)
+            .authorize_as(&moderator)
+            .expect_status(Status::Ok)
+            .get_success_result()
+            .await;
+
+        records.push(record.id);
+    }
+
+    let player = FullRecord::by_id(records[0], &mut connection).await.unwrap().player;
+
+    assert_ne!(score_of(player.id, &mut connection).await, 0.0);
+
+    // Raising the requirement above the 60% record makes it worthless, but does not delete it
+    let demon: FullDemon = clnt
+        .patch(&url, &serde_json::json!({"requirement": 70}))
+        .authorize_as(&moderator)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    let below_requirement: Vec<_> = demon.below_requirement.iter().map(|record| record.id).collect();
+    let listed: Vec<_> = demon.records.iter().map(|record| record.id).collect();
+
+    assert_eq!(below_requirement, vec![records[0]]);
+    assert_eq!(listed, vec![records[1]]);
+    assert_eq!(score_of(player.id, &mut connection).await, 0.0);
+
+    let record = FullRecord::by_id(records[0], &mut connection).await.unwrap();
+
+    assert_eq!(record.status, RecordStatus::Approved);
+
+    // ... and it is hidden everywhere else approved records show up
+    let profile: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}/", player.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(profile.records.is_empty());
+
+    let recent: Vec<serde_json::Value> = clnt.get("/api/v1/records/recent/").expect_status(Status::Ok).get_result().await;
+    let recent: Vec<_> = recent.iter().map(|record| record["id"].as_i64().unwrap() as i32).collect();
+
+    assert!(!recent.contains(&records[0]));
+
+    let listed: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v1/records/?demon_id={}", demon.demon.base.id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+    let listed: Vec<_> = listed.iter().map(|record| record["id"].as_i64().unwrap() as i32).collect();
+
+    assert_eq!(listed, vec![records[1]]);
+
+    // Lowering it again restores the record's score
+    let demon: FullDemon = clnt
+        .patch(&url, &serde_json::json!({"requirement": 50}))
+        .authorize_as(&moderator)
+        .header("If-Match", demon.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(demon.below_requirement.is_empty());
+    assert_eq!(demon.records.len(), 2);
+    assert_ne!(score_of(player.id, &mut connection).await, 0.0);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_consistency_score(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let verifier = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+
+    let partial = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut connection).await;
+    let full = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 60, verifier.id, verifier.id, &mut connection).await;
+    let at_requirement = pointercrate_test::demonlist::add_demon("Slaughterhouse", 3, 40, verifier.id, verifier.id, &mut connection).await;
+    let pending = pointercrate_test::demonlist::add_demon("Tartarus", 4, 50, verifier.id, verifier.id, &mut connection).await;
+
+    pointercrate_test::demonlist::add_simple_record(75, player.id, partial, RecordStatus::Approved, &mut connection).await;
+    pointercrate_test::demonlist::add_simple_record(100, player.id, full, RecordStatus::Approved, &mut connection).await;
+    pointercrate_test::demonlist::add_simple_record(40, player.id, at_requirement, RecordStatus::Approved, &mut connection).await;
+    // Only approved records count
+    pointercrate_test::demonlist::add_simple_record(90, player.id, pending, RecordStatus::Submitted, &mut connection).await;
+
+    let demons = current_list(&mut connection).await.unwrap();
+    let full_score = |id: i32| demons.iter().find(|demon| demon.base.id == id).unwrap().score(&DefaultScoreFormula, 100);
+
+    // 75% on a 50% requirement is halfway there, 40% on a 40% requirement is no way there at all
+    let expected = 0.5 * full_score(partial) + full_score(full);
+    let consistency = player.consistency_score(&DefaultScoreFormula, &mut connection).await.unwrap();
+
+    assert!((consistency - expected).abs() < 1e-9, "expected {}, got {}", expected, consistency);
+
+    // Independent of the stored score, which uses a different formula for partial progress
+    let stored = player.update_score(&mut connection).await.unwrap();
+    assert!((consistency - stored).abs() > 1e-9);
+}
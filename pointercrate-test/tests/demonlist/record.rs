@@ -1,14 +1,20 @@
 use pointercrate_core::error::PointercrateError;
 use pointercrate_core::etag::Taggable;
 use pointercrate_demonlist::{
+    demon::Difficulty,
     error::DemonlistError,
     player::{DatabasePlayer, FullPlayer},
-    record::{note::Note, FullRecord, RecordStatus},
-    LIST_HELPER, LIST_MODERATOR,
+    record::{note::Note, purge_rejected_records_older_than, FullRecord, RecordStatus, Submission, SubmissionRequirements},
+    ENJOYMENT_CURATOR, LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
+use pointercrate_demonlist_api::{SubmissionFreeze, SubmissionHook};
 use pointercrate_test::{demonlist::add_simple_record, user::system_user_with_perms};
 use rocket::http::Status;
 use sqlx::{PgConnection, Pool, Postgres};
+use std::{
+    error::Error,
+    sync::{Arc, Mutex},
+};
 
 #[sqlx::test(migrations = "../migrations")]
 async fn paginate_records_unauthorized(pool: Pool<Postgres>) {
@@ -93,6 +99,35 @@ async fn setup_pagination_tests(connection: &mut PgConnection) -> (i32, i32, i32
     (player1.id, r1, r2, r3)
 }
 
+#[sqlx::test(migrations = "../migrations")]
+async fn paginate_records_by_progress_range(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let (_, _r1, r2, r3) = setup_pagination_tests(&mut connection).await;
+    let user = system_user_with_perms(LIST_HELPER, &mut connection).await;
+
+    let json: Vec<serde_json::Value> = clnt
+        .get("/api/v1/records/?status=REJECTED&progress_min=70&progress_max=99")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(json.len(), 1);
+    assert_eq!(json[0]["id"], r2);
+
+    // Both bounds are inclusive
+    let json: Vec<serde_json::Value> = clnt
+        .get("/api/v1/records/?status=REJECTED&progress_min=70&progress_max=100")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(json.len(), 2);
+    assert_eq!(json[1]["id"], r3);
+}
+
 #[sqlx::test(migrations = "../migrations")]
 async fn unauthed_submit_for_player_with_locked_submission(pool: Pool<Postgres>) {
     let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
@@ -154,6 +189,40 @@ async fn test_submit_successful(pool: Pool<Postgres>) {
         .await;
 }
 
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submit_with_enjoyment(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+
+    let submission = serde_json::json! {{"progress": 60, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com", "enjoyment": 7}};
+
+    let submitted: FullRecord = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    let stored = sqlx::query!("SELECT enjoyment FROM records WHERE id = $1", submitted.id)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap()
+        .enjoyment;
+
+    assert_eq!(stored, Some(7));
+
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}/", submitted.id))
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.enjoyment, Some(7));
+}
+
 #[sqlx::test(migrations = "../migrations")]
 async fn test_no_submitter_info_on_unauthed_get(pool: Pool<Postgres>) {
     let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
@@ -196,6 +265,154 @@ async fn test_no_raw_footage_on_unauthed_get(pool: Pool<Postgres>) {
     assert_eq!(record.raw_footage.as_deref(), Some(raw_footage));
 }
 
+#[sqlx::test(migrations = "../migrations")]
+async fn test_raw_footage_omitted_from_public_responses(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let raw_footage = "https://youtube.com/watch?v=0987654321";
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+
+    // Public submitters do not get to see the raw footage of their own submission echoed back either
+    let submission = serde_json::json! {{"progress": 60, "demon": demon1, "player": player1.name, "video": "https://youtube.com/watch?v=1234567890", "raw_footage": raw_footage}};
+
+    let submitted: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(submitted.get("raw_footage").is_none());
+    assert!(submitted.get("submitter").is_none());
+
+    let record_id = submitted["id"].as_i64().unwrap();
+
+    let as_helper: serde_json::Value = clnt
+        .get(format!("/api/v1/records/{}/", record_id))
+        .authorize_as(&helper)
+        .get_success_result()
+        .await;
+
+    assert_eq!(as_helper["raw_footage"], raw_footage);
+
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}/", record_id))
+        .authorize_as(&helper)
+        .get_success_result()
+        .await;
+
+    clnt.patch(format!("/api/v1/records/{}/", record_id), &serde_json::json! {{"status": "approved"}})
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let as_public: serde_json::Value = clnt.get(format!("/api/v1/records/{}/", record_id)).get_success_result().await;
+
+    assert!(as_public.get("raw_footage").is_none());
+    assert!(as_public.get("approved_by").is_none());
+    assert_eq!(as_public["id"], record_id);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_approval_is_attributed(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+    let record_id = add_simple_record(100, player1.id, demon1, RecordStatus::Submitted, &mut connection).await;
+
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}/", record_id))
+        .authorize_as(&helper)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.approved_by, None);
+
+    let record: FullRecord = clnt
+        .patch(format!("/api/v1/records/{}/", record_id), &serde_json::json! {{"status": "approved"}})
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.approved_by, Some(helper.user().id));
+
+    // The approver is only known for records that are currently approved
+    let record: FullRecord = clnt
+        .patch(format!("/api/v1/records/{}/", record_id), &serde_json::json! {{"status": "rejected"}})
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.approved_by, None);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_reject_with_reason_template(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+    let record_id = add_simple_record(60, player1.id, demon1, RecordStatus::Submitted, &mut connection).await;
+
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}/", record_id))
+        .authorize_as(&helper)
+        .get_success_result()
+        .await;
+
+    // Templates can only accompany rejections
+    let json: serde_json::Value = clnt
+        .patch(format!("/api/v1/records/{}/", record_id), &serde_json::json! {{"status": "approved", "reason_template": "hacked"}})
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"], 42253);
+
+    // No templates are configured by default
+    let json: serde_json::Value = clnt
+        .patch(format!("/api/v1/records/{}/", record_id), &serde_json::json! {{"status": "rejected", "reason_template": "hacked"}})
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"], 42252);
+    assert_eq!(FullRecord::by_id(record_id, &mut connection).await.unwrap().status, RecordStatus::Submitted);
+
+    let templates = |reason: &str| (reason == "hacked").then(|| "{player}'s {progress}% on {demon} was hacked".to_string());
+    let patch = serde_json::from_value(serde_json::json! {{"status": "rejected", "reason_template": "hacked"}}).unwrap();
+
+    let record = FullRecord::by_id(record_id, &mut connection)
+        .await
+        .unwrap()
+        .apply_patch_with(patch, templates, &mut connection)
+        .await
+        .unwrap();
+
+    assert_eq!(record.status, RecordStatus::Rejected);
+
+    let notes = pointercrate_demonlist::record::note::notes_on(record_id, false, &mut connection).await.unwrap();
+
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].content, "stardust1971's 60% on Bloodbath was hacked");
+    assert!(notes[0].is_public);
+}
+
 #[sqlx::test(migrations = "../migrations")]
 async fn test_record_note_creation_and_deletion(pool: Pool<Postgres>) {
     let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
@@ -229,6 +446,56 @@ async fn test_record_note_creation_and_deletion(pool: Pool<Postgres>) {
         .await;
 }
 
+#[sqlx::test(migrations = "../migrations")]
+async fn test_record_note_last_edited(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+    let record = add_simple_record(100, player1.id, demon1, RecordStatus::Approved, &mut connection).await;
+
+    let note: Note = clnt
+        .post(format!("/api/v1/records/{}/notes/", record), &serde_json::json! {{"content": "My Note"}})
+        .authorize_as(&helper)
+        .expect_status(Status::Created)
+        .get_success_result()
+        .await;
+
+    assert_eq!(note.last_edited, None);
+
+    let edited: Note = clnt
+        .patch(format!("/api/v1/records/{}/notes/{}/", record, note.id), &serde_json::json! {{"content": "My edited Note"}})
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    let last_edited = edited.last_edited.expect("editing the content did not set last_edited");
+
+    assert!(last_edited >= note.created_at);
+
+    // Only changes to the content count as edits
+    let published: Note = clnt
+        .patch(format!("/api/v1/records/{}/notes/{}/", record, note.id), &serde_json::json! {{"is_public": true}})
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(published.last_edited, Some(last_edited));
+
+    let notes: Vec<Note> = clnt
+        .get(format!("/api/v1/records/{}/notes/", record))
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(notes[0].last_edited, Some(last_edited));
+    assert_eq!(notes[0].editors, vec![helper.user().name.clone()]);
+}
+
 #[sqlx::test(migrations = "../migrations")]
 async fn test_record_deletion_updates_player_score(pool: Pool<Postgres>) {
     let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
@@ -269,3 +536,908 @@ async fn test_record_deletion_updates_player_score(pool: Pool<Postgres>) {
 
     assert_eq!(player.player.score, 0.0f64, "Deleting approved record failed to lower player score");
 }
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_patch_progress_verified(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+    let record_id = add_simple_record(100, player.id, demon, RecordStatus::Approved, &mut connection).await;
+
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}/", record_id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(!record.progress_verified);
+
+    let record: FullRecord = clnt
+        .patch(format!("/api/v1/records/{}/", record_id), &serde_json::json!({"progress_verified": true}))
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(record.progress_verified);
+    assert_eq!(record.status, RecordStatus::Approved);
+
+    // Changing the progress invalidates the verification
+    let record: FullRecord = clnt
+        .patch(format!("/api/v1/records/{}/", record_id), &serde_json::json!({"progress": 90}))
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(!record.progress_verified);
+
+    // Verifying in the same patch as the change is fine though
+    let record: FullRecord = clnt
+        .patch(format!("/api/v1/records/{}/", record_id), &serde_json::json!({"progress": 95, "progress_verified": true}))
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(record.progress_verified);
+
+    // As does changing the video
+    let record: FullRecord = clnt
+        .patch(
+            format!("/api/v1/records/{}/", record_id),
+            &serde_json::json!({"video": "https://youtube.com/watch?v=1234567890"}),
+        )
+        .authorize_as(&helper)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert!(!record.progress_verified);
+    assert!(!sqlx::query_scalar!("SELECT progress_verified FROM records WHERE id = $1", record_id)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_enjoyment_curator(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let curator = system_user_with_perms(ENJOYMENT_CURATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+    let record_id = add_simple_record(100, player.id, demon, RecordStatus::Approved, &mut connection).await;
+
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}/", record_id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    let patched: FullRecord = clnt
+        .patch(format!("/api/v1/records/{}/", record_id), &serde_json::json!({"enjoyment": 7}))
+        .authorize_as(&curator)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(patched.enjoyment, Some(7));
+
+    // Anything beyond the enjoyment rating still requires the usual permissions
+    clnt.patch(
+        format!("/api/v1/records/{}/", record_id),
+        &serde_json::json!({"enjoyment": 8, "progress_verified": true}),
+    )
+    .authorize_as(&curator)
+    .header("If-Match", record.etag_string())
+    .expect_status(Status::Forbidden)
+    .execute()
+    .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_patch_enjoyment(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let curator = system_user_with_perms(ENJOYMENT_CURATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+    let record_id = add_simple_record(60, player.id, demon, RecordStatus::Approved, &mut connection).await;
+
+    let record: FullRecord = clnt
+        .get(format!("/api/v1/records/{}/", record_id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    // Ratings are checked the same way as on submission
+    let json: serde_json::Value = clnt
+        .patch(format!("/api/v1/records/{}/", record_id), &serde_json::json!({"enjoyment": 11}))
+        .authorize_as(&curator)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"], DemonlistError::InvalidEnjoyment.error_code());
+
+    // Partial progress can be rated, as ENJOYMENT_REQUIRES_COMPLETION is off by default
+    let patched: FullRecord = clnt
+        .patch(format!("/api/v1/records/{}/", record_id), &serde_json::json!({"enjoyment": 4}))
+        .authorize_as(&curator)
+        .header("If-Match", record.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(patched.enjoyment, Some(4));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_cleanup_without_retention(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let moderator = system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let administrator = system_user_with_perms(LIST_ADMINISTRATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+    let record = add_simple_record(100, player.id, demon, RecordStatus::Rejected, &mut connection).await;
+
+    clnt.post("/api/v1/records/cleanup/", &())
+        .authorize_as(&moderator)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    // No retention period is configured, so nothing gets purged
+    let result: serde_json::Value = clnt
+        .post("/api/v1/records/cleanup/", &())
+        .authorize_as(&administrator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(result["purged"], 0);
+    assert!(FullRecord::by_id(record, &mut connection).await.is_ok());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_purge_rejected_records(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+
+    let mut records = Vec::new();
+
+    for (name, position, status) in [
+        ("Bloodbath", 1, RecordStatus::Rejected),
+        ("Slaughterhouse", 2, RecordStatus::Rejected),
+        ("Sonic Wave", 3, RecordStatus::Rejected),
+        ("Tartarus", 4, RecordStatus::Approved),
+        ("Acheron", 5, RecordStatus::Rejected),
+    ] {
+        let demon = pointercrate_test::demonlist::add_demon(name, position, 50, player.id, player.id, &mut connection).await;
+
+        records.push(add_simple_record(100, player.id, demon, status, &mut connection).await);
+    }
+
+    let [old, recent, touched, approved, unknown_age] = records[..] else { unreachable!() };
+
+    // The test connection has no active user, so the audit log is filled in by hand
+    for (record, days_ago) in [(old, 40), (recent, 10), (touched, 40), (approved, 40)] {
+        sqlx::query!(
+            "INSERT INTO record_additions (userid, id, time) VALUES (0, $1, (NOW() AT TIME ZONE 'utc') - make_interval(days => $2))",
+            record,
+            days_ago
+        )
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+    }
+
+    // A modification counts as the record being touched again
+    sqlx::query!(
+        "INSERT INTO record_modifications (userid, id, time) VALUES (0, $1, (NOW() AT TIME ZONE 'utc') - make_interval(days => 10))",
+        touched
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    assert_eq!(purge_rejected_records_older_than(0, &mut connection).await, Ok(0));
+    assert_eq!(purge_rejected_records_older_than(30, &mut connection).await, Ok(1));
+
+    assert!(FullRecord::by_id(old, &mut connection).await.is_err());
+
+    for kept in [recent, touched, approved, unknown_age] {
+        assert!(FullRecord::by_id(kept, &mut connection).await.is_ok());
+    }
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_oldest_pending_records(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let player2 = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+
+    let first = add_simple_record(60, player1.id, demon, RecordStatus::Submitted, &mut connection).await;
+    add_simple_record(100, player2.id, demon, RecordStatus::Approved, &mut connection).await;
+    let second = add_simple_record(70, player2.id, demon, RecordStatus::Submitted, &mut connection).await;
+
+    let (pending, _) = clnt
+        .get("/api/v1/records/oldest-pending/")
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_pagination_result::<serde_json::Value>()
+        .await;
+
+    let ids: Vec<_> = pending.iter().map(|record| record["id"].as_i64().unwrap() as i32).collect();
+
+    assert_eq!(ids, vec![first, second]);
+    assert!(pending.iter().all(|record| record["submitted_at"].is_string()));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_enjoyment_import(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let curator = system_user_with_perms(ENJOYMENT_CURATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+    let record_id = add_simple_record(100, player.id, demon, RecordStatus::Approved, &mut connection).await;
+
+    let rows = serde_json::json!([
+        {"record_id": record_id, "enjoyment": 11},
+        {"record_id": record_id + 1, "enjoyment": 5},
+        {"record_id": record_id, "enjoyment": 6},
+    ]);
+
+    clnt.post("/api/v1/records/enjoyment-import/", &rows)
+        .expect_status(Status::Unauthorized)
+        .execute()
+        .await;
+
+    let results: serde_json::Value = clnt
+        .post("/api/v1/records/enjoyment-import/", &rows)
+        .authorize_as(&curator)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(results[0]["error"]["code"], DemonlistError::InvalidEnjoyment.error_code());
+    assert_eq!(results[1]["error"]["code"], 40401);
+    assert!(results[2]["error"].is_null());
+
+    let enjoyment = sqlx::query!("SELECT enjoyment FROM records WHERE id = $1", record_id)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap()
+        .enjoyment;
+
+    assert_eq!(enjoyment, Some(6));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submit_archived_demon(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+
+    sqlx::query!("UPDATE demons SET archived = TRUE WHERE id = $1", demon)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let submission = serde_json::json! {{"progress": 60, "demon": demon, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(DemonlistError::DemonArchived.error_code() as i64));
+
+    // List mods can still add records directly
+    let submission = serde_json::json! {{"progress": 60, "demon": demon, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "status": "approved"}};
+
+    clnt.post("/api/v1/records/", &submission)
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submit_submissions_closed(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+
+    sqlx::query!("UPDATE demons SET submissions_closed = TRUE WHERE id = $1", demon)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let submission = serde_json::json! {{"progress": 60, "demon": demon, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(DemonlistError::SubmissionsClosed.error_code() as i64));
+
+    let submission = serde_json::json! {{"progress": 60, "demon": demon, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "status": "approved"}};
+
+    clnt.post("/api/v1/records/", &submission)
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+}
+
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_bulk_status_change(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let player2 = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player2.id, player2.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Bloodbath 2", 2, 50, player2.id, player2.id, &mut connection).await;
+
+    let record1 = add_simple_record(100, player1.id, demon1, RecordStatus::Submitted, &mut connection).await;
+    let record2 = add_simple_record(100, player1.id, demon2, RecordStatus::Submitted, &mut connection).await;
+    let record3 = add_simple_record(70, player2.id, demon1, RecordStatus::Submitted, &mut connection).await;
+
+    // A single unknown record makes the whole batch fail
+    let json: serde_json::Value = clnt
+        .patch("/api/v1/records/status/", &serde_json::json!({"ids": [record1, record2, 1000], "status": "approved"}))
+        .authorize_as(&user)
+        .expect_status(Status::NotFound)
+        .get_result()
+        .await;
+
+    assert_eq!(json["data"]["record_id"], 1000);
+
+    for record in [record1, record2, record3] {
+        assert_eq!(FullRecord::by_id(record, &mut connection).await.unwrap().status, RecordStatus::Submitted);
+    }
+
+    let records: Vec<FullRecord> = clnt
+        .patch("/api/v1/records/status/", &serde_json::json!({"ids": [record1, record2, record3], "status": "approved"}))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(records.len(), 3);
+
+    for record in [record1, record2, record3] {
+        assert_eq!(FullRecord::by_id(record, &mut connection).await.unwrap().status, RecordStatus::Approved);
+    }
+
+    let player: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}/", player1.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_ne!(player.player.score, 0.0);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_bulk_status_change_omits_superseded_records(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+
+    let partial = add_simple_record(70, player.id, demon, RecordStatus::Submitted, &mut connection).await;
+    let completion = add_simple_record(100, player.id, demon, RecordStatus::Submitted, &mut connection).await;
+
+    // The 70% record is approved first, and then deleted by the approval of the 100% one
+    let records: Vec<FullRecord> = clnt
+        .patch("/api/v1/records/status/", &serde_json::json!({"ids": [partial, completion], "status": "approved"}))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(records.iter().map(|record| record.id).collect::<Vec<_>>(), vec![completion]);
+    assert_eq!(records[0].status, RecordStatus::Approved);
+    assert!(matches!(
+        FullRecord::by_id(partial, &mut connection).await,
+        Err(DemonlistError::RecordNotFound { .. })
+    ));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_record_notes(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+    let record = add_simple_record(100, player1.id, demon1, RecordStatus::Approved, &mut connection).await;
+    let record = FullRecord::by_id(record, &mut connection).await.unwrap();
+
+    let result = record.add_note("  \n ".to_string(), helper.user(), &mut connection).await;
+
+    assert!(matches!(result, Err(DemonlistError::NoteEmpty)), "{:?}", result);
+
+    let first = record.add_note("First".to_string(), helper.user(), &mut connection).await.unwrap();
+    let second = record.add_note("Second".to_string(), helper.user(), &mut connection).await.unwrap();
+
+    assert_eq!(first.author.as_ref(), Some(&helper.user().name));
+    assert!(!first.is_public);
+
+    let notes = record.notes(&mut connection).await.unwrap();
+
+    assert_eq!(notes.iter().map(|note| note.id).collect::<Vec<_>>(), vec![first.id, second.id]);
+    assert_eq!(notes.iter().map(|note| note.content.as_str()).collect::<Vec<_>>(), vec!["First", "Second"]);
+    assert!(notes.iter().all(|note| note.author.as_ref() == Some(&helper.user().name)));
+    assert_eq!(notes[0].created_at, first.created_at);
+    assert!(notes[0].created_at <= notes[1].created_at);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submissions_frozen(pool: Pool<Postgres>) {
+    let (clnt, mut connection) =
+        pointercrate_test::demonlist::setup_rocket_with(pool, |rocket| rocket.manage(SubmissionFreeze(true))).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+
+    let submission = serde_json::json! {{"progress": 60, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::ServiceUnavailable)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"], 50302);
+
+    // List staff can still add records
+    clnt.post("/api/v1/records/", &submission)
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submit_with_status_requires_helper(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+    let submission = serde_json::json! {{"progress": 100, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "status": "approved"}};
+
+    clnt.post("/api/v1/records/", &submission)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    clnt.post("/api/v1/records/", &submission)
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    let records = sqlx::query_scalar!("SELECT COUNT(*) FROM records")
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(records, Some(0));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_staff_added_records_without_video_are_exempt(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Slaughterhouse", 2, 50, player1.id, player1.id, &mut connection).await;
+
+    let added = clnt
+        .post("/api/v1/records/", &serde_json::json! {{"progress": 100, "demon": demon1, "player": "stardust1972", "status": "approved"}})
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_success_result::<FullRecord>()
+        .await;
+
+    let submitted = clnt
+        .post("/api/v1/records/", &serde_json::json! {{"progress": 100, "demon": demon2, "player": "stardust1972", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}})
+        .expect_status(Status::Ok)
+        .get_result::<serde_json::Value>()
+        .await;
+
+    let exempt = |id: i32| sqlx::query_scalar!("SELECT video_exempt FROM records WHERE id = $1", id);
+
+    assert!(exempt(added.id).fetch_one(&mut *connection).await.unwrap());
+    assert!(!exempt(submitted["data"]["id"].as_i64().unwrap() as i32)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap());
+}
+
+/// Validates the given submission against the given requirements instead of the configured ones
+async fn validate_submission(
+    submission: serde_json::Value, requirements: &SubmissionRequirements, connection: &mut PgConnection,
+) -> Result<(), DemonlistError> {
+    let submission: Submission = serde_json::from_value(submission).unwrap();
+
+    submission
+        .normalize(&mut *connection)
+        .await?
+        .validate_with(requirements, connection)
+        .await
+        .map(|_| ())
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submission_requirements_per_difficulty(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let extreme = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+    let insane = pointercrate_test::demonlist::add_demon("Slaughterhouse", 2, 50, player1.id, player1.id, &mut connection).await;
+
+    sqlx::query!("UPDATE demons SET difficulty = 'insane' WHERE id = $1", insane)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let requirements = SubmissionRequirements {
+        video: false,
+        video_for_completions: false,
+        enjoyment_requires_completion: false,
+        raw_footage_for: vec![Difficulty::Extreme],
+        enjoyment_for: vec![Difficulty::Insane],
+    };
+
+    let submission = |demon: i32| serde_json::json! {{"progress": 100, "demon": demon, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890"}};
+
+    assert_eq!(
+        validate_submission(submission(extreme), &requirements, &mut connection).await,
+        Err(DemonlistError::RawRequired)
+    );
+    assert_eq!(
+        validate_submission(submission(insane), &requirements, &mut connection).await,
+        Err(DemonlistError::EnjoymentRequired)
+    );
+
+    let mut rated = submission(insane);
+    rated["enjoyment"] = 7.into();
+
+    assert_eq!(validate_submission(rated, &requirements, &mut connection).await, Ok(()));
+
+    // List staff adding records directly need not provide either
+    let mut added = submission(extreme);
+    added["status"] = "approved".into();
+
+    assert_eq!(validate_submission(added, &requirements, &mut connection).await, Ok(()));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_completion_video_requirement(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+
+    let requirements = SubmissionRequirements {
+        video: false,
+        video_for_completions: true,
+        enjoyment_requires_completion: false,
+        raw_footage_for: Vec::new(),
+        enjoyment_for: Vec::new(),
+    };
+
+    let submission = |progress: i16| serde_json::json! {{"progress": progress, "demon": demon1, "player": "stardust1971"}};
+
+    assert_eq!(
+        validate_submission(submission(100), &requirements, &mut connection).await,
+        Err(DemonlistError::CompletionVideoRequired)
+    );
+    assert_eq!(validate_submission(submission(60), &requirements, &mut connection).await, Ok(()));
+
+    let mut with_video = submission(100);
+    with_video["video"] = "https://youtube.com/watch?v=1234567890".into();
+
+    assert_eq!(validate_submission(with_video, &requirements, &mut connection).await, Ok(()));
+
+    // A general video requirement takes precedence
+    let requirements = SubmissionRequirements { video: true, ..requirements };
+
+    assert_eq!(
+        validate_submission(submission(100), &requirements, &mut connection).await,
+        Err(DemonlistError::VideoRequired)
+    );
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submit_reports_all_invalid_fields(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+    let submission = serde_json::json! {{"progress": 30, "demon": demon1, "player": "stardust1971", "video": "not a video", "raw_footage": "not a url either", "enjoyment": 11}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"], 42260);
+
+    let problems = json["data"].as_array().unwrap();
+
+    assert_eq!(
+        problems
+            .iter()
+            .map(|problem| problem["field"].as_str().unwrap())
+            .collect::<Vec<_>>(),
+        vec!["progress", "enjoyment", "video", "raw_footage"]
+    );
+    assert_eq!(
+        problems.iter().map(|problem| problem["code"].as_i64().unwrap()).collect::<Vec<_>>(),
+        vec![42215, 42236, 40001, 42233]
+    );
+    assert_eq!(problems[0]["data"]["requirement"], 50);
+
+    // A single problem is still reported on its own
+    let submission = serde_json::json! {{"progress": 30, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"], 42215);
+
+    // Video URLs rejected by the generic URL validation are still attributed to the video
+    let submission = serde_json::json! {{"progress": 30, "demon": demon1, "player": "stardust1971", "video": "ftp://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"], 42260);
+    assert_eq!(json["data"][1]["field"], "video");
+    assert_eq!(json["data"][1]["code"], 42222);
+}
+
+/// Submission hook that remembers which records it was called for, optionally failing each time
+struct RecordingHook {
+    submitted: Arc<Mutex<Vec<i32>>>,
+    fail: bool,
+}
+
+#[rocket::async_trait]
+impl SubmissionHook for RecordingHook {
+    async fn on_submitted(&self, record: &FullRecord) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.submitted.lock().unwrap().push(record.id);
+
+        if self.fail {
+            return Err("webhook unreachable".into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Waits for the background task running the submission hook to be done with the given number of records
+async fn hook_calls(submitted: &Mutex<Vec<i32>>, expected: usize) -> Vec<i32> {
+    for _ in 0..100 {
+        if submitted.lock().unwrap().len() >= expected {
+            break;
+        }
+
+        rocket::tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    submitted.lock().unwrap().clone()
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submission_hook(pool: Pool<Postgres>) {
+    let submitted = Arc::new(Mutex::new(Vec::new()));
+    let hook = RecordingHook {
+        submitted: Arc::clone(&submitted),
+        fail: false,
+    };
+
+    let (clnt, mut connection) =
+        pointercrate_test::demonlist::setup_rocket_with(pool, |rocket| rocket.manage(Arc::new(hook) as Arc<dyn SubmissionHook>)).await;
+
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+
+    let submission = serde_json::json! {{"progress": 60, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}};
+
+    let record: FullRecord = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    // Failed submissions do not trigger the hook
+    let submission =
+        serde_json::json! {{"progress": 30, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=0987654321"}};
+
+    clnt.post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .execute()
+        .await;
+
+    // Neither do records staff add directly
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let submission = serde_json::json! {{"progress": 100, "demon": demon1, "player": "stardust1972", "video": "https://youtube.com/watch?v=0987654321", "status": "approved"}};
+
+    clnt.post("/api/v1/records/", &submission)
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    assert_eq!(hook_calls(&submitted, 2).await, vec![record.id]);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_failing_submission_hook(pool: Pool<Postgres>) {
+    let submitted = Arc::new(Mutex::new(Vec::new()));
+    let hook = RecordingHook {
+        submitted: Arc::clone(&submitted),
+        fail: true,
+    };
+
+    let (clnt, mut connection) =
+        pointercrate_test::demonlist::setup_rocket_with(pool, |rocket| rocket.manage(Arc::new(hook) as Arc<dyn SubmissionHook>)).await;
+
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+
+    let submission = serde_json::json! {{"progress": 60, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}};
+
+    let record: FullRecord = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(hook_calls(&submitted, 1).await, vec![record.id]);
+    assert!(FullRecord::by_id(record.id, &mut connection).await.is_ok());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_record_export(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let admin = system_user_with_perms(LIST_ADMINISTRATOR, &mut connection).await;
+    let moderator = system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let player2 = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+
+    let seeded = vec![
+        add_simple_record(100, player1.id, demon, RecordStatus::Approved, &mut connection).await,
+        add_simple_record(60, player2.id, demon, RecordStatus::Approved, &mut connection).await,
+        add_simple_record(80, player2.id, demon, RecordStatus::Submitted, &mut connection).await,
+    ];
+
+    clnt.get("/api/v1/records/export/")
+        .authorize_as(&moderator)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    let body = clnt
+        .get("/api/v1/records/export/")
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .expect_header("Content-Type", "application/x-ndjson")
+        .execute()
+        .await
+        .into_string()
+        .await
+        .unwrap();
+
+    let exported: Vec<FullRecord> = body.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+    assert_eq!(exported.len(), seeded.len());
+    assert_eq!(exported.iter().map(|record| record.id).collect::<Vec<_>>(), seeded);
+    assert_eq!(exported[2].status, RecordStatus::Submitted);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_recently_approved_records(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let player2 = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 50, player1.id, player1.id, &mut connection).await;
+
+    let oldest = add_simple_record(100, player1.id, demon1, RecordStatus::Approved, &mut connection).await;
+    let newest = add_simple_record(100, player1.id, demon2, RecordStatus::Approved, &mut connection).await;
+    let middle = add_simple_record(80, player2.id, demon1, RecordStatus::Approved, &mut connection).await;
+    // Approved at the same time as `middle`, so it comes before it by virtue of the higher id
+    let tied = add_simple_record(90, player2.id, demon2, RecordStatus::Approved, &mut connection).await;
+    // Neither of these may show up: One is not approved, the other has no known approval time
+    add_simple_record(70, player2.id, demon2, RecordStatus::Submitted, &mut connection).await;
+    add_simple_record(60, player1.id, demon1, RecordStatus::Approved, &mut connection).await;
+
+    for (record, approved_at) in [
+        (oldest, "2010-06-01 12:00:00"),
+        (middle, "2020-06-01 12:00:00"),
+        (tied, "2020-06-01 12:00:00"),
+        (newest, "2024-06-01 12:00:00"),
+    ] {
+        sqlx::query!("UPDATE records SET approved_at = $1::TEXT::TIMESTAMP WHERE id = $2", approved_at, record)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+    }
+
+    let ids = |records: &[serde_json::Value]| records.iter().map(|record| record["id"].as_i64().unwrap() as i32).collect::<Vec<_>>();
+
+    let records: Vec<serde_json::Value> = clnt.get("/api/v1/records/recent/").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(ids(&records), vec![newest, tied, middle, oldest]);
+    assert_eq!(records[0]["demon"]["id"], demon2);
+    assert_eq!(records[0]["player"]["id"], player1.id);
+    assert_eq!(records[0]["approved_at"], "2024-06-01T12:00:00");
+
+    // Paging goes back in time, continuing right after the approval time and id of the last record
+    let records: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v1/records/recent/?after_approved_at=2024-06-01T12:00:00&after_id={}&limit=2", newest))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(ids(&records), vec![tied, middle]);
+
+    // Records approved at the same time as the cursor are not skipped
+    let records: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v1/records/recent/?after_approved_at=2020-06-01T12:00:00&after_id={}", tied))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(ids(&records), vec![middle, oldest]);
+
+    clnt.get("/api/v1/records/recent/?limit=0")
+        .expect_status(Status::UnprocessableEntity)
+        .execute()
+        .await;
+}
@@ -0,0 +1,188 @@
+use pointercrate_core::error::CoreError;
+use pointercrate_demonlist::{
+    error::DemonlistError, player::DatabasePlayer, record::RecordStatus, submitter::Submitter, LIST_ADMINISTRATOR, LIST_HELPER,
+    LIST_MODERATOR,
+};
+use pointercrate_demonlist_api::SubmitterHourlyLimit;
+use pointercrate_test::{demonlist::add_simple_record, user::system_user_with_perms};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+use std::{net::IpAddr, str::FromStr, time::Duration};
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submitter_stats(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = system_user_with_perms(LIST_ADMINISTRATOR, &mut connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let player2 = DatabasePlayer::by_name_or_create("stardust1972", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Slaughterhouse", 2, 50, player1.id, player1.id, &mut connection).await;
+
+    add_simple_record(100, player1.id, demon1, RecordStatus::Approved, &mut connection).await;
+    add_simple_record(60, player1.id, demon2, RecordStatus::Rejected, &mut connection).await;
+    add_simple_record(70, player2.id, demon1, RecordStatus::Submitted, &mut connection).await;
+
+    let stats: Vec<serde_json::Value> = clnt
+        .get("/api/v1/submitters/stats/?since=2000-01-01T00:00:00")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    // All records were added by the system submitter
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0]["submissions"], 3);
+    assert_eq!(stats[0]["approved"], 1);
+    assert_eq!(stats[0]["rejected"], 1);
+    assert_eq!(stats[0]["unique_players"], 2);
+
+    let stats: Vec<serde_json::Value> = clnt
+        .get("/api/v1/submitters/stats/?since=3000-01-01T00:00:00")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert!(stats.is_empty());
+
+    // Both accesses end up in the audit log
+    let accesses = sqlx::query_scalar!("SELECT COUNT(*) FROM submitter_stats_accesses WHERE userid = $1", user.user().id)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(accesses, Some(2));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submitter_stats_requires_admin(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+
+    clnt.get("/api/v1/submitters/stats/?since=2000-01-01T00:00:00")
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submission_limit(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let submitter = Submitter::by_ip(IpAddr::from_str("127.0.0.1").unwrap(), &mut connection)
+        .await
+        .unwrap()
+        .unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Slaughterhouse", 2, 50, player.id, player.id, &mut connection).await;
+    let demon3 = pointercrate_test::demonlist::add_demon("Sonic Wave", 3, 50, player.id, player.id, &mut connection).await;
+
+    add_simple_record(100, player.id, demon1, RecordStatus::Submitted, &mut connection).await;
+    add_simple_record(100, player.id, demon2, RecordStatus::Submitted, &mut connection).await;
+
+    assert!(submitter.check_submission_limit(3, &mut connection).await.is_ok());
+
+    add_simple_record(100, player.id, demon3, RecordStatus::Submitted, &mut connection).await;
+
+    match submitter.check_submission_limit(3, &mut connection).await {
+        Err(DemonlistError::Core(CoreError::Ratelimited { remaining, .. })) => {
+            assert!(remaining <= Duration::from_secs(3600));
+            assert!(remaining >= Duration::from_secs(3500)); // the records were all just added
+        },
+        result => panic!("Expected CoreError::Ratelimited, got {:?}", result),
+    }
+
+    // A limit of 0 disables the check
+    assert!(submitter.check_submission_limit(0, &mut connection).await.is_ok());
+
+    // Submissions older than an hour do not count
+    sqlx::query!("UPDATE record_additions SET time = time - INTERVAL '2 hours'")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    assert!(submitter.check_submission_limit(3, &mut connection).await.is_ok());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_banned_submitter(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let mut submitter = Submitter::by_ip(IpAddr::from_str("127.0.0.1").unwrap(), &mut connection)
+        .await
+        .unwrap()
+        .unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+    let existing = add_simple_record(100, player.id, demon, RecordStatus::Submitted, &mut connection).await;
+
+    let submission = serde_json::json! {{"progress": 60, "demon": demon, "player": "stardust1972", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}};
+
+    submitter.set_banned(true, &mut connection).await.unwrap();
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::Forbidden)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"], 40304);
+
+    // Banning does not touch what was submitted before
+    let still_there = sqlx::query_scalar!("SELECT EXISTS(SELECT 1 FROM records WHERE id = $1)", existing)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(still_there, Some(true));
+
+    submitter.set_banned(false, &mut connection).await.unwrap();
+
+    clnt.post("/api/v1/records/", &submission)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_submission_limit_endpoint(pool: Pool<Postgres>) {
+    let (clnt, mut connection) =
+        pointercrate_test::demonlist::setup_rocket_with(pool, |rocket| rocket.manage(SubmitterHourlyLimit(2))).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut connection).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Slaughterhouse", 2, 50, player.id, player.id, &mut connection).await;
+    let demon3 = pointercrate_test::demonlist::add_demon("Sonic Wave", 3, 50, player.id, player.id, &mut connection).await;
+
+    let submission = |player: &str| serde_json::json! {{"progress": 100, "demon": demon3, "player": player, "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}};
+
+    // Records added like this count as submitted from the IP the test client uses
+    add_simple_record(100, player.id, demon1, RecordStatus::Submitted, &mut connection).await;
+
+    clnt.post("/api/v1/records/", &submission("stardust1972"))
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    add_simple_record(100, player.id, demon2, RecordStatus::Submitted, &mut connection).await;
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission("stardust1973"))
+        .expect_status(Status::TooManyRequests)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"], 42900);
+
+    // List staff are exempt
+    clnt.post("/api/v1/records/", &submission("stardust1974"))
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+}
@@ -1,2 +1,3 @@
 mod demonlist;
 mod user;
+mod maintenance;
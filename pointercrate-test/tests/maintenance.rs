@@ -0,0 +1,51 @@
+use pointercrate_core_api::maintenance::{MaintenanceFairing, MAINTENANCE_HEADER};
+use pointercrate_demonlist::LIST_MODERATOR;
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_maintenance_header(pool: Pool<Postgres>) {
+    let fairing = MaintenanceFairing::new(false);
+    let maintenance = fairing.maintenance();
+
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket_with(pool, |rocket| rocket.attach(fairing)).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut connection).await;
+
+    let response = clnt.get("/api/v2/demons/").expect_status(Status::Ok).execute().await;
+
+    assert_eq!(response.headers().get_one(MAINTENANCE_HEADER), None);
+
+    // Turned on after launch, without restarting the server
+    maintenance.set_active(true);
+
+    let response = clnt.get("/api/v2/demons/").expect_status(Status::Ok).execute().await;
+
+    assert_eq!(response.headers().get_one(MAINTENANCE_HEADER), Some("true"));
+
+    let demon = serde_json::json! {{"name": "Bloodbath", "requirement": 90, "position": 1, "verifier": "Riot", "publisher": "Riot", "creators": [], "difficulty": "extreme"}};
+
+    let response = clnt
+        .post("/api/v2/demons/", &demon)
+        .authorize_as(&moderator)
+        .expect_status(Status::ServiceUnavailable)
+        .execute()
+        .await;
+
+    assert_eq!(response.headers().get_one(MAINTENANCE_HEADER), Some("true"));
+
+    maintenance.set_active(false);
+
+    let response = clnt.get("/api/v2/demons/").expect_status(Status::Ok).execute().await;
+
+    assert_eq!(response.headers().get_one(MAINTENANCE_HEADER), None);
+
+    clnt.post("/api/v2/demons/", &demon)
+        .authorize_as(&moderator)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+
+    // The endpoint requests are redirected to only exists while maintenance mode is on
+    clnt.get("/maintenance/").expect_status(Status::NotFound).execute().await;
+}
@@ -1,2 +1,3 @@
 mod login;
+mod permission;
 mod register;
@@ -0,0 +1,41 @@
+use pointercrate_user::{ADMINISTRATOR, MODERATOR};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_list_permissions(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut connection).await;
+
+    let permissions: serde_json::Value = client
+        .get("/api/v1/permissions/")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(
+        permissions,
+        serde_json::json!([
+            {"permission": MODERATOR.text_id(), "bit": MODERATOR.bit(), "implies": [], "assigns": []},
+            {"permission": ADMINISTRATOR.text_id(), "bit": ADMINISTRATOR.bit(), "implies": [MODERATOR.text_id()], "assigns": [MODERATOR.text_id()]},
+        ])
+    );
+
+    sqlx::query!(
+        "UPDATE members SET permissions = $2::INTEGER::BIT(16) WHERE member_id = $1",
+        user.user().id,
+        MODERATOR.bit() as i16
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    client
+        .get("/api/v1/permissions/")
+        .authorize_as(&user)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+}
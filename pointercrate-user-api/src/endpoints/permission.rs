@@ -0,0 +1,16 @@
+use pointercrate_core::permission::PermissionDescription;
+use pointercrate_core_api::error::Result;
+use pointercrate_user::{auth::ApiToken, ADMINISTRATOR};
+use rocket::serde::json::Json;
+
+use crate::auth::Auth;
+
+
+/// The permissions configured on this instance, to help figuring out why someone can or cannot do
+/// something
+#[rocket::get("/")]
+pub async fn list(auth: Auth<ApiToken>) -> Result<Json<Vec<PermissionDescription>>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    Ok(Json(auth.permissions.describe()))
+}
@@ -32,6 +32,7 @@ pub fn setup(mut rocket: Rocket<Build>) -> Rocket<Build> {
     rocket
         .manage(ratelimits)
         .mount("/api/v1/auth/", auth_routes)
+        .mount("/api/v1/permissions/", rocket::routes![endpoints::permission::list])
         .mount(
             "/api/v1/users/",
             rocket::routes![